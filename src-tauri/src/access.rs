@@ -0,0 +1,172 @@
+//! Permissions for callers from outside the app: scripts on the REST API and
+//! AI assistants on the MCP endpoint. Each caller gets allow, prompt or deny
+//! per operation (`settings.api.permissions`), checked here before anything
+//! is read or changed, and every attempt goes in access_log.json.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons};
+
+use crate::disk;
+use crate::log;
+use crate::settings::{self, Permission};
+use crate::storage;
+use crate::tasks::now_iso;
+
+/// Oldest entries are dropped beyond this many.
+const MAX_LOG_ENTRIES: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Caller {
+    Api,
+    Mcp,
+}
+
+impl Caller {
+    fn describe(self) -> &'static str {
+        match self {
+            Caller::Api => "A tool using the local API",
+            Caller::Mcp => "An AI assistant",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Operation {
+    Read,
+    Create,
+    Complete,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AccessDecision {
+    /// Allowed by the policy
+    Allowed,
+    /// The user allowed it when asked
+    Approved,
+    /// The user refused when asked
+    Declined,
+    /// Denied by the policy
+    Denied,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessRecord {
+    pub at: String,
+    pub caller: Caller,
+    pub operation: Operation,
+    /// What was asked for, e.g. `add "Call the bank"`
+    pub action: String,
+    pub decision: AccessDecision,
+}
+
+#[derive(Default)]
+pub struct AccessLog(Mutex<Vec<AccessRecord>>);
+
+fn get_log_path(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("access_log.json")
+}
+
+pub fn load(app: &AppHandle) -> Result<(), String> {
+    let path = get_log_path(app);
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read the access log: {}", e))?;
+
+    let records: Vec<AccessRecord> = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse the access log: {}", e))?;
+
+    *app.state::<AccessLog>().0.lock().unwrap() = records;
+    Ok(())
+}
+
+fn save(app: &AppHandle, records: &[AccessRecord]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(records)
+        .map_err(|e| format!("Failed to serialize the access log: {}", e))?;
+    disk::write_atomic(&get_log_path(app), content.as_bytes())
+        .map_err(|e| format!("Failed to save the access log: {}", e))
+}
+
+fn record(app: &AppHandle, caller: Caller, operation: Operation, action: &str, decision: AccessDecision) {
+    let log = app.state::<AccessLog>();
+    let mut records = log.0.lock().unwrap();
+    records.push(AccessRecord {
+        at: now_iso(),
+        caller,
+        operation,
+        action: action.to_string(),
+        decision,
+    });
+    let overflow = records.len().saturating_sub(MAX_LOG_ENTRIES);
+    records.drain(..overflow);
+    if let Err(e) = save(app, &records) {
+        log::error(app, e);
+    }
+}
+
+/// Asks the user whether `caller` may go ahead.
+fn confirm(app: &AppHandle, caller: Caller, action: &str) -> bool {
+    app.dialog()
+        .message(format!("{} wants to {}.", caller.describe(), action))
+        .title("Allow this?")
+        .buttons(MessageDialogButtons::OkCancelCustom("Allow".to_string(), "Deny".to_string()))
+        .blocking_show()
+}
+
+/// Checks whether `caller` may perform `operation`, asking the user if the
+/// policy says so, and logs the outcome. `action` describes the request for
+/// the dialog and the log. Blocks while the dialog is open.
+pub fn check(app: &AppHandle, caller: Caller, operation: Operation, action: &str) -> Result<(), String> {
+    let permissions = settings::current(app).api.permissions;
+    let by_operation = match caller {
+        Caller::Api => permissions.api,
+        Caller::Mcp => permissions.mcp,
+    };
+    let permission = match operation {
+        Operation::Read => by_operation.read,
+        Operation::Create => by_operation.create,
+        Operation::Complete => by_operation.complete,
+    };
+
+    let decision = match permission {
+        Permission::Allow => AccessDecision::Allowed,
+        Permission::Deny => AccessDecision::Denied,
+        Permission::Prompt if confirm(app, caller, action) => AccessDecision::Approved,
+        Permission::Prompt => AccessDecision::Declined,
+    };
+    record(app, caller, operation, action, decision);
+
+    match decision {
+        AccessDecision::Allowed | AccessDecision::Approved => Ok(()),
+        AccessDecision::Declined => Err("The user didn't allow this".to_string()),
+        AccessDecision::Denied => Err("Not allowed by the app's permission settings".to_string()),
+    }
+}
+
+/// Returns access attempts newest first.
+#[tauri::command]
+pub fn get_access_log(app: AppHandle, limit: Option<usize>) -> Vec<AccessRecord> {
+    let mut records = app.state::<AccessLog>().0.lock().unwrap().clone();
+    records.reverse();
+    records.truncate(limit.unwrap_or(MAX_LOG_ENTRIES));
+    records
+}
+
+#[tauri::command]
+pub fn clear_access_log(app: AppHandle) -> Result<(), String> {
+    let log = app.state::<AccessLog>();
+    let mut records = log.0.lock().unwrap();
+    records.clear();
+    save(&app, &records)
+}
@@ -0,0 +1,326 @@
+//! Personal analytics over the task history: when tasks actually get done,
+//! how each label's tasks fared quarter by quarter, and when a label's
+//! backlog is likely to clear at the pace it's been going. Times are local,
+//! so a heatmap reflects the user's own day.
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Timelike};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+
+use crate::clock;
+use crate::disk;
+use crate::files::{self, FilePurpose};
+use crate::periods;
+use crate::recurrence::parse_task_date;
+use crate::scheduled_exports::csv_field;
+use crate::settings::{self, RegionalSettings, WeekNumbering};
+use crate::storage;
+use crate::tasks::{self, str_field};
+
+/// Range used when the caller doesn't give a start.
+const DEFAULT_RANGE_DAYS: i64 = 90;
+
+/// Cohort for tasks without labels.
+const NO_LABEL: &str = "(no label)";
+
+/// Weeks of completions a forecast samples from.
+const FORECAST_HISTORY_WEEKS: i64 = 12;
+
+const FORECAST_TRIALS: usize = 2000;
+
+/// Trials that haven't cleared the backlog by then count as never.
+const FORECAST_MAX_WEEKS: u32 = 520;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeatmapCell {
+    /// 0 = Sunday
+    pub weekday: u32,
+    pub hour: u32,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionHeatmap {
+    pub from: String,
+    pub to: String,
+    /// `counts[weekday][hour]`, weekday 0 = Sunday
+    pub counts: Vec<Vec<u32>>,
+    pub total: u32,
+    /// The busiest cell, for scaling the colors
+    pub max: u32,
+    /// The busiest hours, most completions first
+    pub peaks: Vec<HeatmapCell>,
+}
+
+fn completed_at(task: &Value) -> Option<DateTime<Local>> {
+    str_field(task, "completedAt")
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        .map(|at| at.with_timezone(&Local))
+}
+
+/// Parses an optional `yyyy-MM-dd` range, defaulting to the last
+/// `DEFAULT_RANGE_DAYS` up to today.
+fn date_range(from: Option<&str>, to: Option<&str>) -> Result<(NaiveDate, NaiveDate), String> {
+    let parse = |value: &str| parse_task_date(value).ok_or_else(|| format!("Invalid date: {}", value));
+    let to = match to {
+        Some(to) => parse(to)?,
+        None => clock::today(),
+    };
+    let from = match from {
+        Some(from) => parse(from)?,
+        None => to - Duration::days(DEFAULT_RANGE_DAYS - 1),
+    };
+    if from > to {
+        return Err("The range has to start before it ends".to_string());
+    }
+    Ok((from, to))
+}
+
+fn heatmap(tasks: &[Value], from: NaiveDate, to: NaiveDate) -> CompletionHeatmap {
+    let mut counts = vec![vec![0u32; 24]; 7];
+    for at in tasks.iter().filter(|t| tasks::is_done(t)).filter_map(completed_at) {
+        if (from..=to).contains(&at.date_naive()) {
+            counts[at.weekday().num_days_from_sunday() as usize][at.hour() as usize] += 1;
+        }
+    }
+
+    let mut cells: Vec<HeatmapCell> = counts
+        .iter()
+        .enumerate()
+        .flat_map(|(weekday, hours)| {
+            hours.iter().enumerate().map(move |(hour, &count)| HeatmapCell {
+                weekday: weekday as u32,
+                hour: hour as u32,
+                count,
+            })
+        })
+        .filter(|cell| cell.count > 0)
+        .collect();
+    cells.sort_by_key(|cell| std::cmp::Reverse(cell.count));
+    cells.truncate(5);
+
+    CompletionHeatmap {
+        from: from.format("%Y-%m-%d").to_string(),
+        to: to.format("%Y-%m-%d").to_string(),
+        total: counts.iter().flatten().sum(),
+        max: counts.iter().flatten().copied().max().unwrap_or(0),
+        counts,
+        peaks: cells,
+    }
+}
+
+/// Completions by weekday and hour between `from` and `to` (inclusive,
+/// `yyyy-MM-dd`), by default over the last 90 days.
+#[tauri::command]
+pub fn get_completion_heatmap(
+    app: AppHandle,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<CompletionHeatmap, String> {
+    let (from, to) = date_range(from.as_deref(), to.as_deref())?;
+    let data = storage::read_task_data(&app)?;
+    Ok(heatmap(&data.tasks, from, to))
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cohort {
+    pub label: String,
+    /// Fiscal quarter the tasks were created in, e.g. `2026-Q3` or `FY2027-Q1`
+    pub quarter: String,
+    pub created: u32,
+    pub completed: u32,
+    /// Ended without being done (recurring tasks that were stopped)
+    pub cancelled: u32,
+    pub open: u32,
+}
+
+/// Groups tasks by label and the quarter they were created in. A task with
+/// several labels counts in each of them.
+fn cohorts(tasks: &[Value], regional: &RegionalSettings) -> Vec<Cohort> {
+    let mut by_key: BTreeMap<(String, String), Cohort> = BTreeMap::new();
+    for task in tasks {
+        let Some(created) = str_field(task, "createdAt").and_then(parse_task_date) else {
+            continue;
+        };
+        let quarter = periods::format_quarter(created, regional);
+        let mut labels: Vec<&str> = tasks::labels(task).collect();
+        labels.sort_unstable();
+        labels.dedup();
+        if labels.is_empty() {
+            labels.push(NO_LABEL);
+        }
+
+        for label in labels {
+            let cohort = by_key
+                .entry((label.to_string(), quarter.clone()))
+                .or_insert_with(|| Cohort {
+                    label: label.to_string(),
+                    quarter: quarter.clone(),
+                    ..Cohort::default()
+                });
+            cohort.created += 1;
+            if tasks::is_done(task) {
+                cohort.completed += 1;
+            } else if tasks::is_ended(task) {
+                cohort.cancelled += 1;
+            } else {
+                cohort.open += 1;
+            }
+        }
+    }
+    by_key.into_values().collect()
+}
+
+fn cohorts_csv(cohorts: &[Cohort]) -> String {
+    let mut csv = "label,quarter,created,completed,cancelled,open\n".to_string();
+    for cohort in cohorts {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            csv_field(&cohort.label),
+            cohort.quarter,
+            cohort.created,
+            cohort.completed,
+            cohort.cancelled,
+            cohort.open
+        ));
+    }
+    csv
+}
+
+/// Created tasks by label and quarter, with how many were completed,
+/// cancelled or are still open. Sorted by label, then quarter.
+#[tauri::command]
+pub fn get_label_cohorts(app: AppHandle) -> Result<Vec<Cohort>, String> {
+    let data = storage::read_task_data(&app)?;
+    Ok(cohorts(&data.tasks, &settings::current(&app).regional))
+}
+
+/// Writes the cohort report to `path`, which must have been chosen with
+/// `choose_file` for `csvExport`.
+#[tauri::command]
+pub fn export_label_cohorts(app: AppHandle, path: String) -> Result<(), String> {
+    let path = files::take_grant(&app, &path, FilePurpose::CsvExport)?;
+    let data = storage::read_task_data(&app)?;
+    let cohorts = cohorts(&data.tasks, &settings::current(&app).regional);
+    disk::write_atomic(&path, cohorts_csv(&cohorts).as_bytes())
+        .map_err(|e| disk::describe_io_error("Failed to export the report", &e))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Forecast {
+    pub label: String,
+    /// Open one-off tasks with the label, not counting someday ones
+    pub backlog: usize,
+    /// Completions in each of the last weeks, oldest first; the last is the
+    /// current week so far
+    pub weekly_throughput: Vec<u32>,
+    pub average_per_week: f64,
+    /// Backlog divided by the average; `None` if nothing was completed
+    pub simple_weeks: Option<f64>,
+    /// When half of the simulated futures have cleared the backlog
+    pub likely_date: Option<String>,
+    /// When 85% of them have
+    pub confident_date: Option<String>,
+}
+
+/// xorshift64*, enough to shuffle through past weeks.
+struct Rng(u64);
+
+impl Rng {
+    fn new() -> Self {
+        let seed = u64::from_le_bytes(uuid::Uuid::new_v4().as_bytes()[..8].try_into().unwrap_or([1; 8]));
+        Rng(seed | 1)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_f491_4f6c_dd1d) % n as u64) as usize
+    }
+}
+
+/// Weeks until `backlog` is done when each week repeats a random past week,
+/// per trial, sorted.
+fn simulate(backlog: usize, history: &[u32]) -> Vec<u32> {
+    let mut rng = Rng::new();
+    let mut weeks: Vec<u32> = (0..FORECAST_TRIALS)
+        .map(|_| {
+            let mut remaining = backlog as i64;
+            let mut week = 0;
+            while remaining > 0 && week < FORECAST_MAX_WEEKS {
+                remaining -= i64::from(history[rng.below(history.len())]);
+                week += 1;
+            }
+            week
+        })
+        .collect();
+    weeks.sort_unstable();
+    weeks
+}
+
+fn forecast_for(tasks: &[Value], label: &str, today: NaiveDate, weeks: WeekNumbering) -> Forecast {
+    let has_label = |task: &&Value| tasks::labels(task).any(|l| l == label);
+    let one_off = |task: &&Value| str_field(task, "type") != Some("recurring");
+
+    let backlog = tasks
+        .iter()
+        .filter(has_label)
+        .filter(one_off)
+        .filter(|t| tasks::is_open(t) && str_field(t, "status") != Some("someday"))
+        .count();
+
+    let this_week = periods::week_start(today, weeks);
+    let mut weekly_throughput = vec![0u32; FORECAST_HISTORY_WEEKS as usize];
+    for done in tasks.iter().filter(has_label).filter(one_off).filter(|t| tasks::is_done(t)) {
+        let Some(at) = completed_at(done) else {
+            continue;
+        };
+        let week = (this_week - periods::week_start(at.date_naive(), weeks)).num_days() / 7;
+        if (0..FORECAST_HISTORY_WEEKS).contains(&week) {
+            weekly_throughput[(FORECAST_HISTORY_WEEKS - 1 - week) as usize] += 1;
+        }
+    }
+
+    let total: u32 = weekly_throughput.iter().sum();
+    let average_per_week = f64::from(total) / FORECAST_HISTORY_WEEKS as f64;
+    let date_in = |weeks: u32| {
+        (weeks < FORECAST_MAX_WEEKS)
+            .then(|| (today + Duration::weeks(weeks.into())).format("%Y-%m-%d").to_string())
+    };
+
+    let (likely_date, confident_date) = match (backlog, total) {
+        (0, _) => (date_in(0), date_in(0)),
+        (_, 0) => (None, None),
+        _ => {
+            let weeks = simulate(backlog, &weekly_throughput);
+            let percentile = |p: usize| weeks[(weeks.len() * p / 100).min(weeks.len() - 1)];
+            (date_in(percentile(50)), date_in(percentile(85)))
+        }
+    };
+
+    Forecast {
+        label: label.to_string(),
+        backlog,
+        weekly_throughput,
+        average_per_week,
+        simple_weeks: (total > 0).then(|| backlog as f64 / average_per_week),
+        likely_date,
+        confident_date,
+    }
+}
+
+/// Estimates when the open tasks with `label` would all be done, from how
+/// many were completed each week over the last 12.
+#[tauri::command]
+pub fn forecast(app: AppHandle, label: String) -> Result<Forecast, String> {
+    let data = storage::read_task_data(&app)?;
+    let weeks = settings::current(&app).regional.week_numbering;
+    Ok(forecast_for(&data.tasks, &label, clock::today(), weeks))
+}
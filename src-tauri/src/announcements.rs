@@ -0,0 +1,87 @@
+//! Accessibility announcements: short, already localized sentences about
+//! things that happen in the background, emitted as `a11y-announcement` for
+//! the frontend to put in a screen-reader live region. Errors belong in an
+//! assertive region, the rest in a polite one.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::clock;
+use crate::locale;
+
+pub const ANNOUNCEMENT_EVENT: &str = "a11y-announcement";
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AnnouncementKind {
+    /// A reminder fired for a task with a due date
+    DueSoon,
+    /// A reminder fired for a task without one
+    Reminder,
+    /// A focus timer run ended and was tracked
+    TimerFinished,
+    SyncFailed,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Announcement {
+    pub kind: AnnouncementKind,
+    pub severity: Severity,
+    /// What to read out, in the configured locale
+    pub message: String,
+    pub task_id: Option<String>,
+}
+
+fn fill(template: &str, values: &[(&str, &str)]) -> String {
+    values
+        .iter()
+        .fold(template.to_string(), |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+}
+
+fn emit(app: &AppHandle, kind: AnnouncementKind, severity: Severity, task_id: Option<&str>, values: &[(&str, &str)]) {
+    let announcement = Announcement {
+        kind,
+        severity,
+        message: fill(locale::current(app).announcement(kind), values),
+        task_id: task_id.map(str::to_string),
+    };
+    app.emit(ANNOUNCEMENT_EVENT, announcement).ok();
+}
+
+/// A reminder fired; mentions when the task is due if it has a date.
+pub fn reminder(app: &AppHandle, task_id: &str, title: &str, due: Option<NaiveDate>) {
+    match due {
+        Some(due) => {
+            let today = clock::today();
+            let when = locale::current(app).format_relative(due, today);
+            let severity = if due < today { Severity::Warning } else { Severity::Info };
+            emit(app, AnnouncementKind::DueSoon, severity, Some(task_id), &[("task", title), ("when", &when)]);
+        }
+        None => emit(app, AnnouncementKind::Reminder, Severity::Info, Some(task_id), &[("task", title)]),
+    }
+}
+
+pub fn timer_finished(app: &AppHandle, task_id: &str, title: &str, seconds: i64) {
+    let duration = locale::current(app).format_duration(seconds / 60);
+    emit(
+        app,
+        AnnouncementKind::TimerFinished,
+        Severity::Info,
+        Some(task_id),
+        &[("task", title), ("duration", &duration)],
+    );
+}
+
+pub fn sync_failed(app: &AppHandle, source: &str, error: &str) {
+    emit(app, AnnouncementKind::SyncFailed, Severity::Error, None, &[("source", source), ("error", error)]);
+}
@@ -0,0 +1,668 @@
+//! An opt-in REST API on 127.0.0.1 for scripts and third-party tools. Routes
+//! are versioned under `/v1/` and described by an OpenAPI document generated
+//! from the types below, served at `/v1/openapi.json`.
+//!
+//! Every other route needs `Authorization: Bearer <token>`, where the token
+//! is created on first use and kept in the keychain (and in memory, so
+//! requests don't each go to the keychain). The same server hosts
+//! the WebSocket in `events.rs` and, when enabled, the MCP endpoint in
+//! `mcp.rs`. What a token holder may do is up to `settings.api.permissions`
+//! (see `access.rs`); refused requests get 403.
+
+use schemars::gen::SchemaSettings;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::access::{self, Caller, Operation};
+use crate::events::{self, TaskEvent};
+use crate::http;
+use crate::log;
+use crate::mcp;
+use crate::metrics;
+use crate::oauth;
+use crate::paste;
+use crate::reminders::ReminderScheduler;
+use crate::secrets;
+use crate::settings;
+use crate::storage;
+use crate::tasks::{self, str_field};
+
+const TOKEN_SECRET: &str = "api-token";
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// A switchback as the API returns it. Fields the API doesn't describe are
+/// passed through as they are.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiTask {
+    pub id: String,
+    pub title: String,
+    /// `one-off` or `recurring`
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// `not-started`, `in-progress`, `blocked` or `done`
+    pub status: String,
+    /// `p0` (highest) to `p4`
+    pub priority: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stakeholders: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_minutes: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// The body of `POST /v1/tasks`. Anything left out gets the app's defaults.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NewTask {
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// `YYYY-MM-DD`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stakeholders: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_minutes: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+}
+
+/// The body of `POST /v1/capture`: a page the browser extension saves.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CapturedPage {
+    pub url: String,
+    #[serde(default)]
+    pub title: String,
+    /// Text selected on the page, if any
+    #[serde(default)]
+    pub selection: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskList {
+    pub tasks: Vec<ApiTask>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedTask {
+    pub task: ApiTask,
+    /// Ids of open tasks with a similar title
+    pub possible_duplicate_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiError {
+    pub error: String,
+}
+
+pub struct Request {
+    method: String,
+    path: String,
+    query: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn query_param(&self, name: &str) -> Option<String> {
+        self.query.split('&').find_map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (key == name).then(|| http::percent_decode(&value.replace('+', " ")))?
+        })
+    }
+}
+
+struct Response {
+    status: &'static str,
+    body: Value,
+    /// Plain text sent as is instead of `body`, e.g. for `/v1/metrics`
+    text: Option<String>,
+}
+
+impl Response {
+    fn ok(body: impl Serialize) -> Self {
+        Self::with_status("200 OK", body)
+    }
+
+    fn with_status(status: &'static str, body: impl Serialize) -> Self {
+        let body = serde_json::to_value(body).unwrap_or(Value::Null);
+        Self { status, body, text: None }
+    }
+
+    fn text(text: String) -> Self {
+        Self { status: "200 OK", body: Value::Null, text: Some(text) }
+    }
+
+    fn error(status: &'static str, message: impl Into<String>) -> Self {
+        Self::with_status(status, ApiError { error: message.into() })
+    }
+}
+
+/// The API token once it's been read from the keychain.
+#[derive(Default)]
+pub struct ApiToken(Mutex<Option<String>>);
+
+/// The API token, created the first time it's asked for.
+pub fn token(app: &AppHandle) -> Result<String, String> {
+    let state = app.state::<ApiToken>();
+    let mut cached = state.0.lock().unwrap();
+    if let Some(token) = cached.as_ref() {
+        return Ok(token.clone());
+    }
+    let token = match secrets::get_secret(TOKEN_SECRET)? {
+        Some(token) => token,
+        None => {
+            let token = oauth::random_token();
+            secrets::set_secret(TOKEN_SECRET, Some(&token))?;
+            token
+        }
+    };
+    *cached = Some(token.clone());
+    Ok(token)
+}
+
+/// Compares every byte rather than stopping at the first difference, so
+/// response times don't tell a guesser how much of a token was right.
+fn same_token(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given.bytes().zip(expected.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn is_token(app: &AppHandle, given: Option<&str>) -> Result<bool, String> {
+    let expected = token(app)?;
+    Ok(given.is_some_and(|given| same_token(given, &expected)))
+}
+
+/// Starts the server if it's enabled in settings.
+pub fn start(app: AppHandle) {
+    let settings = settings::current(&app).api;
+    if !settings.enabled {
+        return;
+    }
+    let listener = match TcpListener::bind(("127.0.0.1", settings.port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            log::error(&app, format!("Failed to start the API on port {}: {}", settings.port, e));
+            return;
+        }
+    };
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let app = app.clone();
+            thread::spawn(move || handle_connection(&app, stream));
+        }
+    });
+}
+
+fn read_request(stream: &TcpStream) -> Result<Request, String> {
+    let mut reader = BufReader::new(stream);
+
+    // "GET /v1/tasks?status=done HTTP/1.1"
+    let mut request_line = String::new();
+    reader
+        .read_line(&mut request_line)
+        .map_err(|e| format!("Failed to read request: {}", e))?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let target = parts.next().unwrap_or("");
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .map_err(|e| format!("Failed to read request: {}", e))?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+
+    let content_length = match headers.iter().find(|(name, _)| name.eq_ignore_ascii_case("content-length")) {
+        Some((_, value)) => value.parse().map_err(|_| "Invalid Content-Length".to_string())?,
+        None => 0,
+    };
+    if content_length > MAX_BODY_BYTES {
+        return Err("Request body is too large".to_string());
+    }
+
+    let mut body = vec![0; content_length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|e| format!("Failed to read request body: {}", e))?;
+
+    Ok(Request {
+        method,
+        path: path.to_string(),
+        query: query.to_string(),
+        headers,
+        body,
+    })
+}
+
+/// The origin of a browser extension's page, which may call the API
+/// cross-origin. Web pages get no CORS headers.
+fn extension_origin(request: &Request) -> Option<&str> {
+    const SCHEMES: [&str; 3] = ["chrome-extension://", "moz-extension://", "safari-web-extension://"];
+    request
+        .header("origin")
+        .filter(|origin| SCHEMES.iter().any(|scheme| origin.starts_with(scheme)))
+}
+
+fn write_response(stream: &mut TcpStream, response: &Response, cors_origin: Option<&str>) {
+    let (body, content_type) = match (&response.text, &response.body) {
+        (Some(text), _) => (text.clone(), PROMETHEUS_CONTENT_TYPE),
+        (None, Value::Null) => (String::new(), "application/json"),
+        (None, body) => (serde_json::to_string(body).unwrap_or_default(), "application/json"),
+    };
+    let cors = match cors_origin {
+        Some(origin) => format!(
+            "Access-Control-Allow-Origin: {}\r\nAccess-Control-Allow-Headers: Authorization, Content-Type\r\nAccess-Control-Allow-Methods: GET, POST\r\nVary: Origin\r\n",
+            origin
+        ),
+        None => String::new(),
+    };
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n",
+        response.status,
+        content_type,
+        body.len(),
+        cors
+    );
+    stream.write_all(head.as_bytes()).ok();
+    stream.write_all(body.as_bytes()).ok();
+}
+
+fn handle_connection(app: &AppHandle, mut stream: TcpStream) {
+    stream.set_read_timeout(Some(Duration::from_secs(10))).ok();
+    let request = match read_request(&stream) {
+        Ok(request) => request,
+        Err(e) => return write_response(&mut stream, &Response::error("400 Bad Request", e), None),
+    };
+
+    // Browsers can't set headers on a WebSocket, so the token may also be
+    // passed as `?token=`
+    if request.method == "GET" && request.path.trim_matches('/') == "v1/events" {
+        let authorized = is_authorized(app, &request).and_then(|ok| {
+            if ok {
+                return Ok(true);
+            }
+            is_token(app, request.query_param("token").as_deref())
+        });
+        match authorized {
+            Ok(true) => {
+                if let Some(response) = refused(app, Operation::Read, "watch your switchbacks for changes") {
+                    return write_response(&mut stream, &response, None);
+                }
+                stream.set_read_timeout(None).ok();
+                if let Err(e) = events::serve(app, stream, &request) {
+                    log::error(app, e);
+                }
+            }
+            Ok(false) => write_response(
+                &mut stream,
+                &Response::error("401 Unauthorized", "Missing or wrong API token"),
+                None,
+            ),
+            Err(e) => write_response(&mut stream, &Response::error("500 Internal Server Error", e), None),
+        }
+        return;
+    }
+
+    // CORS preflight from an extension; the real request still needs the token
+    let response = if request.method == "OPTIONS" {
+        Response::with_status("204 No Content", Value::Null)
+    } else {
+        route(app, &request)
+    };
+    write_response(&mut stream, &response, extension_origin(&request));
+}
+
+fn is_authorized(app: &AppHandle, request: &Request) -> Result<bool, String> {
+    let given = request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim);
+    is_token(app, given)
+}
+
+fn route(app: &AppHandle, request: &Request) -> Response {
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    let method = request.method.as_str();
+
+    if let (["v1", "openapi.json"], "GET") = (segments.as_slice(), method) {
+        return Response::ok(openapi_document(settings::current(app).api.port));
+    }
+
+    match is_authorized(app, request) {
+        Ok(true) => {}
+        Ok(false) => return Response::error("401 Unauthorized", "Missing or wrong API token"),
+        Err(e) => return Response::error("500 Internal Server Error", e),
+    }
+
+    let result = match (segments.as_slice(), method) {
+        (["v1", "tasks"], "GET") => list_tasks(app, request),
+        (["v1", "tasks"], "POST") => create_task(app, request),
+        (["v1", "tasks", id], "GET") => get_task(app, id),
+        (["v1", "tasks", id, "complete"], "POST") => complete_task(app, id),
+        (["v1", "capture"], "POST") => capture(app, request),
+        // Counts and timings only, so the token is enough
+        (["v1", "metrics"], "GET") => Ok(Response::text(metrics::to_prometheus(&metrics::snapshot(app)))),
+        (["mcp"], "POST") if settings::current(app).api.mcp => Ok(match mcp::handle(app, &request.body) {
+            Some(reply) => Response::ok(reply),
+            None => Response::with_status("202 Accepted", Value::Null),
+        }),
+        _ => Ok(Response::error("404 Not Found", "No such route")),
+    };
+    result.unwrap_or_else(|e| Response::error("500 Internal Server Error", e))
+}
+
+/// A 403 when the permission settings, or the user when asked, refuse.
+fn refused(app: &AppHandle, operation: Operation, action: &str) -> Option<Response> {
+    access::check(app, Caller::Api, operation, action)
+        .err()
+        .map(|e| Response::error("403 Forbidden", e))
+}
+
+fn to_api_task(task: &Value) -> Result<ApiTask, String> {
+    serde_json::from_value(task.clone()).map_err(|e| format!("Failed to read task: {}", e))
+}
+
+fn list_tasks(app: &AppHandle, request: &Request) -> Result<Response, String> {
+    if let Some(response) = refused(app, Operation::Read, "list your switchbacks") {
+        return Ok(response);
+    }
+    let data = storage::read_task_data(app)?;
+    let status = request.query_param("status");
+    let label = request.query_param("label");
+    let search = request.query_param("q").map(|q| q.to_lowercase());
+
+    let tasks = data
+        .tasks
+        .iter()
+        .filter(|task| status.as_deref().is_none_or(|status| str_field(task, "status") == Some(status)))
+        .filter(|task| label.as_deref().is_none_or(|label| tasks::labels(task).any(|l| l == label)))
+        .filter(|task| {
+            search.as_deref().is_none_or(|search| {
+                ["title", "notes"]
+                    .iter()
+                    .filter_map(|field| str_field(task, field))
+                    .any(|text| text.to_lowercase().contains(search))
+            })
+        })
+        // One hand-edited or half-migrated task shouldn't fail the whole list
+        .filter_map(|task| match to_api_task(task) {
+            Ok(task) => Some(task),
+            Err(e) => {
+                let id = tasks::task_id(task).unwrap_or("a task without an id");
+                log::error(app, format!("Leaving {} out of the API list: {}", id, e));
+                None
+            }
+        })
+        .collect();
+
+    Ok(Response::ok(TaskList { tasks }))
+}
+
+fn get_task(app: &AppHandle, id: &str) -> Result<Response, String> {
+    if let Some(response) = refused(app, Operation::Read, &format!("read switchback {}", id)) {
+        return Ok(response);
+    }
+    task_response(app, id)
+}
+
+fn task_response(app: &AppHandle, id: &str) -> Result<Response, String> {
+    let data = storage::read_task_data(app)?;
+    Ok(match data.tasks.iter().find(|task| tasks::task_id(task) == Some(id)) {
+        Some(task) => Response::ok(to_api_task(task)?),
+        None => Response::error("404 Not Found", format!("Task not found: {}", id)),
+    })
+}
+
+fn create_task(app: &AppHandle, request: &Request) -> Result<Response, String> {
+    let new_task: NewTask = match serde_json::from_slice(&request.body) {
+        Ok(new_task) => new_task,
+        Err(e) => return Ok(Response::error("400 Bad Request", format!("Invalid task: {}", e))),
+    };
+    if let Some(response) = refused(app, Operation::Create, &format!("add \"{}\"", new_task.title.trim())) {
+        return Ok(response);
+    }
+    let task = serde_json::to_value(new_task).map_err(|e| format!("Failed to serialize task: {}", e))?;
+
+    let result = match tasks::add_task(app, task, None) {
+        Ok(result) => result,
+        Err(e) => return Ok(Response::error("400 Bad Request", e)),
+    };
+    Ok(Response::with_status(
+        "201 Created",
+        CreatedTask {
+            task: to_api_task(&result.task)?,
+            possible_duplicate_ids: result.possible_duplicates.into_iter().map(|d| d.id).collect(),
+        },
+    ))
+}
+
+/// Saves a web page as a task, for the browser extension. The page title
+/// goes through the quick-add parser so the user can add `#tags` to it.
+fn capture(app: &AppHandle, request: &Request) -> Result<Response, String> {
+    let page: CapturedPage = match serde_json::from_slice(&request.body) {
+        Ok(page) => page,
+        Err(e) => return Ok(Response::error("400 Bad Request", format!("Invalid page: {}", e))),
+    };
+    let title = if page.title.trim().is_empty() { &page.url } else { &page.title };
+    if let Some(response) = refused(app, Operation::Create, &format!("save \"{}\"", title.trim())) {
+        return Ok(response);
+    }
+    let Some(mut task) = paste::parse_line(title) else {
+        return Ok(Response::error("400 Bad Request", "A task needs a title"));
+    };
+
+    let mut notes = Vec::new();
+    if let Some(selection) = page.selection.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        let quoted: Vec<String> = selection.lines().map(|line| format!("> {}", line)).collect();
+        notes.push(quoted.join("\n"));
+    }
+    notes.push(page.url.clone());
+    task["notes"] = json!(notes.join("\n\n"));
+    task["sourceUrl"] = json!(page.url);
+
+    let result = match tasks::add_task(app, task, None) {
+        Ok(result) => result,
+        Err(e) => return Ok(Response::error("400 Bad Request", e)),
+    };
+    Ok(Response::with_status(
+        "201 Created",
+        CreatedTask {
+            task: to_api_task(&result.task)?,
+            possible_duplicate_ids: result.possible_duplicates.into_iter().map(|d| d.id).collect(),
+        },
+    ))
+}
+
+fn complete_task(app: &AppHandle, id: &str) -> Result<Response, String> {
+    let data = storage::read_task_data(app)?;
+    let Some(task) = data.tasks.iter().find(|task| tasks::task_id(task) == Some(id)) else {
+        return Ok(Response::error("404 Not Found", format!("Task not found: {}", id)));
+    };
+    let title = str_field(task, "title").unwrap_or("Untitled");
+    if let Some(response) = refused(app, Operation::Complete, &format!("mark \"{}\" as done", title)) {
+        return Ok(response);
+    }
+    tasks::modify_task_data(app, |data| tasks::complete_task(data, id))?;
+    app.state::<ReminderScheduler>().reschedule();
+    task_response(app, id)
+}
+
+fn openapi_document(port: u16) -> Value {
+    let mut generator = SchemaSettings::openapi3().into_generator();
+    generator.subschema_for::<ApiTask>();
+    generator.subschema_for::<NewTask>();
+    generator.subschema_for::<TaskList>();
+    generator.subschema_for::<CreatedTask>();
+    generator.subschema_for::<ApiError>();
+    generator.subschema_for::<TaskEvent>();
+    generator.subschema_for::<CapturedPage>();
+    let schemas = serde_json::to_value(generator.definitions()).unwrap_or(Value::Null);
+
+    let body = |name: &str| json!({ "application/json": { "schema": { "$ref": format!("#/components/schemas/{}", name) } } });
+    let error = |description: &str| json!({ "description": description, "content": body("ApiError") });
+    let id_param = json!({ "name": "id", "in": "path", "required": true, "schema": { "type": "string" } });
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Afterglow local API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Switchbacks on this computer. Every route except this document needs `Authorization: Bearer <token>`.",
+        },
+        "servers": [{ "url": format!("http://127.0.0.1:{}", port) }],
+        "components": {
+            "schemas": schemas,
+            "securitySchemes": { "token": { "type": "http", "scheme": "bearer" } },
+        },
+        "security": [{ "token": [] }],
+        "paths": {
+            "/v1/tasks": {
+                "get": {
+                    "summary": "List tasks",
+                    "parameters": [
+                        { "name": "status", "in": "query", "schema": { "type": "string" } },
+                        { "name": "label", "in": "query", "schema": { "type": "string" } },
+                        { "name": "q", "in": "query", "description": "Text in the title or notes", "schema": { "type": "string" } },
+                    ],
+                    "responses": {
+                        "200": { "description": "Matching tasks", "content": body("TaskList") },
+                        "401": error("Missing or wrong token"),
+                        "403": error("Not allowed by the app's permission settings, or refused by the user"),
+                    },
+                },
+                "post": {
+                    "summary": "Create a task",
+                    "requestBody": { "required": true, "content": body("NewTask") },
+                    "responses": {
+                        "201": { "description": "The new task", "content": body("CreatedTask") },
+                        "400": error("Invalid task"),
+                        "401": error("Missing or wrong token"),
+                        "403": error("Not allowed by the app's permission settings, or refused by the user"),
+                    },
+                },
+            },
+            "/v1/tasks/{id}": {
+                "get": {
+                    "summary": "Get a task",
+                    "parameters": [id_param],
+                    "responses": {
+                        "200": { "description": "The task", "content": body("ApiTask") },
+                        "403": error("Not allowed by the app's permission settings, or refused by the user"),
+                        "404": error("No task with this id"),
+                    },
+                },
+            },
+            "/v1/capture": {
+                "post": {
+                    "summary": "Save a web page as a task (for the browser extension). #tags and @names in the title are picked up as in quick add.",
+                    "requestBody": { "required": true, "content": body("CapturedPage") },
+                    "responses": {
+                        "201": { "description": "The new task", "content": body("CreatedTask") },
+                        "400": error("Invalid page"),
+                        "401": error("Missing or wrong token"),
+                        "403": error("Not allowed by the app's permission settings, or refused by the user"),
+                    },
+                },
+            },
+            "/v1/events": {
+                "get": {
+                    "summary": "WebSocket stream of task changes. Each text message is a TaskEvent. The token may also be passed as the `token` query parameter.",
+                    "responses": {
+                        "101": { "description": "Switching to the WebSocket protocol", "content": body("TaskEvent") },
+                        "400": error("Not a WebSocket request"),
+                        "401": error("Missing or wrong token"),
+                        "403": error("Not allowed by the app's permission settings, or refused by the user"),
+                    },
+                },
+            },
+            "/v1/metrics": {
+                "get": {
+                    "summary": "Counters, timings and sizes in the Prometheus text format, for scraping.",
+                    "responses": {
+                        "200": { "description": "The metrics", "content": { "text/plain": { "schema": { "type": "string" } } } },
+                        "401": error("Missing or wrong token"),
+                    },
+                },
+            },
+            "/v1/tasks/{id}/complete": {
+                "post": {
+                    "summary": "Complete a task. Recurring tasks get their next instance.",
+                    "parameters": [id_param],
+                    "responses": {
+                        "200": { "description": "The completed task", "content": body("ApiTask") },
+                        "403": error("Not allowed by the app's permission settings, or refused by the user"),
+                        "404": error("No task with this id"),
+                    },
+                },
+            },
+        },
+    })
+}
+
+/// The token to give tools that use the API.
+#[tauri::command]
+pub fn get_api_token(app: AppHandle) -> Result<String, String> {
+    token(&app)
+}
+
+/// Replaces the token, locking out every tool that had the old one.
+#[tauri::command]
+pub fn regenerate_api_token(app: AppHandle) -> Result<String, String> {
+    let state = app.state::<ApiToken>();
+    let mut cached = state.0.lock().unwrap();
+    let token = oauth::random_token();
+    secrets::set_secret(TOKEN_SECRET, Some(&token))?;
+    *cached = Some(token.clone());
+    Ok(token)
+}
@@ -0,0 +1,366 @@
+//! Files attached to switchbacks, and the `afterglow-asset://` protocol that
+//! serves them to the webview. Attachments live in attachments/<task id>/
+//! under the data directory; the frontend never reads them through the fs
+//! plugin.
+//!
+//! URLs look like `afterglow-asset://localhost/file/<task id>/<name>` (or
+//! `.../thumbnail/...`). Requests are only answered for our own windows,
+//! for tasks that still exist, and for files inside the attachment
+//! directory. `Range` requests are honoured so audio and video can seek.
+
+use serde::Serialize;
+use std::borrow::Cow;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::thread;
+use tauri::http::{header, Method, Request, Response, StatusCode};
+use tauri::{AppHandle, UriSchemeContext, UriSchemeResponder, Wry};
+
+use crate::disk;
+use crate::files::{self, FilePurpose};
+use crate::http;
+use crate::storage;
+use crate::tasks::task_id;
+
+pub const ASSET_PROTOCOL: &str = "afterglow-asset";
+
+const THUMBNAIL_DIR: &str = ".thumbnails";
+
+/// Open-ended ranges (`bytes=0-`) are answered with at most this much.
+const MAX_RANGE_BYTES: u64 = 4 * 1024 * 1024;
+
+const MAX_THUMBNAIL_BYTES: usize = 512 * 1024;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Window labels allowed to load assets; matches the default capability.
+fn is_trusted_window(label: &str) -> bool {
+    matches!(label, "main" | "today" | "widget") || label.starts_with("task-")
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentInfo {
+    pub name: String,
+    pub size: u64,
+    pub mime_type: &'static str,
+    pub has_thumbnail: bool,
+}
+
+fn attachments_root(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("attachments")
+}
+
+fn validate_task_id(id: &str) -> Result<(), String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid task id \"{}\"", id));
+    }
+    Ok(())
+}
+
+/// A plain file name: no directories, no `..`, nothing hidden.
+fn validate_name(name: &str) -> Result<(), String> {
+    let invalid = name.is_empty()
+        || name.starts_with('.')
+        || name.contains(['/', '\\', ':', '\0'])
+        || name.len() > 255;
+    if invalid {
+        return Err(format!("Invalid attachment name \"{}\"", name));
+    }
+    Ok(())
+}
+
+fn task_exists(app: &AppHandle, id: &str) -> Result<bool, String> {
+    let data = storage::read_task_data(app)?;
+    Ok(data.tasks.iter().any(|task| task_id(task) == Some(id)))
+}
+
+fn task_dir(app: &AppHandle, id: &str) -> Result<PathBuf, String> {
+    validate_task_id(id)?;
+    Ok(attachments_root(app).join(id))
+}
+
+fn mime_type(name: &str) -> &'static str {
+    let extension = Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "txt" | "md" | "log" => "text/plain",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "eml" => "message/rfc822",
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        _ => "application/octet-stream",
+    }
+}
+
+fn info(dir: &Path, name: &str) -> Option<AttachmentInfo> {
+    let metadata = fs::metadata(dir.join(name)).ok().filter(|m| m.is_file())?;
+    Some(AttachmentInfo {
+        name: name.to_string(),
+        size: metadata.len(),
+        mime_type: mime_type(name),
+        has_thumbnail: dir.join(THUMBNAIL_DIR).join(name).is_file(),
+    })
+}
+
+/// "report.pdf" -> "report (2).pdf" until the name is free.
+fn unique_name(dir: &Path, name: &str) -> String {
+    let path = Path::new(name);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or(name);
+    let extension = path.extension().and_then(|e| e.to_str());
+
+    let mut candidate = name.to_string();
+    let mut n = 2;
+    while dir.join(&candidate).exists() {
+        candidate = match extension {
+            Some(extension) => format!("{} ({}).{}", stem, n, extension),
+            None => format!("{} ({})", stem, n),
+        };
+        n += 1;
+    }
+    candidate
+}
+
+#[tauri::command]
+pub fn list_attachments(app: AppHandle, task_id: String) -> Result<Vec<AttachmentInfo>, String> {
+    let dir = task_dir(&app, &task_id)?;
+    let mut attachments: Vec<AttachmentInfo> = fs::read_dir(&dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| validate_name(name).is_ok())
+        .filter_map(|name| info(&dir, &name))
+        .collect();
+    attachments.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(attachments)
+}
+
+/// Copies `source_path`, chosen with `choose_file`, into the task's
+/// attachments.
+#[tauri::command]
+pub fn add_attachment(
+    app: AppHandle,
+    task_id: String,
+    source_path: String,
+) -> Result<AttachmentInfo, String> {
+    if !task_exists(&app, &task_id)? {
+        return Err(format!("Task not found: {}", task_id));
+    }
+    let source = files::take_grant(&app, &source_path, FilePurpose::Attachment)?;
+    copy_into(&app, &task_id, &source)
+}
+
+/// Copies a file the user chose into the task's attachments.
+pub fn copy_into(app: &AppHandle, task_id: &str, source: &Path) -> Result<AttachmentInfo, String> {
+    let name = source
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.trim_start_matches('.').to_string())
+        .unwrap_or_default();
+    validate_name(&name)?;
+
+    let dir = task_dir(app, task_id)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create attachment folder: {}", e))?;
+    let name = unique_name(&dir, &name);
+
+    disk::retry_io(|| fs::copy(source, dir.join(&name)))
+        .map_err(|e| disk::describe_io_error("Failed to copy attachment", &e))?;
+
+    info(&dir, &name).ok_or_else(|| "Failed to read attachment".to_string())
+}
+
+/// Stores a PNG thumbnail the frontend rendered for an attachment.
+#[tauri::command]
+pub fn set_attachment_thumbnail(
+    app: AppHandle,
+    task_id: String,
+    name: String,
+    png: Vec<u8>,
+) -> Result<(), String> {
+    validate_name(&name)?;
+    if !png.starts_with(PNG_SIGNATURE) || png.len() > MAX_THUMBNAIL_BYTES {
+        return Err("Thumbnails must be PNG images under 512 KB".to_string());
+    }
+
+    let dir = task_dir(&app, &task_id)?;
+    if !dir.join(&name).is_file() {
+        return Err(format!("Attachment not found: {}", name));
+    }
+
+    let thumbnail_dir = dir.join(THUMBNAIL_DIR);
+    fs::create_dir_all(&thumbnail_dir)
+        .map_err(|e| format!("Failed to create thumbnail folder: {}", e))?;
+    disk::write_atomic(&thumbnail_dir.join(&name), &png)
+        .map_err(|e| format!("Failed to save thumbnail: {}", e))
+}
+
+#[tauri::command]
+pub fn remove_attachment(app: AppHandle, task_id: String, name: String) -> Result<(), String> {
+    validate_name(&name)?;
+    let dir = task_dir(&app, &task_id)?;
+
+    fs::remove_file(dir.join(&name)).map_err(|e| format!("Failed to remove attachment: {}", e))?;
+    fs::remove_file(dir.join(THUMBNAIL_DIR).join(&name)).ok();
+    Ok(())
+}
+
+/// Handler for `afterglow-asset://`. File reads happen off the main thread.
+pub fn handle_protocol(
+    ctx: UriSchemeContext<'_, Wry>,
+    request: Request<Vec<u8>>,
+    responder: UriSchemeResponder,
+) {
+    let app = ctx.app_handle().clone();
+    let trusted = is_trusted_window(ctx.webview_label());
+
+    thread::spawn(move || {
+        let response = if trusted {
+            serve(&app, &request).unwrap_or_else(error_response)
+        } else {
+            error_response(StatusCode::FORBIDDEN)
+        };
+        responder.respond(response);
+    });
+}
+
+fn error_response(status: StatusCode) -> Response<Cow<'static, [u8]>> {
+    Response::builder()
+        .status(status)
+        .body(Cow::Borrowed(&[][..]))
+        .unwrap_or_default()
+}
+
+/// Resolves `/<kind>/<task id>/<name>` to a path inside the attachment
+/// directory.
+fn resolve_request_path(app: &AppHandle, path: &str) -> Result<PathBuf, StatusCode> {
+    // The frontend builds URLs with convertFileSrc, which encodes the slashes
+    let path = http::percent_decode(path.trim_start_matches('/')).ok_or(StatusCode::BAD_REQUEST)?;
+    let mut parts = path.splitn(3, '/');
+    let (Some(kind), Some(id), Some(name)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    validate_name(name).map_err(|_| StatusCode::BAD_REQUEST)?;
+    let dir = task_dir(app, id).map_err(|_| StatusCode::BAD_REQUEST)?;
+    if !task_exists(app, id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let file = match kind {
+        "file" => dir.join(name),
+        // Image attachments without a stored thumbnail are scaled by the page
+        "thumbnail" => {
+            let thumbnail = dir.join(THUMBNAIL_DIR).join(name);
+            if thumbnail.is_file() || !mime_type(name).starts_with("image/") {
+                thumbnail
+            } else {
+                dir.join(name)
+            }
+        }
+        _ => return Err(StatusCode::NOT_FOUND),
+    };
+
+    // Catch symlinks pointing out of the attachment directory
+    let root = attachments_root(app).canonicalize().map_err(|_| StatusCode::NOT_FOUND)?;
+    let file = file.canonicalize().map_err(|_| StatusCode::NOT_FOUND)?;
+    if !file.starts_with(&root) || !file.is_file() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(file)
+}
+
+fn serve(
+    app: &AppHandle,
+    request: &Request<Vec<u8>>,
+) -> Result<Response<Cow<'static, [u8]>>, StatusCode> {
+    if request.method() != Method::GET && request.method() != Method::HEAD {
+        return Err(StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    let path = resolve_request_path(app, request.uri().path())?;
+    let mut file = File::open(&path).map_err(|_| StatusCode::NOT_FOUND)?;
+    let size = file.metadata().map_err(|_| StatusCode::NOT_FOUND)?.len();
+
+    let range = request
+        .headers()
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| parse_range(value, size).ok_or(StatusCode::RANGE_NOT_SATISFIABLE))
+        .transpose()?;
+    let (start, end) = range.unwrap_or((0, size.saturating_sub(1)));
+    let length = if size == 0 { 0 } else { end - start + 1 };
+
+    let mut body = Vec::new();
+    if request.method() == Method::GET {
+        file.seek(SeekFrom::Start(start))
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        file.take(length)
+            .read_to_end(&mut body)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let mut response = Response::builder()
+        .header(header::CONTENT_TYPE, mime_type(name))
+        .header(header::CONTENT_LENGTH, length)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CACHE_CONTROL, "no-cache")
+        // Attachments are user files; never let one run as a page
+        .header("X-Content-Type-Options", "nosniff")
+        .header(header::CONTENT_SECURITY_POLICY, "default-src 'none'; sandbox");
+
+    response = if range.is_some() {
+        response
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, size))
+    } else {
+        response.status(StatusCode::OK)
+    };
+
+    response
+        .body(Cow::Owned(body))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Parses a single `bytes=` range into inclusive offsets. Open-ended ranges
+/// are capped at `MAX_RANGE_BYTES`.
+fn parse_range(value: &str, size: u64) -> Option<(u64, u64)> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    // Multipart ranges aren't worth it for attachments
+    if spec.contains(',') || size == 0 {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let last = size - 1;
+
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().ok()?;
+            (size.saturating_sub(suffix), last)
+        }
+        (start, "") => {
+            let start: u64 = start.parse().ok()?;
+            (start, last.min(start.saturating_add(MAX_RANGE_BYTES - 1)))
+        }
+        (start, end) => (start.parse().ok()?, last.min(end.parse().ok()?)),
+    };
+
+    (start <= end && start <= last).then_some((start, end))
+}
@@ -0,0 +1,323 @@
+//! Automatic backups of tasks.json, taken just before every save replaces it.
+//! The file is hard-linked into backups/ where the file system allows it, so
+//! the save barely waits; pruning old backups runs as a background job.
+//!
+//! File names follow `settings.backups`. Every backup is recorded in
+//! backups/manifest.json, so cleanup still recognises backups written under
+//! an earlier naming pattern.
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use crate::disk;
+use crate::jobs;
+use crate::metrics;
+use crate::power;
+use crate::settings::{self, BackupSettings};
+use crate::storage;
+
+const MAX_BACKUPS: usize = 5;
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Backups written before names were configurable all start with this.
+const LEGACY_PREFIX: &str = "tasks_backup_";
+
+/// Value of the `{workspace}` token: the data file's name without extension.
+const WORKSPACE: &str = "tasks";
+
+/// Keeps names comfortably inside Windows' path limits.
+const MAX_FILE_NAME_LEN: usize = 120;
+
+/// Held while the manifest is read, changed and written back.
+static MANIFEST_LOCK: Mutex<()> = Mutex::new(());
+
+/// Characters Windows or common sync tools reject in file names.
+const FORBIDDEN_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct BackupManifest {
+    backups: Vec<BackupEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupEntry {
+    file: String,
+    created_at: String,
+}
+
+fn validate_time_format(format: &str) -> Result<(), String> {
+    if format.is_empty() {
+        return Err("Backup date and time formats can't be empty".to_string());
+    }
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        return Err(format!("Invalid backup timestamp format \"{}\"", format));
+    }
+    Ok(())
+}
+
+/// Renders a backup file name (including `.json`) from the naming settings.
+pub fn render_file_name(config: &BackupSettings, time: DateTime<Local>) -> Result<String, String> {
+    validate_time_format(&config.date_format)?;
+    validate_time_format(&config.time_format)?;
+
+    for token in ["{date}", "{time}"] {
+        if !config.filename_pattern.contains(token) {
+            return Err(format!("Backup filename pattern must contain {}", token));
+        }
+    }
+
+    let name = config
+        .filename_pattern
+        .replace("{workspace}", WORKSPACE)
+        .replace("{date}", &time.format(&config.date_format).to_string())
+        .replace("{time}", &time.format(&config.time_format).to_string());
+
+    if name.contains('{') || name.contains('}') {
+        return Err(
+            "Unknown token in backup filename pattern; use {workspace}, {date} or {time}".to_string(),
+        );
+    }
+    if let Some(c) = name.chars().find(|c| FORBIDDEN_CHARS.contains(c) || c.is_control()) {
+        return Err(format!("Backup filenames can't contain \"{}\"", c));
+    }
+    if name.ends_with('.') || name.ends_with(' ') {
+        return Err("Backup filenames can't end with a dot or a space".to_string());
+    }
+    if name.len() > MAX_FILE_NAME_LEN {
+        return Err(format!("Backup filenames must be at most {} characters", MAX_FILE_NAME_LEN));
+    }
+
+    Ok(format!("{}.json", name))
+}
+
+fn get_backups_dir(app: &AppHandle) -> PathBuf {
+    let backups_dir = storage::get_app_data_dir(app).join("backups");
+    fs::create_dir_all(&backups_dir).ok();
+    backups_dir
+}
+
+fn read_manifest(backups_dir: &Path) -> BackupManifest {
+    fs::read_to_string(backups_dir.join(MANIFEST_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_manifest(backups_dir: &Path, manifest: &BackupManifest) {
+    if let Ok(content) = serde_json::to_string_pretty(manifest) {
+        fs::write(backups_dir.join(MANIFEST_FILE), content).ok();
+    }
+}
+
+/// Backs up `data_path`, the file `FileStorage` is about to replace. The save
+/// renames a new file into place, so a hard link keeps the old contents.
+pub fn create_backup(app: &AppHandle, data_path: &Path) -> Result<(), String> {
+    // Only backup if the data file exists
+    if !data_path.exists() {
+        return Ok(());
+    }
+
+    let now = Local::now();
+    let config = settings::current(app).backups;
+    // A hand-edited settings file with a bad pattern shouldn't stop backups
+    let file_name = render_file_name(&config, now)
+        .or_else(|_| render_file_name(&BackupSettings::default(), now))?;
+
+    let backups_dir = get_backups_dir(app);
+
+    // On battery, saves in quick succession share a backup
+    if power::is_low_power(app) {
+        let latest = read_manifest(&backups_dir)
+            .backups
+            .iter()
+            .filter_map(|entry| DateTime::parse_from_rfc3339(&entry.created_at).ok())
+            .max();
+        if latest.is_some_and(|at| now.signed_duration_since(at) < power::LOW_POWER_BACKUP_INTERVAL) {
+            return Ok(());
+        }
+    }
+
+    let backup_path = backups_dir.join(&file_name);
+    // Two saves within the same second share a name
+    fs::remove_file(&backup_path).ok();
+    let linked = metrics::time("backup", || fs::hard_link(data_path, &backup_path)).is_ok();
+    if !linked {
+        // Skip the backup rather than fill the disk
+        let size = fs::metadata(data_path).map(|m| m.len()).unwrap_or(0);
+        if !disk::has_space_for(&backups_dir, size, disk::BACKUP_HEADROOM) {
+            disk::warn_low_space(app, "Disk space is low, so Afterglow is skipping backups");
+            metrics::increment("backups_skipped_total");
+            return Ok(());
+        }
+        metrics::time("backup", || fs::copy(data_path, &backup_path))
+            .map_err(|e| format!("Failed to create backup: {}", e))?;
+    }
+    metrics::increment("backups_total");
+
+    {
+        let _guard = MANIFEST_LOCK.lock().unwrap();
+        let mut manifest = read_manifest(&backups_dir);
+        manifest.backups.retain(|entry| entry.file != file_name);
+        manifest.backups.push(BackupEntry {
+            file: file_name,
+            created_at: now.to_rfc3339(),
+        });
+        write_manifest(&backups_dir, &manifest);
+    }
+
+    // Clean up old backups in the background, keeping the most recent MAX_BACKUPS
+    jobs::spawn(app, "backup-cleanup", |job| {
+        let backups_dir = get_backups_dir(&job.app);
+        let config = settings::current(&job.app).backups;
+
+        let _guard = MANIFEST_LOCK.lock().unwrap();
+        let mut manifest = read_manifest(&backups_dir);
+        cleanup_old_backups(&backups_dir, &mut manifest, &config);
+        write_manifest(&backups_dir, &manifest);
+        Ok(())
+    });
+
+    Ok(())
+}
+
+/// Parses the creation time back out of a backup file name.
+fn parse_file_name(config: &BackupSettings, file_name: &str) -> Option<DateTime<Local>> {
+    // Literal text in the pattern must not be read as strftime specifiers
+    let format = config
+        .filename_pattern
+        .replace('%', "%%")
+        .replace("{workspace}", WORKSPACE)
+        .replace("{date}", &config.date_format)
+        .replace("{time}", &config.time_format);
+
+    let naive = NaiveDateTime::parse_from_str(file_name.strip_suffix(".json")?, &format).ok()?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+/// When a backup was taken. Cloud-sync tools rewrite mtimes, so the manifest
+/// and the timestamp embedded in the name come first and the file's mtime is
+/// only a fallback.
+fn backup_time(
+    backups_dir: &Path,
+    manifest: &BackupManifest,
+    config: &BackupSettings,
+    file_name: &str,
+) -> Option<DateTime<Local>> {
+    manifest
+        .backups
+        .iter()
+        .find(|entry| entry.file == file_name)
+        .and_then(|entry| DateTime::parse_from_rfc3339(&entry.created_at).ok())
+        .map(|time| time.with_timezone(&Local))
+        .or_else(|| parse_file_name(config, file_name))
+        .or_else(|| parse_file_name(&BackupSettings::default(), file_name))
+        .or_else(|| {
+            fs::metadata(backups_dir.join(file_name))
+                .and_then(|m| m.modified())
+                .ok()
+                .map(DateTime::<Local>::from)
+        })
+}
+
+fn cleanup_old_backups(backups_dir: &Path, manifest: &mut BackupManifest, config: &BackupSettings) {
+    // Forget entries whose file was removed by hand
+    manifest.backups.retain(|entry| backups_dir.join(&entry.file).exists());
+
+    // Backups from the manifest, plus legacy ones that predate it
+    let mut backups: Vec<String> = manifest.backups.iter().map(|e| e.file.clone()).collect();
+    backups.extend(
+        fs::read_dir(backups_dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            // Names that aren't valid Unicode can't be ours; skip them rather
+            // than mangling them into a path that doesn't exist
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| name.starts_with(LEGACY_PREFIX) && name.ends_with(".json"))
+            .filter(|name| !manifest.backups.iter().any(|e| &e.file == name)),
+    );
+
+    // Newest first
+    let mut backups: Vec<_> = backups
+        .into_iter()
+        .map(|name| (backup_time(backups_dir, manifest, config, &name), name))
+        .collect();
+    backups.sort_by_key(|(time, _)| std::cmp::Reverse(*time));
+
+    // Remove old backups beyond MAX_BACKUPS
+    for (_, name) in backups.into_iter().skip(MAX_BACKUPS) {
+        fs::remove_file(backups_dir.join(&name)).ok();
+        manifest.backups.retain(|entry| entry.file != name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disk::tests::scratch_dir;
+
+    fn legacy_backup(dir: &Path, stamp: &str) -> String {
+        let name = format!("{}{}.json", LEGACY_PREFIX, stamp);
+        fs::write(dir.join(&name), b"{}").unwrap();
+        name
+    }
+
+    #[test]
+    fn cleanup_keeps_the_newest_backups() {
+        let dir = disk::long_path(&scratch_dir());
+        let names: Vec<String> = (1..=8)
+            .map(|day| legacy_backup(&dir, &format!("202610{:02}_120000", day)))
+            .collect();
+
+        cleanup_old_backups(&dir, &mut BackupManifest::default(), &BackupSettings::default());
+
+        for (i, name) in names.iter().enumerate() {
+            assert_eq!(dir.join(name).exists(), i >= names.len() - MAX_BACKUPS, "{}", name);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cleanup_ignores_names_that_are_not_unicode() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = scratch_dir();
+        let stray = dir.join(OsStr::from_bytes(b"tasks_backup_\xff\xfe.json"));
+        fs::write(&stray, b"{}").unwrap();
+        for day in 1..=7 {
+            legacy_backup(&dir, &format!("202610{:02}_120000", day));
+        }
+
+        cleanup_old_backups(&dir, &mut BackupManifest::default(), &BackupSettings::default());
+
+        assert!(stray.exists());
+        let remaining = fs::read_dir(&dir).unwrap().filter_map(|entry| entry.ok()).count();
+        assert_eq!(remaining, MAX_BACKUPS + 1);
+    }
+
+    #[test]
+    fn cleanup_forgets_manifest_entries_for_missing_files() {
+        let dir = scratch_dir();
+        let kept = legacy_backup(&dir, "20261001_120000");
+        let mut manifest = BackupManifest {
+            backups: vec![
+                BackupEntry { file: kept.clone(), created_at: "2026-10-01T12:00:00+00:00".to_string() },
+                BackupEntry { file: "gone.json".to_string(), created_at: "2026-10-02T12:00:00+00:00".to_string() },
+            ],
+        };
+
+        cleanup_old_backups(&dir, &mut manifest, &BackupSettings::default());
+
+        let files: Vec<&str> = manifest.backups.iter().map(|entry| entry.file.as_str()).collect();
+        assert_eq!(files, vec![kept.as_str()]);
+    }
+}
@@ -0,0 +1,270 @@
+//! Calendar buckets computed on the backend, so the calendar view stays
+//! quick with large task lists. Which tasks land on which day mirrors
+//! `getTasksForDate` in the weekly view: one-off tasks and finished
+//! recurring instances on their due date, active recurring tasks on every
+//! day their pattern matches from the due date until they're ended. Events
+//! from calendar feeds ride along read-only.
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+
+use crate::calendar_feeds::{self, CalendarEvent};
+use crate::periods;
+use crate::recurrence::{self, format_task_date, parse_task_date, RecurrenceRule};
+use crate::report;
+use crate::settings;
+use crate::storage;
+use crate::tasks::{self, str_field, task_id};
+
+const MAX_CALENDAR_DAYS: i64 = 400;
+
+/// Tasks listed per bucket when the caller doesn't say.
+const DEFAULT_BUCKET_LIMIT: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Granularity {
+    Day,
+    /// Whole weeks, starting on Sunday or Monday per `regional.weekNumbering`
+    Week,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarTask {
+    pub id: String,
+    pub title: String,
+    pub priority: String,
+    pub status: String,
+    pub recurring: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarBucket {
+    pub start: String,
+    pub end: String,
+    /// Week buckets only, e.g. `2026-W42`
+    pub week: Option<String>,
+    /// Distinct tasks in the bucket
+    pub total: usize,
+    pub done: usize,
+    pub high_priority: usize,
+    pub estimated_minutes: i64,
+    /// The first tasks by priority, at most the requested limit
+    pub tasks: Vec<CalendarTask>,
+    /// Tasks left out of `tasks`
+    pub overflow: usize,
+    /// Events from calendar feeds overlapping the bucket
+    pub events: Vec<CalendarEvent>,
+}
+
+/// A task with its dates and rule parsed once per request rather than once
+/// per day it's checked against.
+struct Placed<'a> {
+    task: &'a Value,
+    due: Option<NaiveDate>,
+    /// Active recurring tasks: the rule and the day the task was ended
+    recurring: Option<(RecurrenceRule, Option<NaiveDate>)>,
+}
+
+impl<'a> Placed<'a> {
+    /// `None` for an active recurring task whose rule doesn't parse, which
+    /// shows on no day.
+    fn new(task: &'a Value) -> Option<Self> {
+        let due = str_field(task, "dueDate").and_then(parse_task_date);
+        let recurring = if str_field(task, "type") == Some("recurring") && !tasks::is_done(task) {
+            let rule = serde_json::from_value::<RecurrenceRule>(task.get("recurrence")?.clone()).ok()?;
+            Some((rule, str_field(task, "endedAt").and_then(parse_task_date)))
+        } else {
+            None
+        };
+        Some(Self { task, due, recurring })
+    }
+
+    /// Whether the task shows on `day`. Mirrors `getTasksForDate`.
+    fn shows_on(&self, day: NaiveDate) -> bool {
+        match &self.recurring {
+            None => self.due == Some(day),
+            Some((rule, ended)) => {
+                recurrence::applies_to_date(rule, day)
+                    && self.due.is_none_or(|due| due <= day)
+                    && ended.is_none_or(|ended| day <= ended)
+            }
+        }
+    }
+}
+
+fn bucket(
+    all: &[Placed],
+    events: &[CalendarEvent],
+    start: NaiveDate,
+    end: NaiveDate,
+    limit: usize,
+) -> CalendarBucket {
+    let days: Vec<NaiveDate> = start.iter_days().take_while(|d| *d <= end).collect();
+    let mut in_bucket: Vec<&Value> = all
+        .iter()
+        .filter(|placed| days.iter().any(|day| placed.shows_on(*day)))
+        .map(|placed| placed.task)
+        .collect();
+    report::sort_for_report(&mut in_bucket);
+
+    let is_high = |task: &&Value| matches!(str_field(task, "priority"), Some("p0") | Some("p1"));
+    CalendarBucket {
+        start: format_task_date(start),
+        end: format_task_date(end),
+        week: None,
+        total: in_bucket.len(),
+        done: in_bucket.iter().filter(|t| tasks::is_done(t)).count(),
+        high_priority: in_bucket.iter().filter(|t| !tasks::is_done(t) && is_high(t)).count(),
+        estimated_minutes: in_bucket
+            .iter()
+            .filter_map(|t| t.get("estimatedMinutes").and_then(Value::as_i64))
+            .sum(),
+        tasks: in_bucket
+            .iter()
+            .take(limit)
+            .map(|task| CalendarTask {
+                id: task_id(task).unwrap_or_default().to_string(),
+                title: str_field(task, "title").unwrap_or_default().to_string(),
+                priority: str_field(task, "priority").unwrap_or("p2").to_string(),
+                status: str_field(task, "status").unwrap_or("not-started").to_string(),
+                recurring: str_field(task, "type") == Some("recurring"),
+            })
+            .collect(),
+        overflow: in_bucket.len().saturating_sub(limit),
+        events: events
+            .iter()
+            .filter(|e| {
+                let overlaps = |first: NaiveDate, last: NaiveDate| first <= end && last >= start;
+                match (parse_task_date(&e.event.start), parse_task_date(&e.event.end)) {
+                    (Some(first), Some(last)) => overlaps(first, last),
+                    _ => false,
+                }
+            })
+            .cloned()
+            .collect(),
+    }
+}
+
+/// Tasks between `start` and `end` (inclusive) bucketed by day or week. Week
+/// buckets cover whole weeks, so the first and last may reach outside the
+/// range.
+#[tauri::command]
+pub fn get_calendar(
+    app: AppHandle,
+    start: String,
+    end: String,
+    granularity: Granularity,
+    limit: Option<usize>,
+) -> Result<Vec<CalendarBucket>, String> {
+    let start = parse_task_date(&start).ok_or_else(|| format!("Invalid date \"{}\"", start))?;
+    let end = parse_task_date(&end).ok_or_else(|| format!("Invalid date \"{}\"", end))?;
+    let length = (end - start).num_days() + 1;
+    if !(1..=MAX_CALENDAR_DAYS).contains(&length) {
+        return Err(format!("The range must be 1 to {} days", MAX_CALENDAR_DAYS));
+    }
+
+    let data = storage::read_task_data(&app)?;
+    let all: Vec<Placed> = data.tasks.iter().filter_map(Placed::new).collect();
+    let limit = limit.unwrap_or(DEFAULT_BUCKET_LIMIT);
+
+    let weeks = settings::current(&app).regional.week_numbering;
+    let (first, step) = match granularity {
+        Granularity::Day => (start, 1),
+        Granularity::Week => (periods::week_start(start, weeks), 7),
+    };
+    let events = calendar_feeds::events_between(&app, first, end);
+    let mut buckets = Vec::new();
+    let mut bucket_start = first;
+    while bucket_start <= end {
+        let bucket_end = bucket_start + Duration::days(step - 1);
+        let mut bucket = bucket(&all, &events, bucket_start, bucket_end, limit);
+        if granularity == Granularity::Week {
+            bucket.week = Some(periods::format_week(bucket_start, weeks, true));
+        }
+        buckets.push(bucket);
+        bucket_start += Duration::days(step);
+    }
+    Ok(buckets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn date(value: &str) -> NaiveDate {
+        parse_task_date(value).unwrap()
+    }
+
+    fn shows_on(task: &Value, day: &str) -> bool {
+        Placed::new(task).is_some_and(|placed| placed.shows_on(date(day)))
+    }
+
+    #[test]
+    fn a_one_off_task_shows_on_its_due_date_only() {
+        let task = json!({ "title": "Renew passport", "dueDate": "2028-02-29T09:00" });
+        assert!(shows_on(&task, "2028-02-29"));
+        assert!(!shows_on(&task, "2028-02-28"));
+        assert!(!shows_on(&json!({ "title": "Someday" }), "2028-02-29"));
+    }
+
+    #[test]
+    fn a_recurring_task_shows_from_its_due_date_until_it_ends() {
+        let task = json!({
+            "type": "recurring",
+            "status": "not-started",
+            "dueDate": "2026-10-14",
+            "endedAt": "2026-10-28",
+            "recurrence": { "pattern": "weekly", "weekdays": [3] },
+        });
+        assert!(!shows_on(&task, "2026-10-07"));
+        assert!(shows_on(&task, "2026-10-14"));
+        assert!(shows_on(&task, "2026-10-21"));
+        assert!(!shows_on(&task, "2026-10-22"));
+        assert!(shows_on(&task, "2026-10-28"));
+        assert!(!shows_on(&task, "2026-11-04"));
+    }
+
+    #[test]
+    fn a_finished_recurring_instance_shows_on_its_due_date_only() {
+        let task = json!({
+            "type": "recurring",
+            "status": "done",
+            "dueDate": "2026-10-14",
+            "recurrence": { "pattern": "weekly", "weekdays": [3] },
+        });
+        assert!(shows_on(&task, "2026-10-14"));
+        assert!(!shows_on(&task, "2026-10-21"));
+    }
+
+    #[test]
+    fn a_recurring_task_with_a_broken_rule_shows_nowhere() {
+        let missing = json!({ "type": "recurring", "dueDate": "2026-10-14" });
+        let broken = json!({ "type": "recurring", "dueDate": "2026-10-14", "recurrence": { "pattern": "hourly" } });
+        assert!(Placed::new(&missing).is_none());
+        assert!(Placed::new(&broken).is_none());
+    }
+
+    #[test]
+    fn bucket_counts_each_task_once_across_its_days() {
+        let tasks = [
+            json!({ "id": "a", "title": "Standup", "type": "recurring", "priority": "p1",
+                    "recurrence": { "pattern": "business-days" }, "estimatedMinutes": 15 }),
+            json!({ "id": "b", "title": "Ship", "dueDate": "2026-10-16", "status": "done" }),
+        ];
+        let placed: Vec<Placed> = tasks.iter().filter_map(Placed::new).collect();
+        let week = bucket(&placed, &[], date("2026-10-12"), date("2026-10-18"), 1);
+
+        assert_eq!((week.total, week.done, week.high_priority), (2, 1, 1));
+        assert_eq!(week.estimated_minutes, 15);
+        assert_eq!((week.tasks.len(), week.overflow), (1, 1));
+
+        let empty = bucket(&[], &[], date("2026-10-12"), date("2026-10-18"), 5);
+        assert_eq!((empty.total, empty.overflow), (0, 0));
+    }
+}
@@ -0,0 +1,479 @@
+//! Read-only ICS subscriptions (team vacations, conference schedules),
+//! persisted with their last fetched events in calendar_feeds.json. A
+//! background pass refreshes each feed every few hours. Events show up in
+//! `get_calendar`, and all-day events in a feed marked out-of-office hold
+//! reminders back like quiet hours do.
+//!
+//! Recurring events (`RRULE`) only show their first occurrence, and times
+//! with a `TZID` are read as local time.
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use tauri::{AppHandle, Manager};
+
+use crate::disk;
+use crate::http;
+use crate::jobs::{self, JobContext};
+use crate::log;
+use crate::power;
+use crate::recurrence::{format_task_date, parse_task_date};
+use crate::reminders::ReminderScheduler;
+use crate::storage;
+use crate::tasks::now_iso;
+
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+
+/// How old a feed's events may get before the background pass refetches it.
+const REFRESH_AFTER: Duration = Duration::hours(3);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedEvent {
+    pub uid: String,
+    pub summary: String,
+    /// `YYYY-MM-DD` for all-day events, local `YYYY-MM-DDTHH:MM` otherwise
+    pub start: String,
+    /// Inclusive last day for all-day events
+    pub end: String,
+    pub all_day: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarFeed {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    /// All-day events in this feed mean the user is away
+    #[serde(default)]
+    pub out_of_office: bool,
+    #[serde(default)]
+    pub refreshed_at: Option<String>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub events: Vec<FeedEvent>,
+}
+
+/// A feed without its events, for listing.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedSummary {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub out_of_office: bool,
+    pub refreshed_at: Option<String>,
+    pub last_error: Option<String>,
+    pub event_count: usize,
+}
+
+/// An event with the feed it came from.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarEvent {
+    pub feed_id: String,
+    pub feed_name: String,
+    pub out_of_office: bool,
+    #[serde(flatten)]
+    pub event: FeedEvent,
+}
+
+#[derive(Default)]
+pub struct CalendarFeeds(Mutex<Vec<CalendarFeed>>);
+
+fn get_feeds_path(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("calendar_feeds.json")
+}
+
+pub fn load(app: &AppHandle) -> Result<(), String> {
+    let path = get_feeds_path(app);
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read calendar feeds: {}", e))?;
+
+    let feeds: Vec<CalendarFeed> = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse calendar feeds: {}", e))?;
+
+    *app.state::<CalendarFeeds>().0.lock().unwrap() = feeds;
+    Ok(())
+}
+
+fn save(app: &AppHandle, feeds: &[CalendarFeed]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(feeds)
+        .map_err(|e| format!("Failed to serialize calendar feeds: {}", e))?;
+    disk::write_atomic(&get_feeds_path(app), content.as_bytes())
+        .map_err(|e| format!("Failed to save calendar feeds: {}", e))
+}
+
+/// Joins folded lines (continuations start with a space or tab).
+fn unfold(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in ics.lines() {
+        match (line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+enum IcsTime {
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+}
+
+/// Parses a `DTSTART`/`DTEND` value; UTC times are converted to local time.
+fn parse_time(params: &str, value: &str) -> Option<IcsTime> {
+    if (params.contains("VALUE=DATE") && !params.contains("VALUE=DATE-TIME")) || value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d").ok().map(IcsTime::Date);
+    }
+    match value.strip_suffix('Z') {
+        Some(utc) => {
+            let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+            Some(IcsTime::DateTime(Utc.from_utc_datetime(&naive).with_timezone(&Local).naive_local()))
+        }
+        None => NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok().map(IcsTime::DateTime),
+    }
+}
+
+fn parse_ics(ics: &str) -> Vec<FeedEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<(String, String, Option<IcsTime>, Option<IcsTime>)> = None;
+
+    for line in unfold(ics) {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, params) = key.split_once(';').unwrap_or((key, ""));
+        match (name.to_ascii_uppercase().as_str(), current.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                current = Some((String::new(), String::new(), None, None));
+            }
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                let Some((uid, summary, Some(start), end)) = current.take() else {
+                    continue;
+                };
+                events.push(to_event(uid, summary, start, end));
+            }
+            ("UID", Some(event)) => event.0 = value.to_string(),
+            ("SUMMARY", Some(event)) => event.1 = unescape(value),
+            ("DTSTART", Some(event)) => event.2 = parse_time(params, value),
+            ("DTEND", Some(event)) => event.3 = parse_time(params, value),
+            _ => {}
+        }
+    }
+    events
+}
+
+fn to_event(uid: String, summary: String, start: IcsTime, end: Option<IcsTime>) -> FeedEvent {
+    let format_time = |time: NaiveDateTime| time.format("%Y-%m-%dT%H:%M").to_string();
+    match start {
+        IcsTime::Date(start) => {
+            // DTEND is exclusive for all-day events
+            let end = match end {
+                Some(IcsTime::Date(end)) if end > start => end - Duration::days(1),
+                _ => start,
+            };
+            FeedEvent {
+                uid,
+                summary,
+                start: format_task_date(start),
+                end: format_task_date(end),
+                all_day: true,
+            }
+        }
+        IcsTime::DateTime(start) => {
+            let end = match end {
+                Some(IcsTime::DateTime(end)) if end >= start => end,
+                _ => start,
+            };
+            FeedEvent {
+                uid,
+                summary,
+                start: format_time(start),
+                end: format_time(end),
+                all_day: false,
+            }
+        }
+    }
+}
+
+fn fetch_events(url: &str) -> Result<Vec<FeedEvent>, String> {
+    let url = match url.strip_prefix("webcal://") {
+        Some(rest) => format!("https://{}", rest),
+        None => url.to_string(),
+    };
+    let mut response = http::agent()
+        .get(&url)
+        .header("User-Agent", "Afterglow")
+        .call()
+        .map_err(|e| format!("Failed to fetch calendar feed: {}", e))?;
+    let body = response
+        .body_mut()
+        .read_to_string()
+        .map_err(|e| format!("Failed to read calendar feed: {}", e))?;
+    if !body.contains("BEGIN:VCALENDAR") {
+        return Err("The feed isn't an iCalendar file".to_string());
+    }
+    Ok(parse_ics(&body))
+}
+
+/// Refetches the feeds `due` picks; fetching happens without the lock held.
+fn refresh(app: &AppHandle, due: impl Fn(&CalendarFeed) -> bool) -> Result<usize, String> {
+    let to_fetch: Vec<(String, String)> = app
+        .state::<CalendarFeeds>()
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|feed| due(feed))
+        .map(|feed| (feed.id.clone(), feed.url.clone()))
+        .collect();
+    if to_fetch.is_empty() {
+        return Ok(0);
+    }
+
+    let results: Vec<(String, Result<Vec<FeedEvent>, String>)> =
+        to_fetch.into_iter().map(|(id, url)| (id, fetch_events(&url))).collect();
+
+    let store = app.state::<CalendarFeeds>();
+    let mut feeds = store.0.lock().unwrap();
+    for (id, result) in &results {
+        let Some(feed) = feeds.iter_mut().find(|feed| &feed.id == id) else {
+            continue;
+        };
+        feed.refreshed_at = Some(now_iso());
+        match result {
+            Ok(events) => {
+                feed.events = events.clone();
+                feed.last_error = None;
+            }
+            Err(e) => feed.last_error = Some(e.clone()),
+        }
+    }
+    save(app, &feeds)?;
+    drop(feeds);
+
+    // Out-of-office days may have changed
+    app.state::<ReminderScheduler>().reschedule();
+    Ok(results.len())
+}
+
+fn is_stale(feed: &CalendarFeed) -> bool {
+    let refreshed = feed
+        .refreshed_at
+        .as_deref()
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok());
+    refreshed.is_none_or(|at| Utc::now().signed_duration_since(at) >= REFRESH_AFTER)
+}
+
+pub fn start(app: AppHandle) {
+    thread::spawn(move || loop {
+        if let Err(e) = refresh(&app, is_stale) {
+            log::error(&app, e);
+        }
+        power::sleep(&app, CHECK_INTERVAL);
+    });
+}
+
+/// Every event overlapping `start..=end`.
+pub fn events_between(app: &AppHandle, start: NaiveDate, end: NaiveDate) -> Vec<CalendarEvent> {
+    let feeds = app.state::<CalendarFeeds>().0.lock().unwrap().clone();
+    feeds
+        .iter()
+        .flat_map(|feed| {
+            feed.events.iter().filter_map(move |event| {
+                let first = parse_task_date(&event.start)?;
+                let last = parse_task_date(&event.end)?;
+                (first <= end && last >= start).then(|| CalendarEvent {
+                    feed_id: feed.id.clone(),
+                    feed_name: feed.name.clone(),
+                    out_of_office: feed.out_of_office,
+                    event: event.clone(),
+                })
+            })
+        })
+        .collect()
+}
+
+/// Whether an out-of-office feed has an all-day event on `day`.
+pub fn is_out_of_office(app: &AppHandle, day: NaiveDate) -> bool {
+    events_between(app, day, day)
+        .iter()
+        .any(|event| event.out_of_office && event.event.all_day)
+}
+
+#[tauri::command]
+pub fn list_calendar_feeds(app: AppHandle) -> Vec<FeedSummary> {
+    app.state::<CalendarFeeds>()
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|feed| FeedSummary {
+            id: feed.id.clone(),
+            name: feed.name.clone(),
+            url: feed.url.clone(),
+            out_of_office: feed.out_of_office,
+            refreshed_at: feed.refreshed_at.clone(),
+            last_error: feed.last_error.clone(),
+            event_count: feed.events.len(),
+        })
+        .collect()
+}
+
+/// Subscribes to an ICS feed; its events are fetched on the next refresh.
+#[tauri::command]
+pub fn add_calendar_feed(app: AppHandle, name: String, url: String, out_of_office: bool) -> Result<String, String> {
+    let url = url.trim().to_string();
+    let valid = ["https://", "http://", "webcal://"].iter().any(|scheme| url.starts_with(scheme));
+    if !valid {
+        return Err(format!("Invalid feed URL \"{}\"", url));
+    }
+    let name = match name.trim() {
+        "" => url.clone(),
+        name => name.to_string(),
+    };
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let store = app.state::<CalendarFeeds>();
+    let mut feeds = store.0.lock().unwrap();
+    feeds.push(CalendarFeed {
+        id: id.clone(),
+        name,
+        url,
+        out_of_office,
+        refreshed_at: None,
+        last_error: None,
+        events: Vec::new(),
+    });
+    save(&app, &feeds)?;
+    Ok(id)
+}
+
+#[tauri::command]
+pub fn remove_calendar_feed(app: AppHandle, id: String) -> Result<(), String> {
+    let store = app.state::<CalendarFeeds>();
+    let mut feeds = store.0.lock().unwrap();
+    let before = feeds.len();
+    feeds.retain(|feed| feed.id != id);
+    if feeds.len() == before {
+        return Err(format!("Calendar feed not found: {}", id));
+    }
+    save(&app, &feeds)?;
+    drop(feeds);
+
+    app.state::<ReminderScheduler>().reschedule();
+    Ok(())
+}
+
+/// Refetches every feed now, as a background job. Returns the job id.
+#[tauri::command]
+pub fn refresh_calendar_feeds(app: AppHandle) -> String {
+    jobs::spawn(&app, "calendar-feeds", |job: &JobContext| refresh(&job.app, |_| true))
+}
+
+/// Events between `start` and `end` (inclusive dates), read-only.
+#[tauri::command]
+pub fn get_calendar_events(app: AppHandle, start: String, end: String) -> Result<Vec<CalendarEvent>, String> {
+    let start = parse_task_date(&start).ok_or_else(|| format!("Invalid date \"{}\"", start))?;
+    let end = parse_task_date(&end).ok_or_else(|| format!("Invalid date \"{}\"", end))?;
+    Ok(events_between(&app, start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(lines: &[&str]) -> Vec<FeedEvent> {
+        parse_ics(&lines.join("\r\n"))
+    }
+
+    #[test]
+    fn folded_lines_are_joined() {
+        let events = parse(&[
+            "BEGIN:VCALENDAR",
+            "BEGIN:VEVENT",
+            "UID:abc",
+            "SUMMARY:Quarterly planning with",
+            "  the platform team",
+            "DTSTART:20261014T090000",
+            "END:VEVENT",
+            "END:VCALENDAR",
+        ]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].summary, "Quarterly planning with the platform team");
+        assert_eq!((events[0].start.as_str(), events[0].end.as_str()), ("2026-10-14T09:00", "2026-10-14T09:00"));
+        assert!(!events[0].all_day);
+    }
+
+    #[test]
+    fn all_day_events_end_the_day_before_dtend() {
+        let events = parse(&[
+            "BEGIN:VEVENT",
+            "UID:leave",
+            "SUMMARY:Out of office",
+            "DTSTART;VALUE=DATE:20280228",
+            "DTEND;VALUE=DATE:20280302",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "UID:single",
+            "DTSTART;VALUE=DATE:20261014",
+            "DTEND;VALUE=DATE:20261015",
+            "END:VEVENT",
+        ]);
+        assert_eq!((events[0].start.as_str(), events[0].end.as_str()), ("2028-02-28", "2028-03-01"));
+        assert_eq!((events[1].start.as_str(), events[1].end.as_str()), ("2026-10-14", "2026-10-14"));
+        assert!(events.iter().all(|event| event.all_day));
+    }
+
+    #[test]
+    fn utc_times_are_converted_to_local_time() {
+        let events = parse(&["BEGIN:VEVENT", "DTSTART:20261014T230000Z", "DTEND:20261015T003000Z", "END:VEVENT"]);
+        let local = |value: &str| {
+            let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").unwrap();
+            Utc.from_utc_datetime(&naive).with_timezone(&Local).format("%Y-%m-%dT%H:%M").to_string()
+        };
+        assert_eq!(events[0].start, local("20261014T230000"));
+        assert_eq!(events[0].end, local("20261015T003000"));
+    }
+
+    #[test]
+    fn summaries_are_unescaped() {
+        let events = parse(&["BEGIN:VEVENT", r"SUMMARY:Review\, sign\; file\nsend \\ done", "DTSTART:20261014", "END:VEVENT"]);
+        assert_eq!(events[0].summary, "Review, sign; file\nsend \\ done");
+    }
+
+    #[test]
+    fn events_without_a_start_are_dropped() {
+        let events = parse(&["BEGIN:VEVENT", "UID:nostart", "SUMMARY:Floating", "END:VEVENT"]);
+        assert!(events.is_empty());
+        assert!(parse_ics("").is_empty());
+    }
+}
@@ -0,0 +1,280 @@
+//! Chat channels (Telegram, Matrix) that receive the morning and weekly
+//! digests and critical reminders, for when you're away from your desk. Bot
+//! and access tokens are kept in the keychain.
+
+use chrono::{Datelike, Weekday};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::thread;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::clock;
+use crate::daily;
+use crate::goals;
+use crate::http;
+use crate::integrations::{self, Integration};
+use crate::locale;
+use crate::log;
+use crate::metrics;
+use crate::outbox::{self, OutboxTarget};
+use crate::power;
+use crate::reminders::FiredReminder;
+use crate::report;
+use crate::secrets;
+use crate::settings::{self, ChannelSettings};
+use crate::storage;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+pub trait NotificationChannel {
+    fn kind(&self) -> ChannelKind;
+    fn name(&self) -> &'static str;
+    fn send(&self, title: &str, body: &str) -> Result<(), String>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChannelKind {
+    Telegram,
+    Matrix,
+}
+
+impl ChannelKind {
+    pub fn secret_name(self) -> &'static str {
+        match self {
+            ChannelKind::Telegram => "telegram-bot-token",
+            ChannelKind::Matrix => "matrix-access-token",
+        }
+    }
+
+    fn integration(self) -> Integration {
+        match self {
+            ChannelKind::Telegram => Integration::Telegram,
+            ChannelKind::Matrix => Integration::Matrix,
+        }
+    }
+}
+
+struct TelegramChannel {
+    bot_token: String,
+    chat_id: String,
+}
+
+impl NotificationChannel for TelegramChannel {
+    fn kind(&self) -> ChannelKind {
+        ChannelKind::Telegram
+    }
+
+    fn name(&self) -> &'static str {
+        "Telegram"
+    }
+
+    fn send(&self, title: &str, body: &str) -> Result<(), String> {
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token);
+        http::agent()
+            .post(&url)
+            .send_json(json!({
+                "chat_id": self.chat_id,
+                "text": format!("{}\n\n{}", title, body),
+            }))
+            .map_err(|e| format!("Telegram request failed: {}", e))?;
+        Ok(())
+    }
+}
+
+struct MatrixChannel {
+    homeserver_url: String,
+    room_id: String,
+    access_token: String,
+}
+
+impl NotificationChannel for MatrixChannel {
+    fn kind(&self) -> ChannelKind {
+        ChannelKind::Matrix
+    }
+
+    fn name(&self) -> &'static str {
+        "Matrix"
+    }
+
+    fn send(&self, title: &str, body: &str) -> Result<(), String> {
+        let url = format!(
+            "{}/_matrix/client/v3/rooms/{}/send/m.room.message/{}",
+            self.homeserver_url.trim_end_matches('/'),
+            http::encode_path_segment(&self.room_id),
+            uuid::Uuid::new_v4(),
+        );
+        http::agent()
+            .put(&url)
+            .header("Authorization", &format!("Bearer {}", self.access_token))
+            .send_json(json!({
+                "msgtype": "m.text",
+                "body": format!("{}\n\n{}", title, body),
+            }))
+            .map_err(|e| format!("Matrix request failed: {}", e))?;
+        Ok(())
+    }
+}
+
+/// Channels that are switched on and have a token in the keychain.
+fn enabled_channels(config: &ChannelSettings) -> Vec<Box<dyn NotificationChannel>> {
+    let mut channels: Vec<Box<dyn NotificationChannel>> = Vec::new();
+    let token = |kind: ChannelKind| secrets::get_secret(kind.secret_name()).ok().flatten();
+
+    if config.telegram.enabled {
+        if let Some(bot_token) = token(ChannelKind::Telegram) {
+            channels.push(Box::new(TelegramChannel {
+                bot_token,
+                chat_id: config.telegram.chat_id.clone(),
+            }));
+        }
+    }
+
+    if config.matrix.enabled {
+        if let Some(access_token) = token(ChannelKind::Matrix) {
+            channels.push(Box::new(MatrixChannel {
+                homeserver_url: config.matrix.homeserver_url.clone(),
+                room_id: config.matrix.room_id.clone(),
+                access_token,
+            }));
+        }
+    }
+
+    channels
+}
+
+fn send(app: &AppHandle, channel: &dyn NotificationChannel, title: &str, body: &str) -> Result<(), String> {
+    let result = metrics::time("channel_send", || channel.send(title, body));
+    if result.is_err() {
+        metrics::increment("channel_send_failures_total");
+    }
+    integrations::record(app, channel.kind().integration(), &result);
+    result.map_err(|e| format!("{}: {}", channel.name(), e))
+}
+
+/// Sends a message to every enabled channel, returning one error per failure.
+fn send_to_all(app: &AppHandle, config: &ChannelSettings, title: &str, body: &str) -> Vec<(ChannelKind, String)> {
+    enabled_channels(config)
+        .iter()
+        .filter_map(|channel| {
+            send(app, channel.as_ref(), title, body)
+                .err()
+                .map(|e| (channel.kind(), e))
+        })
+        .collect()
+}
+
+/// Like `send_to_all`, but failed sends are queued in the outbox to retry.
+fn deliver_to_all(app: &AppHandle, config: &ChannelSettings, title: &str, body: &str) {
+    for (channel, e) in send_to_all(app, config, title, body) {
+        log::error(app, format!("Failed to send \"{}\", will retry: {}", title, e));
+        let target = OutboxTarget::Channel {
+            channel,
+            title: title.to_string(),
+            body: body.to_string(),
+        };
+        outbox::enqueue(app, target, e);
+    }
+}
+
+/// Sends a queued message to one channel, if it's still enabled.
+pub fn send_one(app: &AppHandle, kind: ChannelKind, title: &str, body: &str) -> Result<(), String> {
+    let config = settings::current(app).channels;
+    let channel = enabled_channels(&config)
+        .into_iter()
+        .find(|channel| channel.kind() == kind)
+        .ok_or_else(|| format!("{:?} is no longer enabled", kind))?;
+    send(app, channel.as_ref(), title, body)
+}
+
+/// Forwards critical reminders to the chat channels in the background.
+pub fn forward_critical_reminders(app: &AppHandle, reminders: Vec<FiredReminder>) {
+    let config = settings::current(app).channels;
+    if !config.critical_reminders || reminders.is_empty() {
+        return;
+    }
+
+    let app = app.clone();
+    thread::spawn(move || {
+        for reminder in reminders {
+            deliver_to_all(&app, &config, "Afterglow reminder", &reminder.title);
+        }
+    });
+}
+
+/// Starts the thread that sends the digests to the channels.
+pub fn start(app: AppHandle) {
+    thread::spawn(move || loop {
+        send_digest_if_due(&app);
+        send_weekly_digest_if_due(&app);
+        power::sleep(&app, CHECK_INTERVAL);
+    });
+}
+
+fn send_digest_if_due(app: &AppHandle) {
+    let config = settings::current(app).channels;
+    if !config.send_digest || !daily::claim_run(app, "channel-digest", &config.digest_at) {
+        return;
+    }
+
+    let Ok(data) = storage::read_task_data(app) else {
+        return;
+    };
+    let digest = report::morning_digest(&data, clock::today(), locale::current(app));
+
+    deliver_to_all(app, &config, &digest.subject, &digest.body);
+}
+
+fn send_weekly_digest_if_due(app: &AppHandle) {
+    let config = settings::current(app).channels;
+    let today = clock::today();
+    if !config.send_weekly_digest
+        || today.weekday() != Weekday::Mon
+        || !daily::claim_run(app, "channel-weekly-digest", &config.digest_at)
+    {
+        return;
+    }
+
+    let Ok(data) = storage::read_task_data(app) else {
+        return;
+    };
+    let goals = goals::active_progress(app, &data, today);
+    let weeks = settings::current(app).regional.week_numbering;
+    let digest = report::weekly_digest(&data, &goals, today, locale::current(app), weeks);
+
+    deliver_to_all(app, &config, &digest.subject, &digest.body);
+}
+
+/// Stores a channel's bot/access token in the keychain; `None` removes it.
+#[tauri::command]
+pub fn set_channel_token(channel: ChannelKind, token: Option<String>) -> Result<(), String> {
+    secrets::set_secret(channel.secret_name(), token.as_deref())
+}
+
+/// Sends a test message to every enabled channel, on a blocking thread
+/// since each send waits for the service.
+#[tauri::command]
+pub async fn test_notification_channels(app: AppHandle) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || send_test(&app))
+        .await
+        .map_err(|e| format!("Failed to test notification channels: {}", e))?
+}
+
+fn send_test(app: &AppHandle) -> Result<(), String> {
+    let config = settings::current(app).channels;
+
+    if enabled_channels(&config).is_empty() {
+        return Err("No channel is enabled with a token".to_string());
+    }
+
+    let errors: Vec<String> = send_to_all(app, &config, "Afterglow", "Test message from Afterglow")
+        .into_iter()
+        .map(|(_, e)| e)
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
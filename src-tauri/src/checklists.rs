@@ -0,0 +1,143 @@
+//! Named checklist templates ("Release checklist"), persisted in
+//! checklist_templates.json, that can be applied to any switchback so
+//! repeated sub-steps aren't retyped. Applying one appends its items to the
+//! task's `checklist`, skipping items the task already has.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Webview};
+
+use crate::disk;
+use crate::storage;
+use crate::tasks::{self, str_field};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChecklistTemplate {
+    pub name: String,
+    pub items: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct ChecklistTemplates(Mutex<Vec<ChecklistTemplate>>);
+
+fn get_templates_path(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("checklist_templates.json")
+}
+
+pub fn load(app: &AppHandle) -> Result<(), String> {
+    let path = get_templates_path(app);
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read checklist templates: {}", e))?;
+
+    let templates: Vec<ChecklistTemplate> = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse checklist templates: {}", e))?;
+
+    *app.state::<ChecklistTemplates>().0.lock().unwrap() = templates;
+    Ok(())
+}
+
+fn save(app: &AppHandle, templates: &[ChecklistTemplate]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(templates)
+        .map_err(|e| format!("Failed to serialize checklist templates: {}", e))?;
+    disk::write_atomic(&get_templates_path(app), content.as_bytes())
+        .map_err(|e| format!("Failed to save checklist templates: {}", e))
+}
+
+#[tauri::command]
+pub fn list_checklist_templates(app: AppHandle) -> Vec<ChecklistTemplate> {
+    app.state::<ChecklistTemplates>().0.lock().unwrap().clone()
+}
+
+/// Creates the template, or replaces the one with the same name.
+#[tauri::command]
+pub fn save_checklist_template(app: AppHandle, mut template: ChecklistTemplate) -> Result<ChecklistTemplate, String> {
+    template.name = template.name.trim().to_string();
+    if template.name.is_empty() {
+        return Err("A checklist template needs a name".to_string());
+    }
+    template.items = template
+        .items
+        .iter()
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect();
+    if template.items.is_empty() {
+        return Err("A checklist template needs at least one item".to_string());
+    }
+
+    let store = app.state::<ChecklistTemplates>();
+    let mut templates = store.0.lock().unwrap();
+    match templates.iter_mut().find(|t| t.name == template.name) {
+        Some(existing) => *existing = template.clone(),
+        None => templates.push(template.clone()),
+    }
+    save(&app, &templates)?;
+
+    Ok(template)
+}
+
+#[tauri::command]
+pub fn delete_checklist_template(app: AppHandle, name: String) -> Result<(), String> {
+    let store = app.state::<ChecklistTemplates>();
+    let mut templates = store.0.lock().unwrap();
+    let before = templates.len();
+    templates.retain(|t| t.name != name);
+    if templates.len() == before {
+        return Err(format!("Checklist template not found: {}", name));
+    }
+    save(&app, &templates)
+}
+
+/// Appends the template's items to the task's checklist and returns the
+/// updated task.
+#[tauri::command]
+pub fn apply_checklist_template(
+    app: AppHandle,
+    webview: Webview,
+    task_id: String,
+    name: String,
+) -> Result<Value, String> {
+    let template = app
+        .state::<ChecklistTemplates>()
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|t| t.name == name)
+        .cloned()
+        .ok_or_else(|| format!("Checklist template not found: {}", name))?;
+
+    let task = storage::update_task_data(&app, |data| {
+        let task = tasks::find_task_mut(data, &task_id)
+            .ok_or_else(|| format!("Task not found: {}", task_id))?;
+
+        let mut checklist = task
+            .get("checklist")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        for item in &template.items {
+            if !checklist.iter().any(|existing| str_field(existing, "text") == Some(item)) {
+                checklist.push(json!({
+                    "id": uuid::Uuid::new_v4().to_string(),
+                    "text": item,
+                    "done": false,
+                }));
+            }
+        }
+        task["checklist"] = Value::Array(checklist);
+        Ok(task.clone())
+    })?;
+
+    tasks::notify_changed(&app, Some(webview.label()));
+    Ok(task)
+}
@@ -0,0 +1,115 @@
+//! The time the app goes by. It's the system clock plus an offset that stays
+//! zero unless it's moved with `advance_clock` (debug builds only). That way
+//! reminders, nags, recurrence and automation rules can be run forward to
+//! reproduce a bug without waiting for it or changing the system time.
+//!
+//! Code that schedules or compares against "now" asks here rather than
+//! calling `Local::now()` itself.
+
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use serde::Serialize;
+use std::sync::atomic::{AtomicI64, Ordering};
+use tauri::{AppHandle, Manager};
+
+use crate::reminders::ReminderScheduler;
+
+/// How far the app's clock is ahead of the system's, in seconds.
+static OFFSET_SECONDS: AtomicI64 = AtomicI64::new(0);
+
+fn offset() -> Duration {
+    Duration::seconds(OFFSET_SECONDS.load(Ordering::Relaxed))
+}
+
+pub fn now() -> DateTime<Local> {
+    Local::now() + offset()
+}
+
+pub fn now_utc() -> DateTime<Utc> {
+    Utc::now() + offset()
+}
+
+pub fn today() -> NaiveDate {
+    now().date_naive()
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockState {
+    pub now: String,
+    /// Zero while the app follows the system clock
+    pub offset_minutes: i64,
+}
+
+fn state() -> ClockState {
+    ClockState {
+        now: now().to_rfc3339(),
+        offset_minutes: offset().num_minutes(),
+    }
+}
+
+/// Furthest the clock can be moved ahead. Dates far past this overflow
+/// chrono, and nothing needs more than a few years to reproduce.
+const MAX_OFFSET_DAYS: i64 = 10 * 365;
+
+/// Moves the clock `minutes` further ahead, within `MAX_OFFSET_DAYS`.
+pub fn advance(minutes: i64) -> Result<(), String> {
+    if minutes < 0 {
+        return Err("The clock only moves forward".to_string());
+    }
+    let remaining = Duration::days(MAX_OFFSET_DAYS) - offset();
+    if minutes > remaining.num_minutes() {
+        return Err(format!("The clock can be at most {} days ahead", MAX_OFFSET_DAYS));
+    }
+    OFFSET_SECONDS.fetch_add(minutes * 60, Ordering::Relaxed);
+    Ok(())
+}
+
+pub fn reset() {
+    OFFSET_SECONDS.store(0, Ordering::Relaxed);
+}
+
+fn ensure_debug_build() -> Result<(), String> {
+    if !cfg!(debug_assertions) {
+        return Err("The clock can only be moved in debug builds".to_string());
+    }
+    Ok(())
+}
+
+/// What's due changed, so the reminder thread has to look again.
+fn moved(app: &AppHandle) -> ClockState {
+    app.state::<ReminderScheduler>().reschedule();
+    state()
+}
+
+#[tauri::command]
+pub fn get_clock() -> ClockState {
+    state()
+}
+
+/// Moves the app's clock `minutes` forward.
+#[tauri::command]
+pub fn advance_clock(app: AppHandle, minutes: i64) -> Result<ClockState, String> {
+    ensure_debug_build()?;
+    advance(minutes)?;
+    Ok(moved(&app))
+}
+
+/// Goes back to the system clock.
+#[tauri::command]
+pub fn reset_clock(app: AppHandle) -> Result<ClockState, String> {
+    ensure_debug_build()?;
+    reset();
+    Ok(moved(&app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advance_rejects_offsets_past_the_bound() {
+        assert!(advance(-1).is_err());
+        assert!(advance(MAX_OFFSET_DAYS * 24 * 60 + 1).is_err());
+        assert!(advance(i64::MAX).is_err());
+    }
+}
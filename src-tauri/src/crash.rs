@@ -0,0 +1,262 @@
+//! Opt-in crash reports. When `settings.crashReports.enabled` is on, a panic
+//! writes its message, location and backtrace to crash_reports/ before the
+//! app goes down (release builds abort on any panic; debug builds only lose
+//! the panicking thread unless it's the main one). Reports never leave
+//! the machine unless the user sends one with `send_crash_report`, which
+//! redacts it first (see `redact`).
+//!
+//! An abort, a crash outside Rust code or a forced kill can't be caught in
+//! the process, so each run leaves a session marker that a clean exit
+//! removes. A marker still there at the next launch becomes a report without
+//! a backtrace.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::fs;
+use std::panic::{self, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::thread;
+use tauri::AppHandle;
+
+use crate::http;
+use crate::redact::redact;
+use crate::settings;
+use crate::storage;
+
+const SESSION_PREFIX: &str = "session-";
+
+/// Mirrors the setting so the panic hook doesn't need to lock anything.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// This run's session marker.
+static SESSION: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Session {
+    started_at: String,
+    app_version: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub id: String,
+    pub created_at: String,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub thread: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+}
+
+fn get_crash_dir(app: &AppHandle) -> PathBuf {
+    let dir = storage::get_app_data_dir(app).join("crash_reports");
+    fs::create_dir_all(&dir).ok();
+    dir
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Installs the panic hook and marks the session as running. Called before
+/// anything else is loaded, so it reads the opt-in from settings.json itself.
+/// The default hook still runs afterwards, so panics keep showing up on
+/// stderr.
+pub fn install(app: &AppHandle) {
+    set_enabled(settings::read_saved(app).is_ok_and(|saved| saved.is_some_and(|s| s.crash_reports.enabled)));
+
+    let crash_dir = get_crash_dir(app);
+    let app_version = app.package_info().version.to_string();
+    start_session(&crash_dir, &app_version);
+    let default_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        if ENABLED.load(Ordering::Relaxed) {
+            write_report(&crash_dir, &app_version, info);
+            // A panic that ends the app is already covered by this report
+            if cfg!(panic = "abort") || thread::current().name() == Some("main") {
+                end_session();
+            }
+        }
+        default_hook(info);
+    }));
+}
+
+fn start_session(crash_dir: &Path, app_version: &str) {
+    let path = crash_dir.join(format!("{}{}.json", SESSION_PREFIX, uuid::Uuid::new_v4()));
+    let session = Session {
+        started_at: Local::now().to_rfc3339(),
+        app_version: app_version.to_string(),
+    };
+    if let Ok(content) = serde_json::to_string(&session) {
+        if fs::write(&path, content).is_ok() {
+            SESSION.set(path).ok();
+        }
+    }
+}
+
+/// Removes this run's session marker. Called on a clean exit.
+pub fn end_session() {
+    if let Some(path) = SESSION.get() {
+        fs::remove_file(path).ok();
+    }
+}
+
+/// Turns markers left by earlier runs into reports. Only call this once it's
+/// known no other copy of the app is running, since its marker would look
+/// the same.
+pub fn report_unclean_exits(app: &AppHandle) {
+    let crash_dir = get_crash_dir(app);
+    let markers: Vec<PathBuf> = fs::read_dir(&crash_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| Some(path) != SESSION.get())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(SESSION_PREFIX))
+        })
+        .collect();
+
+    for marker in markers {
+        let session = fs::read_to_string(&marker)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Session>(&content).ok());
+        if let (Some(session), true) = (session, ENABLED.load(Ordering::Relaxed)) {
+            let now = Local::now();
+            let report = CrashReport {
+                id: format!("crash-{}", now.format("%Y%m%d-%H%M%S-%3f")),
+                created_at: now.to_rfc3339(),
+                app_version: session.app_version,
+                os: std::env::consts::OS.to_string(),
+                arch: std::env::consts::ARCH.to_string(),
+                thread: "unknown".to_string(),
+                message: format!(
+                    "The run started at {} ended without shutting down: an abort, a crash \
+                     outside Rust code, or a forced kill",
+                    session.started_at
+                ),
+                location: None,
+                backtrace: String::new(),
+            };
+            save_report(&crash_dir, &report);
+        }
+        fs::remove_file(&marker).ok();
+    }
+}
+
+/// The message a panic was raised with.
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Unknown panic".to_string())
+}
+
+fn write_report(crash_dir: &Path, app_version: &str, info: &PanicHookInfo) {
+    let message = panic_message(info.payload());
+
+    let now = Local::now();
+    let report = CrashReport {
+        id: format!("crash-{}", now.format("%Y%m%d-%H%M%S-%3f")),
+        created_at: now.to_rfc3339(),
+        app_version: app_version.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        thread: thread::current().name().unwrap_or("unnamed").to_string(),
+        message,
+        location: info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+        backtrace: Backtrace::force_capture().to_string(),
+    };
+
+    save_report(crash_dir, &report);
+}
+
+fn save_report(crash_dir: &Path, report: &CrashReport) {
+    if let Ok(content) = serde_json::to_string_pretty(report) {
+        fs::write(crash_dir.join(format!("{}.json", report.id)), content).ok();
+    }
+}
+
+fn report_path(app: &AppHandle, id: &str) -> Result<PathBuf, String> {
+    // Ids come from the frontend; don't let one escape the directory
+    if !id.starts_with("crash-") || id.contains(['/', '\\', '.']) {
+        return Err(format!("Invalid crash report id \"{}\"", id));
+    }
+    Ok(get_crash_dir(app).join(format!("{}.json", id)))
+}
+
+fn read_report(app: &AppHandle, id: &str) -> Result<CrashReport, String> {
+    let content = fs::read_to_string(report_path(app, id)?)
+        .map_err(|e| format!("Failed to read crash report: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse crash report: {}", e))
+}
+
+fn redact_report(report: CrashReport) -> CrashReport {
+    CrashReport {
+        message: redact(&report.message),
+        location: report.location.as_deref().map(redact),
+        backtrace: redact(&report.backtrace),
+        ..report
+    }
+}
+
+/// Stored crash reports, newest first.
+#[tauri::command]
+pub fn list_crash_reports(app: AppHandle) -> Vec<CrashReport> {
+    let mut reports: Vec<CrashReport> = fs::read_dir(get_crash_dir(&app))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+    reports.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    reports
+}
+
+/// The report as it would be sent, after redaction.
+#[tauri::command]
+pub fn preview_crash_report(app: AppHandle, id: String) -> Result<CrashReport, String> {
+    read_report(&app, &id).map(redact_report)
+}
+
+/// Uploads a redacted report and deletes the local copy. The upload runs
+/// off the async runtime's workers since it blocks until the server answers.
+#[tauri::command]
+pub async fn send_crash_report(app: AppHandle, id: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || upload_report(app, id))
+        .await
+        .map_err(|e| format!("Failed to send crash report: {}", e))?
+}
+
+fn upload_report(app: AppHandle, id: String) -> Result<(), String> {
+    let config = settings::current(&app).crash_reports;
+    if config.endpoint.is_empty() {
+        return Err("No crash report endpoint is configured".to_string());
+    }
+
+    let report = redact_report(read_report(&app, &id)?);
+    http::agent()
+        .post(&config.endpoint)
+        .send_json(&report)
+        .map_err(|e| format!("Failed to send crash report: {}", e))?;
+
+    delete_crash_report(app, id)
+}
+
+#[tauri::command]
+pub fn delete_crash_report(app: AppHandle, id: String) -> Result<(), String> {
+    fs::remove_file(report_path(&app, &id)?)
+        .map_err(|e| format!("Failed to delete crash report: {}", e))
+}
@@ -0,0 +1,213 @@
+//! Critical path over the `dependsOn` graph of open tasks. Durations are
+//! estimates (30 minutes for unestimated tasks) counted from now, at
+//! `WORKDAY_MINUTES` per working day; tasks that don't depend on each other
+//! may run side by side. A task's deadline is the end of its due date, or
+//! the end of the whole chain when it has none.
+//!
+//! Slack is how long a task can slip before a deadline that waits on it is
+//! missed; tasks without slack are on the critical path. Done dependencies
+//! don't count, and tasks caught in a dependency cycle are reported apart.
+
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use tauri::AppHandle;
+
+use crate::clock;
+use crate::recurrence::{format_task_date, parse_task_date};
+use crate::scoring::depends_on;
+use crate::settings;
+use crate::storage;
+use crate::tasks::{self, str_field, task_id};
+
+const WORKDAY_MINUTES: i64 = 8 * 60;
+
+const UNESTIMATED_MINUTES: i64 = 30;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledTask {
+    pub id: String,
+    pub title: String,
+    pub duration_minutes: i64,
+    /// Minutes of work from now
+    pub earliest_start: i64,
+    pub earliest_finish: i64,
+    pub latest_finish: i64,
+    pub slack_minutes: i64,
+    /// When it would be done at the earliest
+    pub projected_finish: String,
+    pub due_date: Option<String>,
+    /// No slack: any delay pushes a deadline
+    pub critical: bool,
+    /// Can't make its own due date even without delays
+    pub late: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CriticalPath {
+    /// Tasks with dependencies or dependents, in dependency order
+    pub tasks: Vec<ScheduledTask>,
+    /// Ids along the longest chain to the tightest deadline, first to last
+    pub path: Vec<String>,
+    /// Ids of tasks in a dependency cycle, left out of the schedule
+    pub cycles: Vec<String>,
+}
+
+/// Minutes of work available from now until the end of `due`.
+fn minutes_until(due: NaiveDate, today: NaiveDate, working_days: &[u32]) -> i64 {
+    let days = (0..=(due - today).num_days())
+        .map(|offset| today + Duration::days(offset))
+        .filter(|day| working_days.contains(&day.weekday().num_days_from_sunday()))
+        .count();
+    days as i64 * WORKDAY_MINUTES
+}
+
+/// The working day the `minutes`th minute of work falls on.
+fn day_of(minutes: i64, today: NaiveDate, working_days: &[u32]) -> NaiveDate {
+    let mut remaining = minutes;
+    let mut day = today;
+    for _ in 0..3660 {
+        if working_days.contains(&day.weekday().num_days_from_sunday()) {
+            if remaining <= WORKDAY_MINUTES {
+                return day;
+            }
+            remaining -= WORKDAY_MINUTES;
+        }
+        day += Duration::days(1);
+    }
+    day
+}
+
+/// Dependencies of `task` that are still open.
+fn open_dependencies<'a>(task: &'a Value, open: &HashMap<&str, &Value>) -> Vec<&'a str> {
+    let mut deps: Vec<&str> = depends_on(task).filter(|d| open.contains_key(d)).collect();
+    deps.sort_unstable();
+    deps.dedup();
+    deps
+}
+
+fn compute<'a>(all: &'a [Value], today: NaiveDate, working_days: &[u32]) -> CriticalPath {
+    let open: HashMap<&str, &Value> = all
+        .iter()
+        .filter(|t| tasks::is_open(t))
+        .filter_map(|t| Some((task_id(t)?, t)))
+        .collect();
+    let deps = |task: &'a Value| open_dependencies(task, &open);
+
+    // Only tasks linked to others take part
+    let mut successors: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut in_degree: HashMap<&str, usize> = HashMap::new();
+    for (&id, task) in &open {
+        for dep in deps(task) {
+            successors.entry(dep).or_default().push(id);
+            in_degree.entry(dep).or_insert(0);
+            *in_degree.entry(id).or_insert(0) += 1;
+        }
+    }
+
+    let mut ready: VecDeque<&str> = in_degree.iter().filter(|(_, n)| **n == 0).map(|(id, _)| *id).collect();
+    let mut ordered: Vec<&str> = Vec::new();
+    let mut remaining = in_degree;
+    while let Some(id) = ready.pop_front() {
+        ordered.push(id);
+        for next in successors.get(id).into_iter().flatten() {
+            let count = remaining.entry(next).or_default();
+            *count -= 1;
+            if *count == 0 {
+                ready.push_back(next);
+            }
+        }
+    }
+    let mut cycles: Vec<String> = remaining
+        .iter()
+        .filter(|(_, n)| **n > 0)
+        .map(|(id, _)| id.to_string())
+        .collect();
+    cycles.sort();
+
+    let duration = |id: &str| {
+        open[id]
+            .get("estimatedMinutes")
+            .and_then(Value::as_i64)
+            .filter(|m| *m > 0)
+            .unwrap_or(UNESTIMATED_MINUTES)
+    };
+
+    // Forward pass
+    let mut earliest_finish: HashMap<&str, i64> = HashMap::new();
+    let mut earliest_start: HashMap<&str, i64> = HashMap::new();
+    for &id in &ordered {
+        let start = deps(open[id]).iter().filter_map(|d| earliest_finish.get(d)).copied().max().unwrap_or(0);
+        earliest_start.insert(id, start);
+        earliest_finish.insert(id, start + duration(id));
+    }
+    let chain_end = earliest_finish.values().copied().max().unwrap_or(0);
+
+    // Backward pass
+    let deadline = |id: &str| {
+        str_field(open[id], "dueDate")
+            .and_then(parse_task_date)
+            .map(|due| minutes_until(due, today, working_days))
+    };
+    let mut latest_finish: HashMap<&str, i64> = HashMap::new();
+    for &id in ordered.iter().rev() {
+        let by_successors = successors
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter_map(|next| Some(latest_finish.get(next)? - duration(next)))
+            .min();
+        let own = deadline(id);
+        let latest = match (own, by_successors) {
+            (Some(a), Some(b)) => a.min(b),
+            (a, b) => a.or(b).unwrap_or(chain_end),
+        };
+        latest_finish.insert(id, latest);
+    }
+
+    let slack = |id: &str| latest_finish[id] - earliest_finish[id];
+
+    // Walk back from the tightest task with no dependents through the
+    // dependency that finishes last
+    let mut path = Vec::new();
+    let end = ordered
+        .iter()
+        .filter(|id| successors.get(*id).is_none_or(Vec::is_empty))
+        .min_by_key(|id| (slack(id), -earliest_finish[**id]));
+    let mut current = end.copied();
+    while let Some(id) = current {
+        path.push(id.to_string());
+        current = deps(open[id]).into_iter().max_by_key(|d| earliest_finish.get(d).copied().unwrap_or(0));
+    }
+    path.reverse();
+
+    let tasks = ordered
+        .iter()
+        .map(|&id| ScheduledTask {
+            id: id.to_string(),
+            title: str_field(open[id], "title").unwrap_or("Untitled").to_string(),
+            duration_minutes: duration(id),
+            earliest_start: earliest_start[id],
+            earliest_finish: earliest_finish[id],
+            latest_finish: latest_finish[id],
+            slack_minutes: slack(id),
+            projected_finish: format_task_date(day_of(earliest_finish[id], today, working_days)),
+            due_date: str_field(open[id], "dueDate").map(str::to_string),
+            critical: slack(id) <= 0,
+            late: deadline(id).is_some_and(|deadline| earliest_finish[id] > deadline),
+        })
+        .collect();
+
+    CriticalPath { tasks, path, cycles }
+}
+
+/// Schedules the open tasks linked by `dependsOn` and finds the critical path.
+#[tauri::command]
+pub fn get_critical_path(app: AppHandle) -> Result<CriticalPath, String> {
+    let data = storage::read_task_data(&app)?;
+    let working_days = settings::current(&app).task_defaults.working_days;
+    Ok(compute(&data.tasks, clock::today(), &working_days))
+}
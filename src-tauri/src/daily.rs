@@ -0,0 +1,60 @@
+//! Bookkeeping for jobs that run once a day after a configured time (agenda
+//! email, chat digests). The last day each job was attempted is persisted in
+//! daily_jobs.json so a restart doesn't send things twice.
+
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use crate::clock;
+use crate::recurrence::{format_task_date, parse_task_date};
+use crate::settings;
+use crate::storage;
+
+/// Serializes read-modify-write of daily_jobs.json between job threads.
+static STATE_LOCK: Mutex<()> = Mutex::new(());
+
+fn get_state_path(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("daily_jobs.json")
+}
+
+fn read_state(app: &AppHandle) -> BTreeMap<String, String> {
+    fs::read_to_string(get_state_path(app))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn last_attempt(app: &AppHandle, job: &str) -> Option<NaiveDate> {
+    read_state(app).get(job).and_then(|date| parse_task_date(date))
+}
+
+/// Returns true, and records today's attempt, if `job` hasn't run today and
+/// it's past `run_at` (`HH:MM` local time).
+///
+/// The attempt is recorded before the job runs so a failing job isn't
+/// retried every minute.
+pub fn claim_run(app: &AppHandle, job: &str, run_at: &str) -> bool {
+    let Ok(run_at) = settings::parse_clock_time(run_at) else {
+        return false;
+    };
+
+    let _guard = STATE_LOCK.lock().unwrap();
+    let now = clock::now();
+    let today = now.date_naive();
+
+    if now.time() < run_at || last_attempt(app, job) == Some(today) {
+        return false;
+    }
+
+    let mut state = read_state(app);
+    state.insert(job.to_string(), format_task_date(today));
+    if let Ok(content) = serde_json::to_string_pretty(&state) {
+        fs::write(get_state_path(app), content).ok();
+    }
+
+    true
+}
@@ -0,0 +1,409 @@
+//! Yearly dates attached to stakeholders (birthdays, contract renewals),
+//! persisted in stakeholder_dates.json. Each date owns a recurring yearly
+//! switchback: completing it lets the recurrence engine create next year's,
+//! and a background pass sets each new instance's reminder a few days ahead
+//! and recreates the switchback if it was deleted.
+
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use tauri::{AppHandle, Manager};
+
+use crate::clock;
+use crate::disk;
+use crate::log;
+use crate::power;
+use crate::recurrence::{format_task_date, parse_task_date};
+use crate::reminders::ReminderScheduler;
+use crate::storage::{self, TaskData};
+use crate::tasks::{self, now_iso, str_field, task_id};
+
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Hour of day date reminders fire at.
+const REMINDER_HOUR: u32 = 9;
+
+const MAX_REMIND_DAYS_BEFORE: u32 = 60;
+
+fn default_remind_days_before() -> u32 {
+    3
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakeholderDate {
+    pub id: String,
+    pub stakeholder: String,
+    /// e.g. "Birthday" or "Contract renewal"
+    pub label: String,
+    pub month: u32,
+    pub day: u32,
+    /// The year it started, if known
+    #[serde(default)]
+    pub year: Option<i32>,
+    #[serde(default = "default_remind_days_before")]
+    pub remind_days_before: u32,
+    /// First task of the generated chain; later instances point to it
+    /// through `parentRecurringId`
+    #[serde(default)]
+    pub task_id: Option<String>,
+    /// Due date of the instance whose reminder was last set
+    #[serde(default)]
+    pub reminded_due: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakeholderDateInput {
+    pub stakeholder: String,
+    pub label: String,
+    pub month: u32,
+    pub day: u32,
+    pub year: Option<i32>,
+    pub remind_days_before: Option<u32>,
+}
+
+impl StakeholderDateInput {
+    fn validate(&self) -> Result<(), String> {
+        if self.stakeholder.trim().is_empty() || self.label.trim().is_empty() {
+            return Err("A date needs a stakeholder and a label".to_string());
+        }
+        // 2024 is a leap year, so Feb 29 passes
+        if NaiveDate::from_ymd_opt(2024, self.month, self.day).is_none() {
+            return Err(format!("Invalid date: month {} day {}", self.month, self.day));
+        }
+        if self.remind_days_before.unwrap_or(0) > MAX_REMIND_DAYS_BEFORE {
+            return Err(format!(
+                "Reminders can be at most {} days ahead",
+                MAX_REMIND_DAYS_BEFORE
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct DatesStore(Mutex<Vec<StakeholderDate>>);
+
+fn get_dates_path(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("stakeholder_dates.json")
+}
+
+pub fn load(app: &AppHandle) -> Result<(), String> {
+    let path = get_dates_path(app);
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read stakeholder dates: {}", e))?;
+
+    let dates: Vec<StakeholderDate> = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse stakeholder dates: {}", e))?;
+
+    *app.state::<DatesStore>().0.lock().unwrap() = dates;
+    Ok(())
+}
+
+fn save(app: &AppHandle, dates: &[StakeholderDate]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(dates)
+        .map_err(|e| format!("Failed to serialize stakeholder dates: {}", e))?;
+    disk::write_atomic(&get_dates_path(app), content.as_bytes())
+        .map_err(|e| format!("Failed to save stakeholder dates: {}", e))
+}
+
+pub fn start(app: AppHandle) {
+    thread::spawn(move || loop {
+        if let Err(e) = sync(&app) {
+            log::error(&app, e);
+        }
+        power::sleep(&app, CHECK_INTERVAL);
+    });
+}
+
+/// The date in `year`; Feb 29 falls on Feb 28 outside leap years.
+fn date_in_year(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(year, month, day).or_else(|| NaiveDate::from_ymd_opt(year, month, day - 1))
+}
+
+/// The next time the date comes round, today included.
+fn next_occurrence(date: &StakeholderDate, today: NaiveDate) -> Option<NaiveDate> {
+    let this_year = date_in_year(today.year(), date.month, date.day)?;
+    if this_year >= today {
+        Some(this_year)
+    } else {
+        date_in_year(today.year() + 1, date.month, date.day)
+    }
+}
+
+/// Local `yyyy-MM-ddTHH:mm`, `remind_days_before` ahead of `due` but never
+/// before today.
+fn reminder_at(date: &StakeholderDate, due: NaiveDate, today: NaiveDate) -> String {
+    let day = (due - Duration::days(date.remind_days_before as i64)).max(today);
+    format!("{}T{:02}:00", format_task_date(day), REMINDER_HOUR)
+}
+
+fn is_in_chain(task: &Value, root: &str) -> bool {
+    task_id(task) == Some(root) || str_field(task, "parentRecurringId") == Some(root)
+}
+
+fn new_task(date: &StakeholderDate, due: NaiveDate, sort_order: i64) -> Value {
+    let notes = match date.year {
+        Some(year) => format!("{} since {}.", date.label, year),
+        None => String::new(),
+    };
+    json!({
+        "id": uuid::Uuid::new_v4().to_string(),
+        "title": format!("{}: {}", date.label, date.stakeholder),
+        "type": "recurring",
+        "priority": "p2",
+        "status": "not-started",
+        "createdAt": now_iso(),
+        "dueDate": format_task_date(due),
+        "recurrence": { "pattern": "yearly" },
+        "notes": notes,
+        "labels": [],
+        "stakeholders": [date.stakeholder],
+        "sortOrder": sort_order,
+    })
+}
+
+/// Brings the tasks in line with the dates. Returns whether anything changed.
+fn apply(dates: &mut [StakeholderDate], data: &mut TaskData, today: NaiveDate) -> bool {
+    let mut changed = false;
+
+    for date in dates.iter_mut() {
+        let Some(next) = next_occurrence(date, today) else {
+            continue;
+        };
+
+        // Recreate the switchback unless one is still open, or this year's
+        // (or a later one) was already done
+        let covered = date.task_id.as_deref().is_some_and(|root| {
+            data.tasks.iter().any(|task| {
+                is_in_chain(task, root)
+                    && (tasks::is_open(task)
+                        || str_field(task, "dueDate")
+                            .and_then(parse_task_date)
+                            .is_some_and(|due| due >= next))
+            })
+        });
+        if !covered {
+            let task = new_task(date, next, tasks::max_sort_order(data) + 1);
+            date.task_id = task_id(&task).map(str::to_string);
+            date.reminded_due = None;
+            data.tasks.push(task);
+            if !data.stakeholders.contains(&date.stakeholder) {
+                data.stakeholders.push(date.stakeholder.clone());
+            }
+            changed = true;
+        }
+
+        let root = date.task_id.clone().unwrap_or_default();
+        let open = data
+            .tasks
+            .iter_mut()
+            .find(|task| is_in_chain(task, &root) && tasks::is_open(task));
+        let Some(open) = open else {
+            continue;
+        };
+        let Some(due) = str_field(open, "dueDate").and_then(parse_task_date) else {
+            continue;
+        };
+
+        // Once per instance, so a reminder the user cleared stays cleared
+        let due_key = format_task_date(due);
+        if date.reminded_due.as_deref() != Some(due_key.as_str()) {
+            open["reminderAt"] = json!(reminder_at(date, due, today));
+            date.reminded_due = Some(due_key);
+            changed = true;
+        }
+    }
+
+    changed
+}
+
+/// Runs `apply` and saves both files if it changed anything.
+pub fn sync(app: &AppHandle) -> Result<(), String> {
+    let store = app.state::<DatesStore>();
+    let mut dates = store.0.lock().unwrap();
+    let today = clock::today();
+
+    // Dry run first so an hourly check with nothing to do doesn't save
+    let mut preview = dates.clone();
+    if !apply(&mut preview, &mut storage::read_task_data(app)?, today) {
+        return Ok(());
+    }
+
+    tasks::modify_task_data(app, |data| {
+        apply(&mut dates, data, today);
+        Ok(())
+    })?;
+    save(app, &dates)?;
+    app.state::<ReminderScheduler>().reschedule();
+    Ok(())
+}
+
+/// Deletes the open instances of a date's switchback; completed ones stay
+/// as history.
+fn remove_open_tasks(app: &AppHandle, date: &StakeholderDate) -> Result<(), String> {
+    let Some(root) = date.task_id.as_deref() else {
+        return Ok(());
+    };
+    tasks::modify_task_data(app, |data| {
+        data.tasks.retain(|task| !(is_in_chain(task, root) && tasks::is_open(task)));
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn list_stakeholder_dates(app: AppHandle, stakeholder: Option<String>) -> Vec<StakeholderDate> {
+    let dates = app.state::<DatesStore>().0.lock().unwrap().clone();
+    dates
+        .into_iter()
+        .filter(|date| stakeholder.as_ref().is_none_or(|name| &date.stakeholder == name))
+        .collect()
+}
+
+#[tauri::command]
+pub fn add_stakeholder_date(
+    app: AppHandle,
+    input: StakeholderDateInput,
+) -> Result<StakeholderDate, String> {
+    input.validate()?;
+    let date = StakeholderDate {
+        id: uuid::Uuid::new_v4().to_string(),
+        stakeholder: input.stakeholder.trim().to_string(),
+        label: input.label.trim().to_string(),
+        month: input.month,
+        day: input.day,
+        year: input.year,
+        remind_days_before: input.remind_days_before.unwrap_or_else(default_remind_days_before),
+        task_id: None,
+        reminded_due: None,
+    };
+
+    {
+        let store = app.state::<DatesStore>();
+        let mut dates = store.0.lock().unwrap();
+        dates.push(date.clone());
+        save(&app, &dates)?;
+    }
+    sync(&app)?;
+
+    Ok(list_stakeholder_dates(app, None)
+        .into_iter()
+        .find(|d| d.id == date.id)
+        .unwrap_or(date))
+}
+
+/// Replaces a date's details. Its open switchback is replaced with one for
+/// the new date.
+#[tauri::command]
+pub fn update_stakeholder_date(
+    app: AppHandle,
+    id: String,
+    input: StakeholderDateInput,
+) -> Result<StakeholderDate, String> {
+    input.validate()?;
+    let previous = {
+        let store = app.state::<DatesStore>();
+        let mut dates = store.0.lock().unwrap();
+        let date = dates
+            .iter_mut()
+            .find(|d| d.id == id)
+            .ok_or_else(|| format!("Date not found: {}", id))?;
+        let previous = date.clone();
+
+        date.stakeholder = input.stakeholder.trim().to_string();
+        date.label = input.label.trim().to_string();
+        date.month = input.month;
+        date.day = input.day;
+        date.year = input.year;
+        date.remind_days_before = input.remind_days_before.unwrap_or(date.remind_days_before);
+        // Start a fresh chain
+        date.task_id = None;
+        date.reminded_due = None;
+        save(&app, &dates)?;
+        previous
+    };
+
+    remove_open_tasks(&app, &previous)?;
+    sync(&app)?;
+
+    list_stakeholder_dates(app, None)
+        .into_iter()
+        .find(|d| d.id == id)
+        .ok_or_else(|| format!("Date not found: {}", id))
+}
+
+#[tauri::command]
+pub fn remove_stakeholder_date(app: AppHandle, id: String) -> Result<(), String> {
+    let removed = {
+        let store = app.state::<DatesStore>();
+        let mut dates = store.0.lock().unwrap();
+        let index = dates
+            .iter()
+            .position(|d| d.id == id)
+            .ok_or_else(|| format!("Date not found: {}", id))?;
+        let removed = dates.remove(index);
+        save(&app, &dates)?;
+        removed
+    };
+
+    remove_open_tasks(&app, &removed)?;
+    app.state::<ReminderScheduler>().reschedule();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock;
+
+    fn due_date(task: &Value) -> Option<NaiveDate> {
+        str_field(task, "dueDate").and_then(parse_task_date)
+    }
+
+    #[test]
+    fn a_month_past_a_date_brings_next_years_switchback() {
+        let birthday = clock::today() + Duration::days(10);
+        let mut dates = vec![StakeholderDate {
+            id: "date-1".to_string(),
+            stakeholder: "Ana".to_string(),
+            label: "Birthday".to_string(),
+            month: birthday.month(),
+            day: birthday.day(),
+            year: None,
+            remind_days_before: 3,
+            task_id: None,
+            reminded_due: None,
+        }];
+        let mut data = TaskData::default();
+
+        assert!(apply(&mut dates, &mut data, clock::today()));
+        assert_eq!(data.tasks.len(), 1);
+        assert_eq!(due_date(&data.tasks[0]), Some(birthday));
+        let reminder = format!("{}T09:00", format_task_date(birthday - Duration::days(3)));
+        assert_eq!(str_field(&data.tasks[0], "reminderAt"), Some(reminder.as_str()));
+
+        data.tasks[0]["status"] = json!("done");
+        let today = clock::today() + Duration::days(30);
+
+        assert!(apply(&mut dates, &mut data, today));
+        let next = date_in_year(birthday.year() + 1, birthday.month(), birthday.day()).unwrap();
+        let open: Vec<&Value> = data.tasks.iter().filter(|task| tasks::is_open(task)).collect();
+        assert_eq!(open.len(), 1);
+        assert_eq!(due_date(open[0]), Some(next));
+        let reminder = format!("{}T09:00", format_task_date(next - Duration::days(3)));
+        assert_eq!(str_field(open[0], "reminderAt"), Some(reminder.as_str()));
+
+        // Nothing left to do until the clock moves again
+        assert!(!apply(&mut dates, &mut data, today));
+    }
+}
@@ -0,0 +1,307 @@
+//! Disk-level helpers: Windows long-path handling, retrying transient IO
+//! failures (network shares), free-space checks and atomic file replacement.
+
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+/// Space a save must leave free on top of the file itself.
+pub const SAVE_HEADROOM: u64 = 1024 * 1024;
+
+/// Backups are skipped unless this much stays free, so they never eat the
+/// space the next save needs.
+pub const BACKUP_HEADROOM: u64 = 50 * 1024 * 1024;
+
+/// Attempts (including the first) before a transient failure is given up on.
+const IO_ATTEMPTS: u32 = 4;
+
+/// Delay before the first retry; doubles after each attempt.
+const IO_INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Windows error codes for locked files and flaky network shares.
+#[cfg(windows)]
+const TRANSIENT_OS_ERRORS: &[i32] = &[
+    32,   // ERROR_SHARING_VIOLATION
+    33,   // ERROR_LOCK_VIOLATION
+    53,   // ERROR_BAD_NETPATH
+    54,   // ERROR_NETWORK_BUSY
+    55,   // ERROR_DEV_NOT_EXIST
+    59,   // ERROR_UNEXP_NET_ERR
+    64,   // ERROR_NETNAME_DELETED
+    121,  // ERROR_SEM_TIMEOUT
+    1231, // ERROR_NETWORK_UNREACHABLE
+    1236, // ERROR_CONNECTION_ABORTED
+];
+
+#[cfg(not(windows))]
+const TRANSIENT_OS_ERRORS: &[i32] = &[];
+
+/// Only warn about low disk space once per session.
+static LOW_SPACE_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Converts an absolute path to the `\\?\` extended-length form so paths
+/// longer than `MAX_PATH` (deep OneDrive folders, long exports) work on
+/// Windows. Relative paths are made absolute first since the verbatim form
+/// skips `..` and `/` normalization. Elsewhere the path is returned as-is.
+#[cfg(windows)]
+pub fn long_path(path: &Path) -> PathBuf {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    use std::path::{Component, Prefix};
+
+    let Ok(absolute) = std::path::absolute(path) else {
+        return path.to_path_buf();
+    };
+
+    // `C:\x` becomes `\\?\C:\x`, `\\server\share\x` becomes `\\?\UNC\server\share\x`
+    let (verbatim_prefix, skip) = match absolute.components().next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::Disk(_) => (r"\\?\", 0),
+            Prefix::UNC(..) => (r"\\?\UNC", 1),
+            _ => return absolute,
+        },
+        _ => return absolute,
+    };
+
+    let mut wide: Vec<u16> = verbatim_prefix.encode_utf16().collect();
+    wide.extend(absolute.as_os_str().encode_wide().skip(skip));
+    PathBuf::from(OsString::from_wide(&wide))
+}
+
+#[cfg(not(windows))]
+pub fn long_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+/// Whether an IO error is likely to go away on its own (a busy file, a
+/// network share that dropped for a moment) rather than being permanent.
+pub fn is_transient(e: &io::Error) -> bool {
+    if e.raw_os_error().is_some_and(|code| TRANSIENT_OS_ERRORS.contains(&code)) {
+        return true;
+    }
+
+    matches!(
+        e.kind(),
+        ErrorKind::Interrupted
+            | ErrorKind::WouldBlock
+            | ErrorKind::TimedOut
+            | ErrorKind::ResourceBusy
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NotConnected
+            | ErrorKind::BrokenPipe
+            | ErrorKind::HostUnreachable
+            | ErrorKind::NetworkUnreachable
+            | ErrorKind::NetworkDown
+            | ErrorKind::StaleNetworkFileHandle
+    )
+}
+
+/// Runs `op`, retrying transient failures with exponential backoff.
+pub fn retry_io<T>(mut op: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+    let mut backoff = IO_INITIAL_BACKOFF;
+
+    for _ in 1..IO_ATTEMPTS {
+        match op() {
+            Err(e) if is_transient(&e) => {
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+            result => return result,
+        }
+    }
+
+    op()
+}
+
+/// Formats an IO error for the frontend, saying whether it's worth retrying.
+pub fn describe_io_error(context: &str, e: &io::Error) -> String {
+    if is_transient(e) {
+        format!("{} (temporary problem, try again shortly): {}", context, e)
+    } else {
+        format!("{}: {}", context, e)
+    }
+}
+
+/// Whether the volume holding `dir` can take `bytes` more and still keep
+/// `headroom` free. If free space can't be determined we assume there is
+/// enough and let the write itself fail.
+pub fn has_space_for(dir: &Path, bytes: u64, headroom: u64) -> bool {
+    fs4::available_space(dir).map_or(true, |available| {
+        available >= bytes.saturating_add(headroom)
+    })
+}
+
+pub fn warn_low_space(app: &AppHandle, message: &str) {
+    if LOW_SPACE_WARNED.swap(true, Ordering::Relaxed) {
+        return;
+    }
+
+    app.notification()
+        .builder()
+        .title("Low disk space")
+        .body(message)
+        .show()
+        .ok();
+}
+
+/// Where `write_atomic` stages the new contents of `path`.
+pub fn temp_path_for(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
+}
+
+/// Writes `contents` to a temporary file next to `path` and renames it into
+/// place, so a full disk or a crash mid-write never leaves `path` truncated.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let temp_path = temp_path_for(path);
+
+    let result = File::create(&temp_path).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+
+    if let Err(e) = result {
+        fs::remove_file(&temp_path).ok();
+        return Err(e);
+    }
+
+    fs::rename(&temp_path, path)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// A fresh directory under the system temp dir, named like the folders
+    /// that caused trouble: a non-ASCII user name and a redirected OneDrive
+    /// Documents folder.
+    pub fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("afterglow-test-{}", uuid::Uuid::new_v4()))
+            .join("Zoë Müller-Ørsted")
+            .join("OneDrive - Contoso Ltd")
+            .join("文档");
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn long_path_prefixes_drive_paths() {
+        assert_eq!(long_path(Path::new(r"C:\Users\zoë\tasks.json")), PathBuf::from(r"\\?\C:\Users\zoë\tasks.json"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn long_path_prefixes_unc_paths() {
+        assert_eq!(
+            long_path(Path::new(r"\\fileserver\home\zoë\tasks.json")),
+            PathBuf::from(r"\\?\UNC\fileserver\home\zoë\tasks.json")
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn long_path_leaves_verbatim_paths_alone() {
+        let path = Path::new(r"\\?\C:\Users\zoë\tasks.json");
+        assert_eq!(long_path(path), path);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn long_path_makes_relative_paths_absolute() {
+        let path = long_path(Path::new(r"data\..\tasks.json"));
+        assert!(path.to_string_lossy().starts_with(r"\\?\"));
+        assert!(path.ends_with("tasks.json"));
+        assert!(!path.to_string_lossy().contains(".."));
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn long_path_is_a_no_op_elsewhere() {
+        let path = Path::new("/home/zoë/.local/share/afterglow/tasks.json");
+        assert_eq!(long_path(path), path);
+    }
+
+    #[test]
+    fn write_atomic_works_in_non_ascii_folders() {
+        let dir = long_path(&scratch_dir());
+        let path = dir.join("tâches.json");
+
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, "zweite Version ✓".as_bytes()).unwrap();
+
+        assert_eq!(retry_io(|| fs::read_to_string(&path)).unwrap(), "zweite Version ✓");
+        assert!(!temp_path_for(&path).exists());
+    }
+
+    #[test]
+    fn write_atomic_handles_paths_past_max_path() {
+        let mut dir = scratch_dir();
+        while dir.as_os_str().len() < 300 {
+            dir.push("Sehr langer Ordnername für Projekte");
+        }
+        let dir = long_path(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("tasks.json");
+
+        write_atomic(&path, b"{}").unwrap();
+        assert_eq!(retry_io(|| fs::read(&path)).unwrap(), b"{}");
+    }
+
+    #[test]
+    fn write_atomic_keeps_the_old_file_when_it_fails() {
+        let dir = scratch_dir();
+        let path = dir.join("tasks.json");
+        write_atomic(&path, b"old").unwrap();
+
+        // A directory where the temp file should go makes the write fail
+        fs::create_dir(temp_path_for(&path)).unwrap();
+        assert!(write_atomic(&path, b"new").is_err());
+        assert_eq!(fs::read(&path).unwrap(), b"old");
+    }
+
+    #[test]
+    fn retry_io_retries_transient_errors() {
+        let calls = Cell::new(0);
+        let result = retry_io(|| {
+            calls.set(calls.get() + 1);
+            if calls.get() < 3 {
+                Err(io::Error::from(ErrorKind::Interrupted))
+            } else {
+                Ok(calls.get())
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[test]
+    fn retry_io_gives_up_on_permanent_errors() {
+        let calls = Cell::new(0);
+        let result: io::Result<()> = retry_io(|| {
+            calls.set(calls.get() + 1);
+            Err(io::Error::from(ErrorKind::PermissionDenied))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn retry_io_stops_after_the_last_attempt() {
+        let calls = Cell::new(0);
+        let result: io::Result<()> = retry_io(|| {
+            calls.set(calls.get() + 1);
+            Err(io::Error::from(ErrorKind::TimedOut))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls.get(), IO_ATTEMPTS);
+    }
+}
@@ -0,0 +1,94 @@
+//! Fuzzy title matching, used to warn about near-duplicate switchbacks.
+//!
+//! Titles are compared after lowercasing and dropping punctuation. Two titles
+//! match when they share most of their words, or when one is a small edit of
+//! the other ("Send invoice" / "Send invoices").
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+use crate::tasks::{self, str_field, task_id};
+
+/// Similarity (0-1) at or above which a task counts as a possible duplicate.
+const MIN_SIMILARITY: f64 = 0.75;
+
+const MAX_CANDIDATES: usize = 5;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateCandidate {
+    pub id: String,
+    pub title: String,
+    pub due_date: Option<String>,
+    pub similarity: f64,
+}
+
+/// Lowercased words, without punctuation.
+pub fn normalize_title(title: &str) -> String {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// How alike two normalized titles are, 0-1.
+fn similarity(a: &str, b: &str) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    if a == b {
+        return 1.0;
+    }
+
+    let words_a: BTreeSet<&str> = a.split(' ').collect();
+    let words_b: BTreeSet<&str> = b.split(' ').collect();
+    let shared = words_a.intersection(&words_b).count() as f64;
+    let word_overlap = shared / words_a.union(&words_b).count() as f64;
+
+    let chars_a: Vec<char> = a.chars().collect();
+    let chars_b: Vec<char> = b.chars().collect();
+    let longest = chars_a.len().max(chars_b.len()) as f64;
+    let edit_similarity = 1.0 - edit_distance(&chars_a, &chars_b) as f64 / longest;
+
+    word_overlap.max(edit_similarity)
+}
+
+/// Open tasks whose titles are close to `title`, most similar first.
+pub fn find_duplicates(tasks: &[Value], title: &str) -> Vec<DuplicateCandidate> {
+    let wanted = normalize_title(title);
+    let mut candidates: Vec<DuplicateCandidate> = tasks
+        .iter()
+        .filter(|task| tasks::is_open(task))
+        .filter_map(|task| {
+            let id = task_id(task)?;
+            let existing = str_field(task, "title")?;
+            let similarity = similarity(&wanted, &normalize_title(existing));
+            (similarity >= MIN_SIMILARITY).then(|| DuplicateCandidate {
+                id: id.to_string(),
+                title: existing.to_string(),
+                due_date: str_field(task, "dueDate").map(str::to_string),
+                similarity,
+            })
+        })
+        .collect();
+
+    candidates.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    candidates.truncate(MAX_CANDIDATES);
+    candidates
+}
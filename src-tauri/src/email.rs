@@ -0,0 +1,105 @@
+//! Optional morning agenda email sent over SMTP.
+//!
+//! A background thread sends the morning digest once a day after the
+//! configured time, so the agenda arrives even on days the main window is
+//! never opened. The SMTP password is kept in the keychain (see `secrets`).
+
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use std::thread;
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::clock;
+use crate::daily;
+use crate::integrations::{self, Integration};
+use crate::locale;
+use crate::power;
+use crate::report;
+use crate::secrets;
+use crate::settings::{self, AgendaEmailSettings, SmtpSecurity};
+use crate::storage;
+use crate::telemetry;
+
+const SMTP_PASSWORD_SECRET: &str = "smtp-password";
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+pub fn start(app: AppHandle) {
+    thread::spawn(move || loop {
+        send_if_due(&app);
+        power::sleep(&app, CHECK_INTERVAL);
+    });
+}
+
+fn send_if_due(app: &AppHandle) {
+    let config = settings::current(app).agenda_email;
+    if !config.enabled || !daily::claim_run(app, "agenda-email", &config.send_at) {
+        return;
+    }
+
+    let result = send_agenda(app, &config);
+    integrations::record(app, Integration::Email, &result);
+    if let Err(e) = result {
+        app.notification()
+            .builder()
+            .title("Couldn't send agenda email")
+            .body(&e)
+            .show()
+            .ok();
+    }
+}
+
+fn send_agenda(app: &AppHandle, config: &AgendaEmailSettings) -> Result<(), String> {
+    let data = storage::read_task_data(app)?;
+    let digest = report::morning_digest(&data, clock::today(), locale::current(app));
+
+    let message = Message::builder()
+        .from(config.from.parse().map_err(|e| format!("Invalid from address: {}", e))?)
+        .to(config.to.parse().map_err(|e| format!("Invalid to address: {}", e))?)
+        .subject(digest.subject)
+        .header(ContentType::TEXT_PLAIN)
+        .body(digest.body)
+        .map_err(|e| format!("Failed to build email: {}", e))?;
+
+    let builder = match config.security {
+        SmtpSecurity::Starttls => SmtpTransport::starttls_relay(&config.smtp_host),
+        SmtpSecurity::Tls => SmtpTransport::relay(&config.smtp_host),
+    }
+    .map_err(|e| format!("Failed to set up SMTP connection: {}", e))?
+    .port(config.smtp_port);
+
+    let builder = match secrets::get_secret(SMTP_PASSWORD_SECRET)? {
+        Some(password) => builder.credentials(Credentials::new(config.username.clone(), password)),
+        None => builder,
+    };
+
+    builder
+        .build()
+        .send(&message)
+        .map_err(|e| format!("Failed to send email: {}", e))?;
+
+    Ok(())
+}
+
+/// Stores the SMTP password in the keychain; `None` removes it.
+#[tauri::command]
+pub fn set_smtp_password(password: Option<String>) -> Result<(), String> {
+    secrets::set_secret(SMTP_PASSWORD_SECRET, password.as_deref())
+}
+
+/// Sends the agenda right away, e.g. to test the SMTP settings. SMTP
+/// blocks, so it runs on a blocking thread.
+#[tauri::command]
+pub async fn send_agenda_email(app: AppHandle) -> Result<(), String> {
+    telemetry::record(&app, "agenda-email.send");
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = send_agenda(&app, &settings::current(&app).agenda_email);
+        integrations::record(&app, Integration::Email, &result);
+        result
+    })
+    .await
+    .map_err(|e| format!("Failed to send agenda email: {}", e))?
+}
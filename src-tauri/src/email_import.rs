@@ -0,0 +1,246 @@
+//! Saved emails (.eml) dropped on the app become switchbacks: titled with
+//! the subject, with the sender as stakeholder and the email attached.
+//! Only the headers are read; the email itself stays an attachment.
+
+use chrono::{DateTime, Local};
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use tauri::{AppHandle, Webview};
+
+use crate::attachments;
+use crate::disk;
+use crate::files::{self, FilePurpose};
+use crate::locale::{self, DateStyle};
+use crate::tasks::{self, QuickAddResult};
+
+const MAX_EMAIL_BYTES: u64 = 25 * 1024 * 1024;
+
+struct EmailHeaders {
+    subject: String,
+    /// Display name, or the address when there isn't one
+    sender: Option<String>,
+    from: Option<String>,
+    date: Option<DateTime<Local>>,
+}
+
+/// Unfolded header lines, up to the blank line that ends them.
+fn header_lines(raw: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in raw.lines() {
+        if line.trim().is_empty() {
+            break;
+        }
+        match lines.last_mut() {
+            Some(previous) if line.starts_with([' ', '\t']) => {
+                previous.push(' ');
+                previous.push_str(line.trim());
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+fn header<'a>(lines: &'a [String], name: &str) -> Option<&'a str> {
+    lines.iter().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+fn decode_charset(bytes: Vec<u8>, charset: &str) -> String {
+    if charset.eq_ignore_ascii_case("iso-8859-1") || charset.eq_ignore_ascii_case("latin1") {
+        bytes.into_iter().map(char::from).collect()
+    } else {
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+}
+
+/// Decodes one `=?charset?B|Q?text?=` word (RFC 2047).
+fn decode_word(word: &str) -> Option<String> {
+    let inner = word.strip_prefix("=?")?.strip_suffix("?=")?;
+    let mut parts = inner.splitn(3, '?');
+    let (charset, encoding, text) = (parts.next()?, parts.next()?, parts.next()?);
+
+    let bytes = if encoding.eq_ignore_ascii_case("b") {
+        files::base64_decode(text)?
+    } else if encoding.eq_ignore_ascii_case("q") {
+        let text = text.replace('_', " ");
+        let mut bytes = Vec::with_capacity(text.len());
+        let mut rest = text.as_bytes();
+        while let Some((&byte, tail)) = rest.split_first() {
+            match (byte, tail.get(..2)) {
+                (b'=', Some(hex)) => {
+                    bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+                    rest = &tail[2..];
+                }
+                _ => {
+                    bytes.push(byte);
+                    rest = tail;
+                }
+            }
+        }
+        bytes
+    } else {
+        return None;
+    };
+    Some(decode_charset(bytes, charset))
+}
+
+/// Decodes the encoded words in a header value. Whitespace between two
+/// encoded words is dropped, as the RFC says.
+fn decode_header(value: &str) -> String {
+    let mut decoded = String::new();
+    let mut previous_encoded = false;
+    for (i, word) in value.split(' ').enumerate() {
+        match decode_word(word) {
+            Some(text) => {
+                if i > 0 && !previous_encoded {
+                    decoded.push(' ');
+                }
+                decoded.push_str(&text);
+                previous_encoded = true;
+            }
+            None => {
+                if i > 0 {
+                    decoded.push(' ');
+                }
+                decoded.push_str(word);
+                previous_encoded = false;
+            }
+        }
+    }
+    decoded
+}
+
+/// `"Ana Silva" <ana@example.com>` -> `Ana Silva`; a bare address is kept.
+fn sender_name(from: &str) -> Option<String> {
+    let name = match from.split_once('<') {
+        Some((name, address)) => {
+            let name = name.trim().trim_matches('"').trim();
+            if name.is_empty() {
+                address.trim_end_matches('>').trim()
+            } else {
+                name
+            }
+        }
+        None => from.trim(),
+    };
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Drops any number of leading "Re:" / "Fwd:" prefixes.
+fn strip_reply_prefixes(subject: &str) -> &str {
+    let mut subject = subject.trim();
+    loop {
+        let lower = subject.to_lowercase();
+        let Some(prefix) = ["re:", "fw:", "fwd:", "aw:"].iter().find(|p| lower.starts_with(**p)) else {
+            return subject;
+        };
+        subject = subject[prefix.len()..].trim_start();
+    }
+}
+
+fn parse_headers(raw: &str) -> EmailHeaders {
+    let lines = header_lines(raw);
+    let from = header(&lines, "from").map(decode_header);
+    let subject = header(&lines, "subject").map(decode_header).unwrap_or_default();
+    let date = header(&lines, "date")
+        .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+        .map(|date| date.with_timezone(&Local));
+
+    EmailHeaders {
+        subject: strip_reply_prefixes(&subject).to_string(),
+        sender: from.as_deref().and_then(sender_name),
+        from,
+        date,
+    }
+}
+
+/// Creates a task from a .eml file dropped on the window (or picked with
+/// `choose_file`) and attaches the email to it.
+#[tauri::command]
+pub fn import_email(app: AppHandle, webview: Webview, path: String) -> Result<QuickAddResult, String> {
+    let source = files::take_grant(&app, &path, FilePurpose::Email)?;
+    import_file(&app, &source, Some(webview.label()))
+}
+
+/// Creates the task for an email the user already chose; `window` is the
+/// label of the window it came from, if any.
+pub fn import_file(app: &AppHandle, source: &Path, window: Option<&str>) -> Result<QuickAddResult, String> {
+    let size = fs::metadata(source)
+        .map_err(|e| disk::describe_io_error("Failed to read email", &e))?
+        .len();
+    if size > MAX_EMAIL_BYTES {
+        return Err(format!("The email is too large (limit {} MB)", MAX_EMAIL_BYTES / (1024 * 1024)));
+    }
+    let bytes = disk::retry_io(|| fs::read(source))
+        .map_err(|e| disk::describe_io_error("Failed to read email", &e))?;
+    let headers = parse_headers(&String::from_utf8_lossy(&bytes));
+
+    let title = if headers.subject.is_empty() { "(no subject)" } else { &headers.subject };
+    let mut notes = Vec::new();
+    if let Some(from) = &headers.from {
+        notes.push(format!("From: {}", from));
+    }
+    if let Some(date) = headers.date {
+        let locale = locale::current(app);
+        notes.push(format!("Sent: {}", locale.format_date(date.date_naive(), DateStyle::Medium)));
+    }
+
+    let mut task = json!({ "title": title });
+    if !notes.is_empty() {
+        task["notes"] = json!(notes.join("\n"));
+    }
+    if let Some(sender) = headers.sender {
+        task["stakeholders"] = json!([sender]);
+    }
+
+    let result = tasks::add_task(app, task, window)?;
+    let id = tasks::task_id(&result.task).unwrap_or_default();
+    attachments::copy_into(app, id, source)
+        .map_err(|e| format!("The task was created, but the email couldn't be attached: {}", e))?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_base64_and_quoted_printable_words() {
+        assert_eq!(decode_header("=?UTF-8?B?Q2Fmw6k=?= order"), "Café order");
+        assert_eq!(decode_header("Re: =?utf-8?q?Caf=C3=A9_au_lait?="), "Re: Café au lait");
+        assert_eq!(decode_header("=?iso-8859-1?Q?Jos=E9?= Silva"), "José Silva");
+    }
+
+    #[test]
+    fn whitespace_between_encoded_words_is_dropped() {
+        assert_eq!(decode_header("=?UTF-8?Q?Quarterly_?= =?UTF-8?Q?review?="), "Quarterly review");
+        assert_eq!(decode_header("=?UTF-8?X?bad?= plain"), "=?UTF-8?X?bad?= plain");
+        assert_eq!(decode_header(""), "");
+    }
+
+    #[test]
+    fn sender_name_prefers_the_display_name() {
+        assert_eq!(sender_name("\"Ana Silva\" <ana@example.com>").as_deref(), Some("Ana Silva"));
+        assert_eq!(sender_name("<ana@example.com>").as_deref(), Some("ana@example.com"));
+        assert_eq!(sender_name("ana@example.com").as_deref(), Some("ana@example.com"));
+        assert_eq!(sender_name("  "), None);
+    }
+
+    #[test]
+    fn folded_headers_are_unfolded_up_to_the_body() {
+        let raw = "From: Ana <ana@example.com>\r\nSubject: Fwd: RE: Budget for\r\n\tnext quarter\r\nDate: Tue, 29 Feb 2028 09:30:00 +0000\r\n\r\nSubject: not a header";
+        let lines = header_lines(raw);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(header(&lines, "SUBJECT"), Some("Fwd: RE: Budget for next quarter"));
+
+        let headers = parse_headers(raw);
+        assert_eq!(headers.subject, "Budget for next quarter");
+        assert_eq!(headers.sender.as_deref(), Some("Ana"));
+        assert!(headers.date.is_some());
+        assert!(header_lines("").is_empty());
+    }
+}
@@ -0,0 +1,150 @@
+//! How far estimates drift from tracked time. The bias factor is tracked
+//! time over estimated time for completed tasks with both: above 1 means
+//! tasks take longer than estimated. Per-label factors need a few samples
+//! before they're trusted; otherwise the overall factor applies.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use tauri::{AppHandle, Manager};
+
+use crate::storage;
+use crate::tasks::{self, task_id};
+use crate::time_tracking::{TimeLog, TrackedTime};
+
+/// Fewer samples than this and a label falls back to the overall factor.
+const MIN_SAMPLES: usize = 3;
+
+/// Keeps one wildly off task from skewing a factor.
+const MAX_RATIO: f64 = 5.0;
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EstimationBias {
+    pub samples: usize,
+    pub estimated_minutes: i64,
+    pub tracked_minutes: i64,
+    /// `None` until there are enough samples
+    pub factor: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelBias {
+    pub label: String,
+    #[serde(flatten)]
+    pub bias: EstimationBias,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EstimationReport {
+    pub overall: EstimationBias,
+    pub labels: Vec<LabelBias>,
+}
+
+#[derive(Default)]
+struct Totals {
+    samples: usize,
+    estimated: i64,
+    tracked: i64,
+    /// Sum of per-task ratios, each capped at `MAX_RATIO`
+    ratio_sum: f64,
+}
+
+impl Totals {
+    fn add(&mut self, estimated: i64, tracked: i64) {
+        self.samples += 1;
+        self.estimated += estimated;
+        self.tracked += tracked;
+        self.ratio_sum += (tracked as f64 / estimated as f64).min(MAX_RATIO);
+    }
+
+    fn bias(&self) -> EstimationBias {
+        EstimationBias {
+            samples: self.samples,
+            estimated_minutes: self.estimated,
+            tracked_minutes: self.tracked,
+            factor: (self.samples >= MIN_SAMPLES).then(|| self.ratio_sum / self.samples as f64),
+        }
+    }
+}
+
+fn report(tasks: &[Value], tracked: &BTreeMap<String, TrackedTime>) -> EstimationReport {
+    let mut overall = Totals::default();
+    let mut by_label: BTreeMap<String, Totals> = BTreeMap::new();
+
+    for task in tasks.iter().filter(|task| tasks::is_done(task)) {
+        let estimated = task.get("estimatedMinutes").and_then(Value::as_i64).unwrap_or(0);
+        let tracked = task_id(task)
+            .and_then(|id| tracked.get(id))
+            .map_or(0, |time| time.seconds / 60);
+        if estimated <= 0 || tracked <= 0 {
+            continue;
+        }
+
+        overall.add(estimated, tracked);
+        let labels: BTreeSet<&str> = tasks::labels(task).collect();
+        for label in labels {
+            by_label.entry(label.to_string()).or_default().add(estimated, tracked);
+        }
+    }
+
+    EstimationReport {
+        overall: overall.bias(),
+        labels: by_label
+            .into_iter()
+            .map(|(label, totals)| LabelBias { label, bias: totals.bias() })
+            .collect(),
+    }
+}
+
+/// The factor to apply for a task with `labels`: the average of its labels'
+/// factors, or the overall one.
+fn factor_for(report: &EstimationReport, labels: &[String]) -> Option<f64> {
+    let label_factors: Vec<f64> = report
+        .labels
+        .iter()
+        .filter(|l| labels.contains(&l.label))
+        .filter_map(|l| l.bias.factor)
+        .collect();
+
+    if label_factors.is_empty() {
+        report.overall.factor
+    } else {
+        Some(label_factors.iter().sum::<f64>() / label_factors.len() as f64)
+    }
+}
+
+fn current_report(app: &AppHandle) -> Result<EstimationReport, String> {
+    let data = storage::read_task_data(app)?;
+    Ok(report(&data.tasks, &app.state::<TimeLog>().totals()))
+}
+
+#[tauri::command]
+pub fn get_estimation_bias(app: AppHandle) -> Result<EstimationReport, String> {
+    current_report(&app)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdjustedEstimate {
+    pub minutes: i64,
+    /// `None` when there isn't enough history to adjust
+    pub factor: Option<f64>,
+}
+
+/// Scales an estimate by the bias for `labels`, rounded to 5 minutes.
+#[tauri::command]
+pub fn adjust_estimate(
+    app: AppHandle,
+    minutes: i64,
+    labels: Vec<String>,
+) -> Result<AdjustedEstimate, String> {
+    let factor = factor_for(&current_report(&app)?, &labels);
+    let adjusted = match factor {
+        Some(factor) if minutes > 0 => (((minutes as f64 * factor) / 5.0).round() as i64 * 5).max(5),
+        _ => minutes,
+    };
+    Ok(AdjustedEstimate { minutes: adjusted, factor })
+}
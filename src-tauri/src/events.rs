@@ -0,0 +1,179 @@
+//! Task change events for the local API's WebSocket at `/v1/events`, so
+//! dashboards and Stream Deck buttons can follow changes as they happen.
+//!
+//! The backend only learns that tasks changed, not how, so while anyone is
+//! subscribed a snapshot is kept and each change is diffed against it.
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
+use std::io::Write;
+use std::net::TcpStream;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::api::Request;
+use crate::files;
+use crate::storage;
+use crate::tasks;
+
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskEventKind {
+    Created,
+    Updated,
+    Completed,
+    Deleted,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskEvent {
+    #[serde(rename = "type")]
+    pub kind: TaskEventKind,
+    pub task_id: String,
+    /// The task after the change; absent for `deleted`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<Value>,
+}
+
+#[derive(Default)]
+struct Subscribers {
+    senders: Vec<Sender<String>>,
+    snapshot: HashMap<String, Value>,
+}
+
+#[derive(Default)]
+pub struct EventStream(Mutex<Subscribers>);
+
+fn snapshot(app: &AppHandle) -> HashMap<String, Value> {
+    storage::read_task_data(app)
+        .map(|data| {
+            data.tasks
+                .into_iter()
+                .filter_map(|task| Some((tasks::task_id(&task)?.to_string(), task)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn diff(before: &HashMap<String, Value>, after: &HashMap<String, Value>) -> Vec<TaskEvent> {
+    let mut events: Vec<TaskEvent> = after
+        .iter()
+        .filter_map(|(id, task)| {
+            let kind = match before.get(id) {
+                None => TaskEventKind::Created,
+                Some(old) if old == task => return None,
+                Some(old) if tasks::is_done(task) && !tasks::is_done(old) => TaskEventKind::Completed,
+                Some(_) => TaskEventKind::Updated,
+            };
+            Some(TaskEvent {
+                kind,
+                task_id: id.clone(),
+                task: Some(task.clone()),
+            })
+        })
+        .collect();
+    events.extend(before.keys().filter(|id| !after.contains_key(*id)).map(|id| TaskEvent {
+        kind: TaskEventKind::Deleted,
+        task_id: id.clone(),
+        task: None,
+    }));
+    events
+}
+
+/// Sends subscribers what changed since the last call. Called from
+/// `tasks::notify_changed`; does nothing when nobody is listening.
+pub fn publish(app: &AppHandle) {
+    let stream = app.state::<EventStream>();
+    let mut subscribers = stream.0.lock().unwrap();
+    if subscribers.senders.is_empty() {
+        return;
+    }
+
+    let current = snapshot(app);
+    let messages: Vec<String> = diff(&subscribers.snapshot, &current)
+        .iter()
+        .filter_map(|event| serde_json::to_string(event).ok())
+        .collect();
+    subscribers.snapshot = current;
+    subscribers
+        .senders
+        .retain(|sender| messages.iter().all(|message| sender.send(message.clone()).is_ok()));
+}
+
+fn subscribe(app: &AppHandle) -> Receiver<String> {
+    let stream = app.state::<EventStream>();
+    let mut subscribers = stream.0.lock().unwrap();
+    if subscribers.senders.is_empty() {
+        subscribers.snapshot = snapshot(app);
+    }
+    let (sender, receiver) = mpsc::channel();
+    subscribers.senders.push(sender);
+    receiver
+}
+
+/// Server-to-client frames are never masked.
+fn frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        len if len < 126 => frame.push(len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// Completes the WebSocket handshake and streams events until the client
+/// goes away. Messages from the client are ignored.
+pub fn serve(app: &AppHandle, mut stream: TcpStream, request: &Request) -> Result<(), String> {
+    let key = match request.header("sec-websocket-key") {
+        Some(key) if request.header("upgrade").is_some_and(|v| v.eq_ignore_ascii_case("websocket")) => key,
+        _ => {
+            let body = r#"{"error":"Expected a WebSocket upgrade"}"#;
+            let response = format!(
+                "HTTP/1.1 400 Bad Request\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).ok();
+            return Ok(());
+        }
+    };
+
+    let accept = files::base64_encode(&Sha1::digest(format!("{}{}", key, WEBSOCKET_GUID).as_bytes()));
+    let handshake = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept
+    );
+    stream
+        .write_all(handshake.as_bytes())
+        .map_err(|e| format!("Failed to open the event stream: {}", e))?;
+
+    let receiver = subscribe(app);
+    loop {
+        let frame = match receiver.recv_timeout(PING_INTERVAL) {
+            Ok(message) => frame(0x1, message.as_bytes()),
+            // Keeps idle connections open and finds clients that left
+            Err(RecvTimeoutError::Timeout) => frame(0x9, &[]),
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        };
+        if stream.write_all(&frame).is_err() {
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,277 @@
+//! Links between Afterglow tasks and their counterparts in other systems
+//! (a Jira issue, a GitHub issue, a calendar event), persisted in
+//! external_ids.json. Sync integrations look tasks up here rather than
+//! keeping their own tables, so one place can be checked when links break.
+//!
+//! Each (system, external id) pair points at exactly one task. A link breaks
+//! when its task is deleted, or when a recurring task is completed and the
+//! chain moves on to a new instance; `repair_external_links` follows the
+//! chain where it can and drops links it can't.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::disk;
+use crate::storage::{self, TaskData};
+use crate::tasks::{self, now_iso, str_field, task_id};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalLink {
+    pub task_id: String,
+    /// e.g. "jira" or "github"
+    pub system: String,
+    pub external_id: String,
+    pub linked_at: String,
+}
+
+#[derive(Default)]
+pub struct ExternalIdStore(Mutex<Vec<ExternalLink>>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LinkProblem {
+    /// The task no longer exists
+    MissingTask,
+    /// The task was completed and its recurring chain has a newer open
+    /// instance the link should follow
+    Superseded,
+    /// The task is linked more than once in the same system
+    DuplicateTask,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokenLink {
+    #[serde(flatten)]
+    pub link: ExternalLink,
+    pub problem: LinkProblem,
+    /// Where repairing would point the link, if anywhere
+    pub suggested_task_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairSummary {
+    pub relinked: usize,
+    pub removed: usize,
+}
+
+fn get_links_path(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("external_ids.json")
+}
+
+pub fn load(app: &AppHandle) -> Result<(), String> {
+    let path = get_links_path(app);
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read external ids: {}", e))?;
+
+    let links: Vec<ExternalLink> = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse external ids: {}", e))?;
+
+    *app.state::<ExternalIdStore>().0.lock().unwrap() = links;
+    Ok(())
+}
+
+fn save(app: &AppHandle, links: &[ExternalLink]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(links)
+        .map_err(|e| format!("Failed to serialize external ids: {}", e))?;
+    disk::write_atomic(&get_links_path(app), content.as_bytes())
+        .map_err(|e| format!("Failed to save external ids: {}", e))
+}
+
+/// The chain's newest open instance, for a completed recurring task.
+fn open_successor<'a>(data: &'a TaskData, task: &Value) -> Option<&'a str> {
+    let chain = str_field(task, "parentRecurringId").or_else(|| task_id(task))?;
+    data.tasks
+        .iter()
+        .filter(|t| tasks::is_open(t))
+        .filter(|t| str_field(t, "parentRecurringId").or_else(|| task_id(t)) == Some(chain))
+        .max_by(|a, b| str_field(a, "dueDate").cmp(&str_field(b, "dueDate")))
+        .and_then(task_id)
+}
+
+fn find_broken(links: &[ExternalLink], data: &TaskData) -> Vec<BrokenLink> {
+    let by_id: HashMap<&str, &Value> = data
+        .tasks
+        .iter()
+        .filter_map(|task| Some((task_id(task)?, task)))
+        .collect();
+
+    // Newest first, so the link kept for a task linked twice is the newest
+    let mut newest_first: Vec<&ExternalLink> = links.iter().collect();
+    newest_first.sort_by(|a, b| b.linked_at.cmp(&a.linked_at));
+
+    let mut linked: HashSet<(&str, &str)> = HashSet::new();
+    let mut broken = Vec::new();
+    for link in newest_first {
+        let first = linked.insert((link.system.as_str(), link.task_id.as_str()));
+        let problem = match by_id.get(link.task_id.as_str()) {
+            None => Some((LinkProblem::MissingTask, None)),
+            Some(_) if !first => Some((LinkProblem::DuplicateTask, None)),
+            Some(task) if tasks::is_done(task) => open_successor(data, task)
+                .map(|next| (LinkProblem::Superseded, Some(next.to_string()))),
+            Some(_) => None,
+        };
+        if let Some((problem, suggested_task_id)) = problem {
+            broken.push(BrokenLink {
+                link: link.clone(),
+                problem,
+                suggested_task_id,
+            });
+        }
+    }
+    broken
+}
+
+/// All links, optionally only those for one system or one task.
+#[tauri::command]
+pub fn list_external_links(
+    app: AppHandle,
+    system: Option<String>,
+    task_id: Option<String>,
+) -> Vec<ExternalLink> {
+    app.state::<ExternalIdStore>()
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|link| system.as_ref().is_none_or(|s| &link.system == s))
+        .filter(|link| task_id.as_ref().is_none_or(|id| &link.task_id == id))
+        .cloned()
+        .collect()
+}
+
+/// Points (system, external id) at a task, replacing any existing link for it.
+#[tauri::command]
+pub fn link_external_id(
+    app: AppHandle,
+    task_id: String,
+    system: String,
+    external_id: String,
+) -> Result<ExternalLink, String> {
+    if system.trim().is_empty() || external_id.trim().is_empty() {
+        return Err("A link needs a system and an external id".to_string());
+    }
+    let data = storage::read_task_data(&app)?;
+    if !data.tasks.iter().any(|task| tasks::task_id(task) == Some(task_id.as_str())) {
+        return Err(format!("Task not found: {}", task_id));
+    }
+
+    let store = app.state::<ExternalIdStore>();
+    let mut links = store.0.lock().unwrap();
+    let link = insert_link(&mut links, task_id, &system, external_id);
+    save(&app, &links)?;
+    Ok(link)
+}
+
+fn insert_link(links: &mut Vec<ExternalLink>, task_id: String, system: &str, external_id: String) -> ExternalLink {
+    links.retain(|link| link.system != system || link.external_id != external_id);
+    let link = ExternalLink {
+        task_id,
+        system: system.to_string(),
+        external_id,
+        linked_at: now_iso(),
+    };
+    links.push(link.clone());
+    link
+}
+
+/// Task ids by external id, for one system.
+pub fn links_for(app: &AppHandle, system: &str) -> HashMap<String, String> {
+    app.state::<ExternalIdStore>()
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|link| link.system == system)
+        .map(|link| (link.external_id.clone(), link.task_id.clone()))
+        .collect()
+}
+
+/// How many links are kept, across all systems.
+pub fn link_count(app: &AppHandle) -> usize {
+    app.state::<ExternalIdStore>().0.lock().unwrap().len()
+}
+
+/// Records (task id, external id) links for one system.
+pub fn add_links(app: &AppHandle, system: &str, new_links: Vec<(String, String)>) -> Result<(), String> {
+    if new_links.is_empty() {
+        return Ok(());
+    }
+    let store = app.state::<ExternalIdStore>();
+    let mut links = store.0.lock().unwrap();
+    for (task_id, external_id) in new_links {
+        insert_link(&mut links, task_id, system, external_id);
+    }
+    save(app, &links)
+}
+
+#[tauri::command]
+pub fn unlink_external_id(app: AppHandle, system: String, external_id: String) -> Result<(), String> {
+    let store = app.state::<ExternalIdStore>();
+    let mut links = store.0.lock().unwrap();
+    let before = links.len();
+    links.retain(|link| link.system != system || link.external_id != external_id);
+    if links.len() == before {
+        return Err(format!("No link for {} {}", system, external_id));
+    }
+    save(&app, &links)
+}
+
+/// Links whose task is gone, superseded, or linked twice in one system.
+#[tauri::command]
+pub fn check_external_links(app: AppHandle) -> Result<Vec<BrokenLink>, String> {
+    let data = storage::read_task_data(&app)?;
+    let links = app.state::<ExternalIdStore>().0.lock().unwrap().clone();
+    Ok(find_broken(&links, &data))
+}
+
+/// Moves superseded links to the chain's open instance and removes the rest
+/// of the broken links.
+#[tauri::command]
+pub fn repair_external_links(app: AppHandle) -> Result<RepairSummary, String> {
+    let data = storage::read_task_data(&app)?;
+    let store = app.state::<ExternalIdStore>();
+    let mut links = store.0.lock().unwrap();
+    let broken = find_broken(&links, &data);
+    let mut summary = RepairSummary { relinked: 0, removed: 0 };
+    if broken.is_empty() {
+        return Ok(summary);
+    }
+
+    for fix in broken {
+        let position = links.iter().position(|link| {
+            link.system == fix.link.system
+                && link.external_id == fix.link.external_id
+                && link.task_id == fix.link.task_id
+        });
+        let Some(position) = position else {
+            continue;
+        };
+        match fix.suggested_task_id {
+            Some(next) => {
+                links[position].task_id = next;
+                links[position].linked_at = now_iso();
+                summary.relinked += 1;
+            }
+            None => {
+                links.remove(position);
+                summary.removed += 1;
+            }
+        }
+    }
+
+    save(&app, &links)?;
+    Ok(summary)
+}
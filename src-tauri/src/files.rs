@@ -0,0 +1,376 @@
+//! Access to the user's files outside the data directory. The webview has no
+//! fs or dialog permissions: it asks the backend to show a file dialog with
+//! `choose_file`, and the path the user picks is granted once, for that
+//! purpose only. Commands that read or write user files redeem a grant with
+//! `take_grant` and refuse any other path.
+//!
+//! `read_user_file` hands the frontend parsed content instead of file access,
+//! which lets the webview run under a strict CSP; images come back as data
+//! URLs.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::DialogExt;
+
+use crate::disk;
+use crate::storage::{self, TaskData};
+
+/// Older grants are dropped once this many are outstanding.
+const MAX_GRANTS: usize = 16;
+
+const MAX_READ_BYTES: u64 = 10 * 1024 * 1024;
+
+const MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FilePurpose {
+    Import,
+    Export,
+    Attachment,
+    /// A spreadsheet of switchbacks to import
+    CsvImport,
+    Image,
+    /// A recording to transcribe
+    Audio,
+    /// A saved email to turn into a task
+    Email,
+    /// Where to save a report as CSV
+    CsvExport,
+}
+
+impl FilePurpose {
+    /// Allowed extensions; `None` allows any file.
+    fn extensions(self) -> Option<&'static [&'static str]> {
+        match self {
+            FilePurpose::Import | FilePurpose::Export => Some(&["json"]),
+            FilePurpose::Attachment => None,
+            FilePurpose::CsvImport | FilePurpose::CsvExport => Some(&["csv"]),
+            FilePurpose::Image => Some(&["png", "jpg", "jpeg", "gif", "webp"]),
+            FilePurpose::Audio => Some(&["wav", "mp3", "ogg", "flac"]),
+            FilePurpose::Email => Some(&["eml"]),
+        }
+    }
+
+    fn filter_name(self) -> &'static str {
+        match self {
+            FilePurpose::Import | FilePurpose::Export => "Afterglow data",
+            FilePurpose::Attachment => "All files",
+            FilePurpose::CsvImport | FilePurpose::CsvExport => "CSV",
+            FilePurpose::Image => "Images",
+            FilePurpose::Audio => "Audio",
+            FilePurpose::Email => "Email",
+        }
+    }
+
+    fn is_save(self) -> bool {
+        matches!(self, FilePurpose::Export | FilePurpose::CsvExport)
+    }
+}
+
+/// Paths the user picked and no command has used yet.
+#[derive(Default)]
+pub struct FileGrants(Mutex<Vec<(PathBuf, FilePurpose)>>);
+
+/// Resolves symlinks, or for a file that doesn't exist yet, its folder.
+fn canonical(path: &Path) -> Option<PathBuf> {
+    path.canonicalize().ok().or_else(|| {
+        let parent = path.parent()?.canonicalize().ok()?;
+        Some(parent.join(path.file_name()?))
+    })
+}
+
+/// Folders the app manages itself; user files never point into them.
+fn protected_dirs(app: &AppHandle) -> Vec<PathBuf> {
+    let mut dirs = vec![storage::get_app_data_dir(app)];
+    dirs.extend(app.path().app_local_data_dir().ok());
+    dirs.iter().filter_map(|dir| dir.canonicalize().ok()).collect()
+}
+
+fn validate(app: &AppHandle, path: &Path, purpose: FilePurpose) -> Result<PathBuf, String> {
+    let invalid = |reason: &str| format!("Can't use {}: {}", path.display(), reason);
+
+    if !path.is_absolute() || path.components().any(|c| c == Component::ParentDir) {
+        return Err(invalid("not an absolute path"));
+    }
+
+    if let Some(extensions) = purpose.extensions() {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if !extensions.contains(&extension.as_str()) {
+            return Err(invalid(&format!("expected a .{} file", extensions.join(", ."))));
+        }
+    }
+
+    let resolved = canonical(path).ok_or_else(|| invalid("the folder doesn't exist"))?;
+    if protected_dirs(app).iter().any(|dir| resolved.starts_with(dir)) {
+        return Err(invalid("it's inside Afterglow's data folder"));
+    }
+    if !purpose.is_save() && !resolved.is_file() {
+        return Err(invalid("not a file"));
+    }
+
+    Ok(disk::long_path(&resolved))
+}
+
+fn grant(app: &AppHandle, path: PathBuf, purpose: FilePurpose) {
+    let grants = app.state::<FileGrants>();
+    let mut grants = grants.0.lock().unwrap();
+    grants.retain(|(granted, _)| granted != &path);
+    grants.push((path, purpose));
+    if grants.len() > MAX_GRANTS {
+        grants.remove(0);
+    }
+}
+
+/// Redeems the grant for `path`, re-validating it in case the file changed
+/// since it was picked.
+pub fn take_grant(app: &AppHandle, path: &str, purpose: FilePurpose) -> Result<PathBuf, String> {
+    find_grant(app, path, purpose, true)
+}
+
+/// Like `take_grant`, but leaves the grant for a later command.
+pub fn check_grant(app: &AppHandle, path: &str, purpose: FilePurpose) -> Result<PathBuf, String> {
+    find_grant(app, path, purpose, false)
+}
+
+fn find_grant(
+    app: &AppHandle,
+    path: &str,
+    purpose: FilePurpose,
+    consume: bool,
+) -> Result<PathBuf, String> {
+    let resolved = validate(app, Path::new(path), purpose)?;
+
+    let grants = app.state::<FileGrants>();
+    let mut grants = grants.0.lock().unwrap();
+    let index = grants
+        .iter()
+        .position(|(granted, granted_for)| granted == &resolved && *granted_for == purpose)
+        .ok_or_else(|| format!("{} wasn't chosen for this; pick it again", path))?;
+    if consume {
+        grants.remove(index);
+    }
+
+    Ok(resolved)
+}
+
+/// Grants files dropped onto a window for each purpose they suit. The paths
+/// come from the OS rather than the webview, so a drop counts as picking.
+pub fn grant_dropped(app: &AppHandle, paths: &[PathBuf]) {
+    let purposes = [
+        FilePurpose::Import,
+        FilePurpose::CsvImport,
+        FilePurpose::Image,
+        FilePurpose::Audio,
+        FilePurpose::Email,
+        FilePurpose::Attachment,
+    ];
+    for path in paths {
+        for purpose in purposes {
+            if let Ok(resolved) = validate(app, path, purpose) {
+                grant(app, resolved, purpose);
+            }
+        }
+    }
+}
+
+/// Shows an open (or, for exports, save) dialog and grants the picked path.
+/// Returns `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn choose_file(
+    app: AppHandle,
+    purpose: FilePurpose,
+    suggested_name: Option<String>,
+) -> Result<Option<String>, String> {
+    let mut dialog = app.dialog().file();
+    if let Some(extensions) = purpose.extensions() {
+        dialog = dialog.add_filter(purpose.filter_name(), extensions);
+    }
+
+    // Async commands run off the main thread, so the blocking dialogs are fine
+    let picked = if purpose.is_save() {
+        let default_name = match purpose {
+            FilePurpose::CsvExport => "afterglow-report.csv",
+            _ => "afterglow-export.json",
+        };
+        dialog
+            .set_file_name(suggested_name.as_deref().unwrap_or(default_name))
+            .blocking_save_file()
+    } else {
+        dialog.blocking_pick_file()
+    };
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+
+    let path = picked
+        .into_path()
+        .map_err(|e| format!("Failed to read the chosen path: {}", e))?;
+    let path = validate(&app, &path, purpose)?;
+    let display = path.to_string_lossy().to_string();
+    grant(&app, path, purpose);
+
+    Ok(Some(display))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum UserFile {
+    /// An Afterglow export, e.g. to preview before importing
+    Tasks { data: TaskData },
+    Csv { headers: Vec<String>, rows: Vec<Vec<String>> },
+    #[serde(rename_all = "camelCase")]
+    Image { data_url: String },
+}
+
+/// Reads a file chosen with `choose_file` and returns it parsed for its
+/// purpose. The grant stays, so an import can be previewed and then run.
+#[tauri::command]
+pub fn read_user_file(
+    app: AppHandle,
+    path: String,
+    purpose: FilePurpose,
+) -> Result<UserFile, String> {
+    let limit = match purpose {
+        FilePurpose::Import | FilePurpose::CsvImport => MAX_READ_BYTES,
+        FilePurpose::Image => MAX_IMAGE_BYTES,
+        FilePurpose::Export
+        | FilePurpose::CsvExport
+        | FilePurpose::Attachment
+        | FilePurpose::Audio
+        | FilePurpose::Email => {
+            return Err("Files chosen for this can't be read back".to_string())
+        }
+    };
+
+    let path = check_grant(&app, &path, purpose)?;
+    let size = fs::metadata(&path)
+        .map_err(|e| disk::describe_io_error("Failed to read file", &e))?
+        .len();
+    if size > limit {
+        return Err(format!("The file is too large (limit {} MB)", limit / (1024 * 1024)));
+    }
+    let bytes = disk::retry_io(|| fs::read(&path))
+        .map_err(|e| disk::describe_io_error("Failed to read file", &e))?;
+
+    match purpose {
+        FilePurpose::Import => serde_json::from_slice(&bytes)
+            .map(|data| UserFile::Tasks { data })
+            .map_err(|e| format!("Failed to parse import file: {}", e)),
+        FilePurpose::CsvImport => {
+            let text = String::from_utf8(bytes)
+                .map_err(|_| "The CSV file isn't valid UTF-8".to_string())?;
+            let mut records = parse_csv(text.trim_start_matches('\u{feff}'));
+            let headers = if records.is_empty() { Vec::new() } else { records.remove(0) };
+            Ok(UserFile::Csv { headers, rows: records })
+        }
+        _ => {
+            let mime = image_mime(&bytes).ok_or_else(|| "Not a supported image".to_string())?;
+            Ok(UserFile::Image {
+                data_url: format!("data:{};base64,{}", mime, base64_encode(&bytes)),
+            })
+        }
+    }
+}
+
+/// Sniffs the format rather than trusting the extension.
+fn image_mime(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() > 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// RFC 4180 records: quoted fields may hold commas, newlines and `""`.
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    // Blank lines aren't records
+    records.retain(|r| !(r.len() == 1 && r[0].is_empty()));
+    records
+}
+
+/// Decodes standard base64, skipping whitespace. Returns `None` for
+/// anything else that isn't in the alphabet.
+pub fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(text.len() / 4 * 3);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ => return None,
+        };
+        buffer = buffer << 6 | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+        }
+    }
+    Some(decoded)
+}
+
+pub fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
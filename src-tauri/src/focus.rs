@@ -0,0 +1,210 @@
+//! The focus task and its timer, shown in the mini widget. The timer lives
+//! in memory for the session, but every finished run is added to the task's
+//! tracked time. The focus task falls back to the most pressing open task
+//! when none was picked.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::announcements;
+use crate::clock;
+use crate::recurrence::parse_task_date;
+use crate::reminders::ReminderScheduler;
+use crate::storage;
+use crate::tasks::{self, str_field, task_id};
+use crate::telemetry;
+use crate::time_tracking;
+
+/// Emitted when the focus task or timer changes.
+pub const FOCUS_CHANGED_EVENT: &str = "focus-changed";
+
+#[derive(Debug, Default)]
+struct FocusTimer {
+    task_id: Option<String>,
+    /// Set while the timer runs
+    running_since: Option<DateTime<Utc>>,
+    /// Time accumulated before the current run
+    elapsed_seconds: i64,
+}
+
+impl FocusTimer {
+    fn elapsed(&self) -> i64 {
+        self.elapsed_seconds
+            + self
+                .running_since
+                .map_or(0, |since| (Utc::now() - since).num_seconds().max(0))
+    }
+}
+
+#[derive(Default)]
+pub struct FocusState(Mutex<FocusTimer>);
+
+/// Just what the widget shows.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WidgetTask {
+    pub id: String,
+    pub title: String,
+    pub priority: String,
+    pub due_date: Option<String>,
+    pub estimated_minutes: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WidgetState {
+    pub task: Option<WidgetTask>,
+    /// Whether the task was picked, rather than suggested
+    pub pinned: bool,
+    pub running: bool,
+    pub elapsed_seconds: i64,
+}
+
+fn widget_task(task: &Value) -> Option<WidgetTask> {
+    Some(WidgetTask {
+        id: task_id(task)?.to_string(),
+        title: str_field(task, "title").unwrap_or("Untitled").to_string(),
+        priority: str_field(task, "priority").unwrap_or("p2").to_string(),
+        due_date: str_field(task, "dueDate").map(str::to_string),
+        estimated_minutes: task.get("estimatedMinutes").and_then(Value::as_i64),
+    })
+}
+
+/// In progress first, then due soonest, then priority.
+fn suggested_task(tasks: &[Value]) -> Option<&Value> {
+    let today = clock::today();
+    tasks
+        .iter()
+        .filter(|task| tasks::is_open(task) && str_field(task, "status") != Some("someday"))
+        .filter(|task| {
+            str_field(task, "dueDate")
+                .and_then(parse_task_date)
+                .is_some_and(|due| due <= today)
+                || str_field(task, "status") == Some("in-progress")
+        })
+        .min_by_key(|task| {
+            (
+                str_field(task, "status") != Some("in-progress"),
+                str_field(task, "dueDate").and_then(parse_task_date),
+                str_field(task, "priority").unwrap_or("p2").to_string(),
+            )
+        })
+}
+
+fn emit_changed(app: &AppHandle) {
+    app.emit(FOCUS_CHANGED_EVENT, ()).ok();
+}
+
+#[tauri::command]
+pub fn get_widget_state(app: AppHandle) -> Result<WidgetState, String> {
+    let data = storage::read_task_data(&app)?;
+    let state = app.state::<FocusState>();
+    let timer = state.0.lock().unwrap();
+
+    let pinned = timer
+        .task_id
+        .as_deref()
+        .and_then(|id| data.tasks.iter().find(|task| task_id(task) == Some(id)))
+        .filter(|task| tasks::is_open(task));
+
+    Ok(WidgetState {
+        task: pinned.or_else(|| suggested_task(&data.tasks)).and_then(widget_task),
+        pinned: pinned.is_some(),
+        running: timer.running_since.is_some(),
+        elapsed_seconds: timer.elapsed(),
+    })
+}
+
+/// Stops a running timer, adding the run to the task's tracked time.
+fn end_run(app: &AppHandle, timer: &mut FocusTimer) {
+    let Some(since) = timer.running_since.take() else {
+        return;
+    };
+    let run = (Utc::now() - since).num_seconds().max(0);
+    timer.elapsed_seconds += run;
+    if let Some(id) = &timer.task_id {
+        time_tracking::record(app, id, run);
+        let data = storage::read_task_data(app).unwrap_or_default();
+        if let Some(task) = data.tasks.iter().find(|task| task_id(task) == Some(id.as_str())) {
+            announcements::timer_finished(app, id, str_field(task, "title").unwrap_or("Untitled"), run);
+        }
+    }
+}
+
+/// Makes `task_id` the focus task (`None` goes back to the suggestion) and
+/// resets the timer.
+#[tauri::command]
+pub fn set_focus_task(app: AppHandle, task_id: Option<String>) {
+    let state = app.state::<FocusState>();
+    let mut timer = state.0.lock().unwrap();
+    end_run(&app, &mut timer);
+    *timer = FocusTimer {
+        task_id,
+        ..FocusTimer::default()
+    };
+    drop(timer);
+    emit_changed(&app);
+}
+
+/// Starts the timer. Without a picked task, the suggested one is pinned so
+/// the time is tracked against it.
+#[tauri::command]
+pub fn start_focus_timer(app: AppHandle) -> Result<(), String> {
+    let data = storage::read_task_data(&app)?;
+    let state = app.state::<FocusState>();
+    let mut timer = state.0.lock().unwrap();
+
+    let pinned_is_open = timer.task_id.as_deref().is_some_and(|id| {
+        data.tasks.iter().any(|task| task_id(task) == Some(id) && tasks::is_open(task))
+    });
+    if !pinned_is_open {
+        timer.task_id = suggested_task(&data.tasks)
+            .and_then(task_id)
+            .map(str::to_string);
+    }
+    if timer.running_since.is_none() {
+        timer.running_since = Some(Utc::now());
+    }
+    drop(timer);
+    emit_changed(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn pause_focus_timer(app: AppHandle) {
+    let state = app.state::<FocusState>();
+    let mut timer = state.0.lock().unwrap();
+    end_run(&app, &mut timer);
+    drop(timer);
+    emit_changed(&app);
+}
+
+/// Zeroes the timer display. Time already run stays tracked.
+#[tauri::command]
+pub fn reset_focus_timer(app: AppHandle) {
+    let state = app.state::<FocusState>();
+    let mut timer = state.0.lock().unwrap();
+    end_run(&app, &mut timer);
+    timer.elapsed_seconds = 0;
+    drop(timer);
+    emit_changed(&app);
+}
+
+/// Completes the task shown in the widget and clears the timer.
+#[tauri::command]
+pub fn complete_focus_task(app: AppHandle, task_id: String) -> Result<(), String> {
+    {
+        let state = app.state::<FocusState>();
+        let mut timer = state.0.lock().unwrap();
+        end_run(&app, &mut timer);
+        *timer = FocusTimer::default();
+    }
+    tasks::modify_task_data(&app, |data| tasks::complete_task(data, &task_id))?;
+    app.state::<ReminderScheduler>().reschedule();
+    telemetry::record(&app, "widget.complete");
+    emit_changed(&app);
+    Ok(())
+}
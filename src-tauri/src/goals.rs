@@ -0,0 +1,256 @@
+//! Goals with key results layered on top of switchbacks, persisted in
+//! goals.json. A key result links tasks directly or through labels; its
+//! progress is computed from those tasks (by estimate when they have
+//! estimates, by count otherwise) and never stored.
+
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::clock;
+use crate::disk;
+use crate::recurrence::parse_task_date;
+use crate::storage::{self, TaskData};
+use crate::tasks::{self, now_iso, str_field, task_id};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyResult {
+    #[serde(default)]
+    pub id: String,
+    pub title: String,
+    /// Linked tasks; a recurring task's later instances count too
+    #[serde(default)]
+    pub task_ids: Vec<String>,
+    /// Every task with one of these labels counts
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Goal {
+    #[serde(default)]
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub target_date: Option<String>,
+    #[serde(default)]
+    pub key_results: Vec<KeyResult>,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub archived: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyResultProgress {
+    pub id: String,
+    pub title: String,
+    pub done_tasks: usize,
+    pub total_tasks: usize,
+    pub done_minutes: i64,
+    pub total_minutes: i64,
+    /// 0.0 to 1.0
+    pub progress: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoalProgress {
+    pub goal_id: String,
+    pub title: String,
+    pub target_date: Option<String>,
+    /// Average of the key results, 0.0 to 1.0
+    pub progress: f64,
+    /// Compares progress with the time elapsed; `None` without a target date
+    pub on_track: Option<bool>,
+    pub key_results: Vec<KeyResultProgress>,
+}
+
+#[derive(Default)]
+pub struct GoalsStore(Mutex<Vec<Goal>>);
+
+fn get_goals_path(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("goals.json")
+}
+
+pub fn load(app: &AppHandle) -> Result<(), String> {
+    let path = get_goals_path(app);
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read goals: {}", e))?;
+
+    let goals: Vec<Goal> =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse goals: {}", e))?;
+
+    *app.state::<GoalsStore>().0.lock().unwrap() = goals;
+    Ok(())
+}
+
+fn save(app: &AppHandle, goals: &[Goal]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(goals)
+        .map_err(|e| format!("Failed to serialize goals: {}", e))?;
+    disk::write_atomic(&get_goals_path(app), content.as_bytes())
+        .map_err(|e| format!("Failed to save goals: {}", e))
+}
+
+fn is_linked(task: &Value, key_result: &KeyResult) -> bool {
+    let linked_id = |id: Option<&str>| id.is_some_and(|id| key_result.task_ids.iter().any(|t| t == id));
+
+    linked_id(task_id(task))
+        || linked_id(str_field(task, "parentRecurringId"))
+        || tasks::labels(task).any(|label| key_result.labels.iter().any(|l| l == label))
+}
+
+fn key_result_progress(key_result: &KeyResult, data: &TaskData) -> KeyResultProgress {
+    // Ended recurring tasks that were never done don't count either way
+    let linked: Vec<&Value> = data
+        .tasks
+        .iter()
+        .filter(|task| is_linked(task, key_result))
+        .filter(|task| tasks::is_done(task) || !tasks::is_ended(task))
+        .collect();
+    let minutes = |task: &&Value| task.get("estimatedMinutes").and_then(Value::as_i64).unwrap_or(0);
+
+    let done_tasks = linked.iter().filter(|task| tasks::is_done(task)).count();
+    let total_minutes: i64 = linked.iter().map(minutes).sum();
+    let done_minutes: i64 = linked.iter().filter(|task| tasks::is_done(task)).map(minutes).sum();
+
+    let progress = if total_minutes > 0 {
+        done_minutes as f64 / total_minutes as f64
+    } else if !linked.is_empty() {
+        done_tasks as f64 / linked.len() as f64
+    } else {
+        0.0
+    };
+
+    KeyResultProgress {
+        id: key_result.id.clone(),
+        title: key_result.title.clone(),
+        done_tasks,
+        total_tasks: linked.len(),
+        done_minutes,
+        total_minutes,
+        progress,
+    }
+}
+
+/// Whether `progress` keeps pace with the time between creation and the
+/// target date.
+fn on_track(goal: &Goal, progress: f64, today: NaiveDate) -> Option<bool> {
+    let target = goal.target_date.as_deref().and_then(parse_task_date)?;
+    let created = DateTime::parse_from_rfc3339(&goal.created_at)
+        .ok()?
+        .with_timezone(&Local)
+        .date_naive();
+
+    let span = (target - created).num_days();
+    if span <= 0 || today >= target {
+        return Some(progress >= 1.0);
+    }
+    let elapsed = (today - created).num_days().max(0) as f64 / span as f64;
+    Some(progress >= elapsed)
+}
+
+pub fn goal_progress(goal: &Goal, data: &TaskData, today: NaiveDate) -> GoalProgress {
+    let key_results: Vec<KeyResultProgress> =
+        goal.key_results.iter().map(|kr| key_result_progress(kr, data)).collect();
+    let progress = if key_results.is_empty() {
+        0.0
+    } else {
+        key_results.iter().map(|kr| kr.progress).sum::<f64>() / key_results.len() as f64
+    };
+
+    GoalProgress {
+        goal_id: goal.id.clone(),
+        title: goal.title.clone(),
+        target_date: goal.target_date.clone(),
+        progress,
+        on_track: on_track(goal, progress, today),
+        key_results,
+    }
+}
+
+/// Progress of every goal that isn't archived.
+pub fn active_progress(app: &AppHandle, data: &TaskData, today: NaiveDate) -> Vec<GoalProgress> {
+    let goals = app.state::<GoalsStore>().0.lock().unwrap().clone();
+    goals
+        .iter()
+        .filter(|goal| !goal.archived)
+        .map(|goal| goal_progress(goal, data, today))
+        .collect()
+}
+
+fn validate(goal: &Goal) -> Result<(), String> {
+    if goal.title.trim().is_empty() {
+        return Err("A goal needs a title".to_string());
+    }
+    if let Some(target) = &goal.target_date {
+        parse_task_date(target).ok_or_else(|| format!("Invalid target date \"{}\"", target))?;
+    }
+    if goal.key_results.iter().any(|kr| kr.title.trim().is_empty()) {
+        return Err("Every key result needs a title".to_string());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_goals(app: AppHandle) -> Vec<Goal> {
+    app.state::<GoalsStore>().0.lock().unwrap().clone()
+}
+
+/// Creates the goal, or replaces the one with the same id. Empty ids (on the
+/// goal or its key results) get a fresh one.
+#[tauri::command]
+pub fn save_goal(app: AppHandle, mut goal: Goal) -> Result<Goal, String> {
+    validate(&goal)?;
+    if goal.id.is_empty() {
+        goal.id = uuid::Uuid::new_v4().to_string();
+    }
+    if goal.created_at.is_empty() {
+        goal.created_at = now_iso();
+    }
+    for key_result in &mut goal.key_results {
+        if key_result.id.is_empty() {
+            key_result.id = uuid::Uuid::new_v4().to_string();
+        }
+    }
+
+    let store = app.state::<GoalsStore>();
+    let mut goals = store.0.lock().unwrap();
+    match goals.iter_mut().find(|g| g.id == goal.id) {
+        Some(existing) => *existing = goal.clone(),
+        None => goals.push(goal.clone()),
+    }
+    save(&app, &goals)?;
+
+    Ok(goal)
+}
+
+#[tauri::command]
+pub fn delete_goal(app: AppHandle, id: String) -> Result<(), String> {
+    let store = app.state::<GoalsStore>();
+    let mut goals = store.0.lock().unwrap();
+    let before = goals.len();
+    goals.retain(|g| g.id != id);
+    if goals.len() == before {
+        return Err(format!("Goal not found: {}", id));
+    }
+    save(&app, &goals)
+}
+
+#[tauri::command]
+pub fn get_goal_progress(app: AppHandle) -> Result<Vec<GoalProgress>, String> {
+    let data = storage::read_task_data(&app)?;
+    Ok(active_progress(&app, &data, clock::today()))
+}
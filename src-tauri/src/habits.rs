@@ -0,0 +1,381 @@
+//! Habit tracking for recurring tasks marked `habit: true`. Completions are
+//! logged per day in habits.json so the history survives old instances being
+//! deleted. Each habit's history is a string with one character per day from
+//! its start date: `x` done, `s` skipped, `.` nothing recorded.
+//!
+//! Skipped days and vacation days are neutral: they neither extend nor break
+//! a streak. Days the habit isn't scheduled on (per its weekdays) are neutral
+//! too, and today only counts once it's done.
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::clock;
+use crate::disk;
+use crate::log;
+use crate::recurrence::{format_task_date, parse_task_date, RecurrencePattern, RecurrenceRule};
+use crate::storage::{self, TaskData};
+use crate::tasks::{self, str_field, task_id};
+
+const MAX_MATRIX_DAYS: i64 = 400;
+
+const DONE: char = 'x';
+const SKIPPED: char = 's';
+const EMPTY: char = '.';
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DateRange {
+    pub start: String,
+    pub end: String,
+}
+
+impl DateRange {
+    fn contains(&self, day: NaiveDate) -> bool {
+        match (parse_task_date(&self.start), parse_task_date(&self.end)) {
+            (Some(start), Some(end)) => day >= start && day <= end,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HabitHistory {
+    title: String,
+    start: String,
+    days: String,
+    /// Scheduled weekdays (0 = Sunday); `None` means every day
+    #[serde(default)]
+    weekdays: Option<Vec<u32>>,
+}
+
+impl HabitHistory {
+    fn new(title: &str, start: NaiveDate) -> Self {
+        Self {
+            title: title.to_string(),
+            start: format_task_date(start),
+            days: String::new(),
+            weekdays: None,
+        }
+    }
+
+    fn start_date(&self) -> NaiveDate {
+        parse_task_date(&self.start).unwrap_or_else(clock::today)
+    }
+
+    fn get(&self, day: NaiveDate) -> char {
+        let index = (day - self.start_date()).num_days();
+        if index < 0 {
+            return EMPTY;
+        }
+        self.days.chars().nth(index as usize).unwrap_or(EMPTY)
+    }
+
+    /// Sets `day`, moving the start back if needed. Returns whether it changed.
+    fn set(&mut self, day: NaiveDate, state: char) -> bool {
+        let start = self.start_date();
+        if day < start {
+            let gap = (start - day).num_days() as usize;
+            self.days = EMPTY.to_string().repeat(gap) + &self.days;
+            self.start = format_task_date(day);
+        }
+
+        let index = (day - self.start_date()).num_days() as usize;
+        let mut days: Vec<char> = self.days.chars().collect();
+        if days.len() <= index {
+            days.resize(index + 1, EMPTY);
+        }
+        if days[index] == state {
+            return false;
+        }
+        days[index] = state;
+        self.days = days.into_iter().collect::<String>().trim_end_matches(EMPTY).to_string();
+        true
+    }
+
+    fn is_scheduled(&self, day: NaiveDate) -> bool {
+        let weekday = day.weekday().num_days_from_sunday();
+        self.weekdays.as_ref().is_none_or(|days| days.contains(&weekday))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct HabitLog {
+    /// Keyed by the first task of the recurring chain
+    habits: BTreeMap<String, HabitHistory>,
+    vacations: Vec<DateRange>,
+}
+
+#[derive(Default)]
+pub struct HabitStore(Mutex<HabitLog>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HabitDay {
+    Done,
+    Missed,
+    Skipped,
+    Vacation,
+    /// Not a scheduled day, or before tracking started
+    Unscheduled,
+    /// Today, not done yet
+    Pending,
+    Future,
+}
+
+impl HabitDay {
+    fn breaks_streak(self) -> bool {
+        self == HabitDay::Missed
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HabitRow {
+    pub habit_id: String,
+    pub title: String,
+    /// One entry per day of the requested range
+    pub days: Vec<HabitDay>,
+    pub current_streak: u32,
+    pub longest_streak: u32,
+}
+
+fn get_habits_path(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("habits.json")
+}
+
+pub fn load(app: &AppHandle) -> Result<(), String> {
+    let path = get_habits_path(app);
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read habit history: {}", e))?;
+
+    let log: HabitLog = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse habit history: {}", e))?;
+
+    *app.state::<HabitStore>().0.lock().unwrap() = log;
+    Ok(())
+}
+
+fn save(app: &AppHandle, log: &HabitLog) -> Result<(), String> {
+    let content = serde_json::to_string(log)
+        .map_err(|e| format!("Failed to serialize habit history: {}", e))?;
+    disk::write_atomic(&get_habits_path(app), content.as_bytes())
+        .map_err(|e| format!("Failed to save habit history: {}", e))
+}
+
+fn is_habit(task: &Value) -> bool {
+    task.get("habit").and_then(Value::as_bool).unwrap_or(false)
+}
+
+fn habit_id(task: &Value) -> Option<&str> {
+    str_field(task, "parentRecurringId").or_else(|| task_id(task))
+}
+
+fn local_date(timestamp: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|at| at.with_timezone(&Local).date_naive())
+}
+
+/// Weekdays the recurrence lands on, or `None` for "every day".
+fn scheduled_weekdays(task: &Value) -> Option<Vec<u32>> {
+    let rule: RecurrenceRule = serde_json::from_value(task.get("recurrence")?.clone()).ok()?;
+    match rule.pattern {
+        RecurrencePattern::BusinessDays => Some(vec![1, 2, 3, 4, 5]),
+        RecurrencePattern::Weekly | RecurrencePattern::Biweekly => rule
+            .weekdays
+            .filter(|days| !days.is_empty())
+            .or_else(|| {
+                let due = str_field(task, "dueDate").and_then(parse_task_date)?;
+                Some(vec![due.weekday().num_days_from_sunday()])
+            }),
+        _ => None,
+    }
+}
+
+/// Logs completions of habit tasks. Called whenever the task data changes.
+pub fn record(app: &AppHandle) {
+    let Ok(data) = storage::read_task_data(app) else {
+        return;
+    };
+    let store = app.state::<HabitStore>();
+    let mut log = store.0.lock().unwrap();
+
+    if apply(&mut log, &data) {
+        if let Err(e) = save(app, &log) {
+            log::error(app, e);
+        }
+    }
+}
+
+fn apply(log: &mut HabitLog, data: &TaskData) -> bool {
+    let mut changed = false;
+
+    for task in data.tasks.iter().filter(|task| is_habit(task)) {
+        let Some(id) = habit_id(task) else {
+            continue;
+        };
+        let title = str_field(task, "title").unwrap_or("Untitled");
+        let created = str_field(task, "createdAt").and_then(local_date);
+
+        let history = log.habits.entry(id.to_string()).or_insert_with(|| {
+            changed = true;
+            HabitHistory::new(title, created.unwrap_or_else(clock::today))
+        });
+
+        if tasks::is_open(task) {
+            let weekdays = scheduled_weekdays(task);
+            if history.title != title || history.weekdays != weekdays {
+                history.title = title.to_string();
+                history.weekdays = weekdays;
+                changed = true;
+            }
+        }
+
+        if tasks::is_done(task) {
+            if let Some(day) = str_field(task, "completedAt").and_then(local_date) {
+                // Don't overwrite a deliberate skip of the same day
+                if history.get(day) == EMPTY {
+                    changed |= history.set(day, DONE);
+                }
+            }
+        }
+    }
+
+    changed
+}
+
+fn day_state(history: &HabitHistory, vacations: &[DateRange], day: NaiveDate, today: NaiveDate) -> HabitDay {
+    match history.get(day) {
+        DONE => return HabitDay::Done,
+        SKIPPED => return HabitDay::Skipped,
+        _ => {}
+    }
+    if day > today {
+        HabitDay::Future
+    } else if vacations.iter().any(|v| v.contains(day)) {
+        HabitDay::Vacation
+    } else if day < history.start_date() || !history.is_scheduled(day) {
+        HabitDay::Unscheduled
+    } else if day == today {
+        HabitDay::Pending
+    } else {
+        HabitDay::Missed
+    }
+}
+
+/// (current, longest) streaks of done days, up to today.
+fn streaks(history: &HabitHistory, vacations: &[DateRange], today: NaiveDate) -> (u32, u32) {
+    let (mut current, mut longest) = (0, 0);
+    let mut day = history.start_date();
+
+    while day <= today {
+        match day_state(history, vacations, day, today) {
+            HabitDay::Done => {
+                current += 1;
+                longest = longest.max(current);
+            }
+            state if state.breaks_streak() => current = 0,
+            _ => {}
+        }
+        day += Duration::days(1);
+    }
+
+    (current, longest)
+}
+
+/// Per-day states of every habit between `start` and `end` (inclusive), for
+/// a heatmap.
+#[tauri::command]
+pub fn get_habit_matrix(app: AppHandle, start: String, end: String) -> Result<Vec<HabitRow>, String> {
+    let range_start = parse_task_date(&start).ok_or_else(|| format!("Invalid date \"{}\"", start))?;
+    let range_end = parse_task_date(&end).ok_or_else(|| format!("Invalid date \"{}\"", end))?;
+    let length = (range_end - range_start).num_days() + 1;
+    if !(1..=MAX_MATRIX_DAYS).contains(&length) {
+        return Err(format!("The range must be 1 to {} days", MAX_MATRIX_DAYS));
+    }
+
+    record(&app);
+    let today = clock::today();
+    let log = app.state::<HabitStore>().0.lock().unwrap().clone();
+
+    Ok(log
+        .habits
+        .iter()
+        .map(|(id, history)| {
+            let (current_streak, longest_streak) = streaks(history, &log.vacations, today);
+            HabitRow {
+                habit_id: id.clone(),
+                title: history.title.clone(),
+                days: (0..length)
+                    .map(|offset| {
+                        let day = range_start + Duration::days(offset);
+                        day_state(history, &log.vacations, day, today)
+                    })
+                    .collect(),
+                current_streak,
+                longest_streak,
+            }
+        })
+        .collect())
+}
+
+/// Marks (or unmarks) a day as skipped for one habit.
+#[tauri::command]
+pub fn skip_habit_day(app: AppHandle, habit_id: String, date: String, skipped: bool) -> Result<(), String> {
+    let day = parse_task_date(&date).ok_or_else(|| format!("Invalid date \"{}\"", date))?;
+    let store = app.state::<HabitStore>();
+    let mut log = store.0.lock().unwrap();
+    let history = log
+        .habits
+        .get_mut(&habit_id)
+        .ok_or_else(|| format!("Habit not found: {}", habit_id))?;
+
+    let current = history.get(day);
+    let next = match (skipped, current) {
+        (true, _) => SKIPPED,
+        (false, SKIPPED) => EMPTY,
+        (false, state) => state,
+    };
+    if history.set(day, next) {
+        save(&app, &log)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_habit_vacations(app: AppHandle) -> Vec<DateRange> {
+    app.state::<HabitStore>().0.lock().unwrap().vacations.clone()
+}
+
+/// Replaces the vacation list. Vacation days don't break any habit's streak.
+#[tauri::command]
+pub fn set_habit_vacations(app: AppHandle, vacations: Vec<DateRange>) -> Result<(), String> {
+    for vacation in &vacations {
+        let (Some(start), Some(end)) = (parse_task_date(&vacation.start), parse_task_date(&vacation.end))
+        else {
+            return Err(format!("Invalid vacation {} to {}", vacation.start, vacation.end));
+        };
+        if end < start {
+            return Err(format!("Vacation ends before it starts: {} to {}", vacation.start, vacation.end));
+        }
+    }
+
+    let store = app.state::<HabitStore>();
+    let mut log = store.0.lock().unwrap();
+    log.vacations = vacations;
+    save(&app, &log)
+}
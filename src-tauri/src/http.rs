@@ -0,0 +1,103 @@
+//! Shared HTTP client for outbound requests. Every request goes through
+//! `agent()`, so the proxy and CA bundle from `settings.network` apply to
+//! all of them.
+
+use std::fs;
+use std::sync::Mutex;
+use std::time::Duration;
+use ureq::tls::{parse_pem, Certificate, PemItem, RootCerts, TlsConfig};
+use ureq::Proxy;
+
+use crate::settings::NetworkSettings;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Built from the network settings; `None` until settings are applied.
+static AGENT: Mutex<Option<ureq::Agent>> = Mutex::new(None);
+
+fn build_agent(network: &NetworkSettings) -> Result<ureq::Agent, String> {
+    let proxy = match network.proxy_url.trim() {
+        "" => Proxy::try_from_env(),
+        url => Some(Proxy::new(url).map_err(|e| format!("Invalid proxy \"{}\": {}", url, e))?),
+    };
+
+    let mut tls = TlsConfig::builder();
+    let ca_bundle_path = network.ca_bundle_path.trim();
+    if !ca_bundle_path.is_empty() {
+        let pem = fs::read(ca_bundle_path)
+            .map_err(|e| format!("Failed to read CA bundle {}: {}", ca_bundle_path, e))?;
+        let certs: Vec<_> = parse_pem(&pem)
+            .filter_map(|item| match item {
+                Ok(PemItem::Certificate(cert)) => Some(cert),
+                _ => None,
+            })
+            .collect();
+        if certs.is_empty() {
+            return Err(format!("No certificates found in {}", ca_bundle_path));
+        }
+        // The bundle adds to Mozilla's roots rather than replacing them, so
+        // public hosts stay reachable next to the company's own
+        let mut roots: Vec<Certificate<'static>> = webpki_root_certs::TLS_SERVER_ROOT_CERTS
+            .iter()
+            .map(|cert| Certificate::from_der(cert.as_ref()))
+            .collect();
+        roots.extend(certs);
+        tls = tls.root_certs(RootCerts::new_with_certs(&roots));
+    }
+
+    Ok(ureq::Agent::config_builder()
+        .timeout_global(Some(REQUEST_TIMEOUT))
+        .proxy(proxy)
+        .tls_config(tls.build())
+        .build()
+        .into())
+}
+
+/// Rebuilds the shared agent from `network`. On error the previous agent
+/// stays in use.
+pub fn configure(network: &NetworkSettings) -> Result<(), String> {
+    let agent = build_agent(network)?;
+    *AGENT.lock().unwrap() = Some(agent);
+    Ok(())
+}
+
+pub fn agent() -> ureq::Agent {
+    AGENT
+        .lock()
+        .unwrap()
+        .get_or_insert_with(|| {
+            build_agent(&NetworkSettings::default()).expect("default network settings are valid")
+        })
+        .clone()
+}
+
+/// Percent-encodes a URL path segment (e.g. a Matrix room id like `!abc:matrix.org`).
+pub fn encode_path_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Decodes `%XX` escapes. `None` for malformed escapes or invalid UTF-8.
+pub fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
@@ -0,0 +1,205 @@
+//! Registry of the integrations that talk to other systems, with their
+//! on/off switches and health. Each integration records the outcome of its
+//! work here, persisted in integrations.json, so a failing connector shows
+//! up in `get_integrations_status` instead of silently going stale.
+//!
+//! The switches themselves live in settings next to each integration's
+//! other options.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::channels::ChannelKind;
+use crate::disk;
+use crate::log;
+use crate::oauth::{self, OAuthProvider};
+use crate::secrets;
+use crate::settings::{self, Settings};
+use crate::storage;
+use crate::tasks::now_iso;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Integration {
+    Email,
+    Telegram,
+    Matrix,
+    Github,
+    Google,
+    Jira,
+}
+
+impl Integration {
+    const ALL: [Integration; 6] = [
+        Integration::Email,
+        Integration::Telegram,
+        Integration::Matrix,
+        Integration::Github,
+        Integration::Google,
+        Integration::Jira,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Integration::Email => "Agenda email",
+            Integration::Telegram => "Telegram",
+            Integration::Matrix => "Matrix",
+            Integration::Github => "GitHub",
+            Integration::Google => "Google",
+            Integration::Jira => "Jira",
+        }
+    }
+
+    fn enabled(self, settings: &Settings) -> bool {
+        match self {
+            Integration::Email => settings.agenda_email.enabled,
+            Integration::Telegram => settings.channels.telegram.enabled,
+            Integration::Matrix => settings.channels.matrix.enabled,
+            Integration::Github => settings.sync.github.enabled,
+            Integration::Google => settings.sync.google.enabled,
+            Integration::Jira => settings.sync.jira.enabled,
+        }
+    }
+
+    fn set_enabled(self, settings: &mut Settings, enabled: bool) {
+        let switch = match self {
+            Integration::Email => &mut settings.agenda_email.enabled,
+            Integration::Telegram => &mut settings.channels.telegram.enabled,
+            Integration::Matrix => &mut settings.channels.matrix.enabled,
+            Integration::Github => &mut settings.sync.github.enabled,
+            Integration::Google => &mut settings.sync.google.enabled,
+            Integration::Jira => &mut settings.sync.jira.enabled,
+        };
+        *switch = enabled;
+    }
+
+    /// Whether it has what it needs to run (addresses, tokens, client ids).
+    fn configured(self, settings: &Settings) -> bool {
+        let has_secret = |name: &str| secrets::get_secret(name).ok().flatten().is_some();
+        match self {
+            Integration::Email => {
+                let email = &settings.agenda_email;
+                !email.smtp_host.is_empty() && !email.from.is_empty() && !email.to.is_empty()
+            }
+            Integration::Telegram => {
+                !settings.channels.telegram.chat_id.is_empty() && has_secret(ChannelKind::Telegram.secret_name())
+            }
+            Integration::Matrix => {
+                let matrix = &settings.channels.matrix;
+                !matrix.homeserver_url.is_empty()
+                    && !matrix.room_id.is_empty()
+                    && has_secret(ChannelKind::Matrix.secret_name())
+            }
+            Integration::Github => {
+                !settings.sync.github.repository.is_empty() && has_secret("github-token")
+            }
+            Integration::Google => oauth::is_connected(OAuthProvider::Google),
+            Integration::Jira => oauth::is_connected(OAuthProvider::Jira),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Health {
+    pub last_success_at: Option<String>,
+    pub last_error_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Default)]
+pub struct IntegrationHealth(Mutex<BTreeMap<Integration, Health>>);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrationStatus {
+    pub integration: Integration,
+    pub name: &'static str,
+    pub enabled: bool,
+    pub configured: bool,
+    #[serde(flatten)]
+    pub health: Health,
+    /// The last run failed and nothing has succeeded since
+    pub failing: bool,
+}
+
+fn get_health_path(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("integrations.json")
+}
+
+pub fn load(app: &AppHandle) -> Result<(), String> {
+    let path = get_health_path(app);
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read integration status: {}", e))?;
+
+    let health: BTreeMap<Integration, Health> = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse integration status: {}", e))?;
+
+    *app.state::<IntegrationHealth>().0.lock().unwrap() = health;
+    Ok(())
+}
+
+/// Records the outcome of an integration's work.
+pub fn record<T>(app: &AppHandle, integration: Integration, result: &Result<T, String>) {
+    let store = app.state::<IntegrationHealth>();
+    let mut health = store.0.lock().unwrap();
+    let entry = health.entry(integration).or_default();
+    match result {
+        Ok(_) => entry.last_success_at = Some(now_iso()),
+        Err(e) => {
+            entry.last_error_at = Some(now_iso());
+            entry.last_error = Some(e.clone());
+        }
+    }
+
+    let saved = serde_json::to_string_pretty(&*health)
+        .map_err(|e| e.to_string())
+        .and_then(|content| {
+            disk::write_atomic(&get_health_path(app), content.as_bytes()).map_err(|e| e.to_string())
+        });
+    if let Err(e) = saved {
+        log::error(app, format!("Failed to save integration status: {}", e));
+    }
+}
+
+#[tauri::command]
+pub fn get_integrations_status(app: AppHandle) -> Vec<IntegrationStatus> {
+    let settings = settings::current(&app);
+    let health = app.state::<IntegrationHealth>().0.lock().unwrap().clone();
+
+    Integration::ALL
+        .into_iter()
+        .map(|integration| {
+            let health = health.get(&integration).cloned().unwrap_or_default();
+            let failing = match (&health.last_error_at, &health.last_success_at) {
+                (Some(error), Some(success)) => error > success,
+                (Some(_), None) => true,
+                _ => false,
+            };
+            IntegrationStatus {
+                integration,
+                name: integration.name(),
+                enabled: integration.enabled(&settings),
+                configured: integration.configured(&settings),
+                health,
+                failing,
+            }
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn set_integration_enabled(app: AppHandle, integration: Integration, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::current(&app);
+    integration.set_enabled(&mut settings, enabled);
+    settings::save(&app, settings)
+}
@@ -0,0 +1,325 @@
+//! Background jobs: long-running commands and housekeeping (imports,
+//! exports, backup cleanup) are queued and run by a small pool of worker
+//! threads instead of blocking the command that started them.
+//!
+//! A command that queues a job returns its id straight away and
+//! `cancel_job` stops it. `list_jobs` and the `jobs-changed` event give the
+//! activity panel each job's status and progress; the outcome also arrives
+//! as a `job-finished` event.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::crash;
+use crate::tasks::now_iso;
+
+/// Emitted once per job when it completes, fails or is cancelled.
+pub const JOB_FINISHED_EVENT: &str = "job-finished";
+
+/// Emitted whenever a job is queued, starts, reports progress or finishes.
+pub const JOBS_CHANGED_EVENT: &str = "jobs-changed";
+
+/// The error a job returns when it noticed it was cancelled.
+pub const CANCELLED: &str = "Cancelled";
+
+const WORKER_COUNT: usize = 2;
+
+/// Finished jobs kept for `list_jobs`.
+const MAX_FINISHED_JOBS: usize = 50;
+
+/// Checked by a job between steps of its work.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// `Err(CANCELLED)` once cancelled, for use with `?`.
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err(CANCELLED.to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    fn is_finished(self) -> bool {
+        !matches!(self, JobStatus::Queued | JobStatus::Running)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobInfo {
+    pub id: String,
+    pub kind: &'static str,
+    pub status: JobStatus,
+    /// 0.0 to 1.0, for jobs that can tell
+    pub progress: Option<f64>,
+    pub error: Option<String>,
+    pub queued_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobFinished {
+    pub id: String,
+    pub kind: &'static str,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub cancelled: bool,
+}
+
+/// What a running job gets to work with.
+pub struct JobContext {
+    pub app: AppHandle,
+    pub id: String,
+    pub kind: &'static str,
+    pub cancel: CancelToken,
+}
+
+impl JobContext {
+    /// Records how far along the job is, from 0.0 to 1.0.
+    pub fn set_progress(&self, progress: f64) {
+        update(&self.app, &self.id, |job| job.progress = Some(progress.clamp(0.0, 1.0)));
+    }
+}
+
+type Work = Box<dyn FnOnce(&JobContext) -> Result<Option<serde_json::Value>, String> + Send>;
+
+struct QueuedJob {
+    context: JobContext,
+    work: Work,
+}
+
+#[derive(Default)]
+struct QueueState {
+    pending: VecDeque<QueuedJob>,
+    /// Every queued, running and recently finished job, in the order queued
+    jobs: Vec<(JobInfo, CancelToken)>,
+}
+
+#[derive(Default)]
+pub struct JobQueue {
+    state: Mutex<QueueState>,
+    available: Condvar,
+}
+
+/// Starts the worker threads.
+pub fn start(app: AppHandle) {
+    for _ in 0..WORKER_COUNT {
+        let app = app.clone();
+        thread::spawn(move || loop {
+            let job = {
+                let queue = app.state::<JobQueue>();
+                let state = queue.state.lock().unwrap();
+                let mut state = queue
+                    .available
+                    .wait_while(state, |state| state.pending.is_empty())
+                    .unwrap();
+                state.pending.pop_front().unwrap()
+            };
+            run(job);
+        });
+    }
+}
+
+/// Queues `work` as a cancellable job and returns its id.
+pub fn spawn<T: Serialize>(
+    app: &AppHandle,
+    kind: &'static str,
+    work: impl FnOnce(&JobContext) -> Result<T, String> + Send + 'static,
+) -> String {
+    let id = uuid::Uuid::new_v4().to_string();
+    let cancel = CancelToken::default();
+    let info = JobInfo {
+        id: id.clone(),
+        kind,
+        status: JobStatus::Queued,
+        progress: None,
+        error: None,
+        queued_at: now_iso(),
+        started_at: None,
+        finished_at: None,
+    };
+
+    let queue = app.state::<JobQueue>();
+    {
+        let mut state = queue.state.lock().unwrap();
+        state.jobs.push((info, cancel.clone()));
+        state.pending.push_back(QueuedJob {
+            context: JobContext {
+                app: app.clone(),
+                id: id.clone(),
+                kind,
+                cancel,
+            },
+            work: Box::new(move |context| {
+                work(context).map(|value| serde_json::to_value(value).ok())
+            }),
+        });
+    }
+    queue.available.notify_one();
+    app.emit(JOBS_CHANGED_EVENT, ()).ok();
+
+    id
+}
+
+fn run(job: QueuedJob) {
+    let QueuedJob { context, work } = job;
+
+    let status = update(&context.app, &context.id, |job| {
+        if job.status == JobStatus::Queued {
+            job.status = JobStatus::Running;
+            job.started_at = Some(now_iso());
+        }
+        job.status
+    });
+
+    // Cancelled while still queued (status was already set by cancel_job).
+    // A panicking job fails like any other instead of taking the worker down
+    // with it. Release builds abort on panic, so there a panic still ends
+    // the app.
+    let outcome = if status == Some(JobStatus::Running) {
+        panic::catch_unwind(AssertUnwindSafe(|| work(&context)))
+            .unwrap_or_else(|payload| Err(format!("The job crashed: {}", crash::panic_message(&*payload))))
+    } else {
+        Err(CANCELLED.to_string())
+    };
+
+    let cancelled =
+        status != Some(JobStatus::Running) || (context.cancel.is_cancelled() && outcome.is_err());
+    let (result, error) = match outcome {
+        Ok(value) => (value, None),
+        Err(e) => (None, Some(e)),
+    };
+
+    update(&context.app, &context.id, |job| {
+        job.status = match (&error, cancelled) {
+            (_, true) => JobStatus::Cancelled,
+            (Some(_), false) => JobStatus::Failed,
+            (None, false) => JobStatus::Completed,
+        };
+        if job.status == JobStatus::Completed {
+            job.progress = Some(1.0);
+        }
+        job.error = error.clone().filter(|_| !cancelled);
+        job.finished_at = Some(now_iso());
+    });
+    prune_finished(&context.app);
+
+    context
+        .app
+        .emit(
+            JOB_FINISHED_EVENT,
+            JobFinished {
+                id: context.id.clone(),
+                kind: context.kind,
+                result,
+                error,
+                cancelled,
+            },
+        )
+        .ok();
+}
+
+/// Applies `change` to a job's info and tells the frontend.
+fn update<T>(app: &AppHandle, id: &str, change: impl FnOnce(&mut JobInfo) -> T) -> Option<T> {
+    let result = {
+        let queue = app.state::<JobQueue>();
+        let mut state = queue.state.lock().unwrap();
+        state
+            .jobs
+            .iter_mut()
+            .find(|(job, _)| job.id == id)
+            .map(|(job, _)| change(job))
+    };
+    app.emit(JOBS_CHANGED_EVENT, ()).ok();
+    result
+}
+
+fn prune_finished(app: &AppHandle) {
+    let queue = app.state::<JobQueue>();
+    let mut state = queue.state.lock().unwrap();
+
+    let finished = state.jobs.iter().filter(|(job, _)| job.status.is_finished()).count();
+    let mut excess = finished.saturating_sub(MAX_FINISHED_JOBS);
+
+    // Oldest first, since jobs are pushed as they're queued
+    state.jobs.retain(|(job, _)| {
+        if excess > 0 && job.status.is_finished() {
+            excess -= 1;
+            false
+        } else {
+            true
+        }
+    });
+}
+
+/// How many jobs are queued and running.
+pub fn active_counts(app: &AppHandle) -> (usize, usize) {
+    let queue = app.state::<JobQueue>();
+    let state = queue.state.lock().unwrap();
+    let count = |status| state.jobs.iter().filter(|(job, _)| job.status == status).count();
+    (count(JobStatus::Queued), count(JobStatus::Running))
+}
+
+/// Asks a job to stop. A queued job never starts; a running one finishes
+/// as cancelled once it reaches a safe point.
+#[tauri::command]
+pub fn cancel_job(app: AppHandle, id: String) -> Result<(), String> {
+    let found = update(&app, &id, |job| {
+        if job.status == JobStatus::Queued {
+            job.status = JobStatus::Cancelled;
+            job.finished_at = Some(now_iso());
+        }
+        job.status
+    });
+
+    match found {
+        Some(JobStatus::Completed | JobStatus::Failed) | None => {
+            Err(format!("No running job with id {}", id))
+        }
+        Some(_) => {
+            let queue = app.state::<JobQueue>();
+            let state = queue.state.lock().unwrap();
+            if let Some((_, cancel)) = state.jobs.iter().find(|(job, _)| job.id == id) {
+                cancel.cancel();
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Queued, running and recently finished jobs, oldest first.
+#[tauri::command]
+pub fn list_jobs(app: AppHandle) -> Vec<JobInfo> {
+    let queue = app.state::<JobQueue>();
+    let state = queue.state.lock().unwrap();
+    state.jobs.iter().map(|(job, _)| job.clone()).collect()
+}
@@ -0,0 +1,370 @@
+//! Locale-aware formatting of dates, relative dates, durations and numbers
+//! for text the backend produces (digests, exports, the CLI). The locale
+//! comes from `settings.regional.locale`; unknown locales fall back to the
+//! language, then to US English.
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::announcements::AnnouncementKind;
+use crate::clock;
+use crate::recurrence::parse_task_date;
+use crate::settings;
+
+const MAX_DECIMALS: usize = 10;
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DateStyle {
+    /// 03/14/2026
+    Short,
+    /// Mar 14, 2026
+    Medium,
+    /// March 14, 2026
+    Long,
+    /// Saturday, March 14, 2026
+    Full,
+    /// Mar 14
+    DayMonth,
+    /// Saturday, March 14
+    WeekdayDayMonth,
+}
+
+/// Relative-date words. `{}` is replaced with the number or phrase.
+struct RelativeWords {
+    today: &'static str,
+    tomorrow: &'static str,
+    yesterday: &'static str,
+    future: &'static str,
+    past: &'static str,
+    /// (singular, plural) for days, weeks, months and years
+    units: [(&'static str, &'static str); 4],
+}
+
+/// Screen-reader announcements (see `announcements.rs`). `{task}`,
+/// `{when}`, `{duration}`, `{source}` and `{error}` are filled in.
+struct AnnouncementWords {
+    due_soon: &'static str,
+    reminder: &'static str,
+    timer_finished: &'static str,
+    sync_failed: &'static str,
+}
+
+pub struct Locale {
+    pub tag: &'static str,
+    chrono: chrono::Locale,
+    date_formats: [&'static str; 6],
+    decimal_separator: char,
+    group_separator: char,
+    hours: &'static str,
+    minutes: &'static str,
+    relative: RelativeWords,
+    announcements: AnnouncementWords,
+}
+
+const LOCALES: &[Locale] = &[
+    Locale {
+        tag: "en-US",
+        chrono: chrono::Locale::en_US,
+        date_formats: ["%m/%d/%Y", "%b %-d, %Y", "%B %-d, %Y", "%A, %B %-d, %Y", "%b %-d", "%A, %B %-d"],
+        decimal_separator: '.',
+        group_separator: ',',
+        hours: "{}h",
+        minutes: "{}m",
+        relative: RelativeWords {
+            today: "today",
+            tomorrow: "tomorrow",
+            yesterday: "yesterday",
+            future: "in {}",
+            past: "{} ago",
+            units: [("day", "days"), ("week", "weeks"), ("month", "months"), ("year", "years")],
+        },
+        announcements: AnnouncementWords {
+            due_soon: "\"{task}\" is due {when}",
+            reminder: "Reminder: \"{task}\"",
+            timer_finished: "Focus timer finished after {duration} on \"{task}\"",
+            sync_failed: "Sync with {source} failed: {error}",
+        },
+    },
+    Locale {
+        tag: "en-GB",
+        chrono: chrono::Locale::en_GB,
+        date_formats: ["%d/%m/%Y", "%-d %b %Y", "%-d %B %Y", "%A, %-d %B %Y", "%-d %b", "%A, %-d %B"],
+        decimal_separator: '.',
+        group_separator: ',',
+        hours: "{}h",
+        minutes: "{}m",
+        relative: RelativeWords {
+            today: "today",
+            tomorrow: "tomorrow",
+            yesterday: "yesterday",
+            future: "in {}",
+            past: "{} ago",
+            units: [("day", "days"), ("week", "weeks"), ("month", "months"), ("year", "years")],
+        },
+        announcements: AnnouncementWords {
+            due_soon: "\"{task}\" is due {when}",
+            reminder: "Reminder: \"{task}\"",
+            timer_finished: "Focus timer finished after {duration} on \"{task}\"",
+            sync_failed: "Sync with {source} failed: {error}",
+        },
+    },
+    Locale {
+        tag: "de-DE",
+        chrono: chrono::Locale::de_DE,
+        date_formats: ["%d.%m.%Y", "%-d. %b %Y", "%-d. %B %Y", "%A, %-d. %B %Y", "%-d. %b", "%A, %-d. %B"],
+        decimal_separator: ',',
+        group_separator: '.',
+        hours: "{} Std.",
+        minutes: "{} Min.",
+        relative: RelativeWords {
+            today: "heute",
+            tomorrow: "morgen",
+            yesterday: "gestern",
+            future: "in {}",
+            past: "vor {}",
+            units: [("Tag", "Tagen"), ("Woche", "Wochen"), ("Monat", "Monaten"), ("Jahr", "Jahren")],
+        },
+        announcements: AnnouncementWords {
+            due_soon: "„{task}“ ist {when} fällig",
+            reminder: "Erinnerung: „{task}“",
+            timer_finished: "Fokus-Timer nach {duration} für „{task}“ beendet",
+            sync_failed: "Synchronisierung mit {source} fehlgeschlagen: {error}",
+        },
+    },
+    Locale {
+        tag: "fr-FR",
+        chrono: chrono::Locale::fr_FR,
+        date_formats: ["%d/%m/%Y", "%-d %b %Y", "%-d %B %Y", "%A %-d %B %Y", "%-d %b", "%A %-d %B"],
+        decimal_separator: ',',
+        group_separator: '\u{202f}',
+        hours: "{} h",
+        minutes: "{} min",
+        relative: RelativeWords {
+            today: "aujourd'hui",
+            tomorrow: "demain",
+            yesterday: "hier",
+            future: "dans {}",
+            past: "il y a {}",
+            units: [("jour", "jours"), ("semaine", "semaines"), ("mois", "mois"), ("an", "ans")],
+        },
+        announcements: AnnouncementWords {
+            due_soon: "« {task} » arrive à échéance {when}",
+            reminder: "Rappel : « {task} »",
+            timer_finished: "Minuteur de concentration terminé après {duration} sur « {task} »",
+            sync_failed: "Échec de la synchronisation avec {source} : {error}",
+        },
+    },
+    Locale {
+        tag: "es-ES",
+        chrono: chrono::Locale::es_ES,
+        date_formats: ["%d/%m/%Y", "%-d %b %Y", "%-d de %B de %Y", "%A, %-d de %B de %Y", "%-d %b", "%A, %-d de %B"],
+        decimal_separator: ',',
+        group_separator: '.',
+        hours: "{} h",
+        minutes: "{} min",
+        relative: RelativeWords {
+            today: "hoy",
+            tomorrow: "mañana",
+            yesterday: "ayer",
+            future: "dentro de {}",
+            past: "hace {}",
+            units: [("día", "días"), ("semana", "semanas"), ("mes", "meses"), ("año", "años")],
+        },
+        announcements: AnnouncementWords {
+            due_soon: "«{task}» vence {when}",
+            reminder: "Recordatorio: «{task}»",
+            timer_finished: "Temporizador de concentración terminado tras {duration} en «{task}»",
+            sync_failed: "Error al sincronizar con {source}: {error}",
+        },
+    },
+    Locale {
+        tag: "nl-NL",
+        chrono: chrono::Locale::nl_NL,
+        date_formats: ["%d-%m-%Y", "%-d %b %Y", "%-d %B %Y", "%A %-d %B %Y", "%-d %b", "%A %-d %B"],
+        decimal_separator: ',',
+        group_separator: '.',
+        hours: "{} u",
+        minutes: "{} min",
+        relative: RelativeWords {
+            today: "vandaag",
+            tomorrow: "morgen",
+            yesterday: "gisteren",
+            future: "over {}",
+            past: "{} geleden",
+            units: [("dag", "dagen"), ("week", "weken"), ("maand", "maanden"), ("jaar", "jaar")],
+        },
+        announcements: AnnouncementWords {
+            due_soon: "‘{task}’ moet {when} af zijn",
+            reminder: "Herinnering: ‘{task}’",
+            timer_finished: "Focustimer gestopt na {duration} aan ‘{task}’",
+            sync_failed: "Synchronisatie met {source} mislukt: {error}",
+        },
+    },
+];
+
+/// Looks up a locale by tag (`de-DE`), then by language (`de`), falling back
+/// to US English.
+pub fn resolve(tag: &str) -> &'static Locale {
+    let tag = tag.replace('_', "-");
+    let language = tag.split('-').next().unwrap_or_default();
+
+    LOCALES
+        .iter()
+        .find(|locale| locale.tag.eq_ignore_ascii_case(&tag))
+        .or_else(|| {
+            LOCALES
+                .iter()
+                .find(|locale| locale.tag.split('-').next().is_some_and(|l| l.eq_ignore_ascii_case(language)))
+        })
+        .unwrap_or(&LOCALES[0])
+}
+
+pub fn is_supported(tag: &str) -> bool {
+    LOCALES.iter().any(|locale| locale.tag.eq_ignore_ascii_case(tag))
+}
+
+/// The configured locale.
+pub fn current(app: &AppHandle) -> &'static Locale {
+    resolve(&settings::current(app).regional.locale)
+}
+
+impl Locale {
+    pub fn format_date(&self, date: NaiveDate, style: DateStyle) -> String {
+        let format = self.date_formats[style as usize];
+        date.format_localized(format, self.chrono).to_string()
+    }
+
+    /// "today", "in 3 days", "2 weeks ago", ...
+    pub fn format_relative(&self, date: NaiveDate, today: NaiveDate) -> String {
+        let days = (date - today).num_days();
+        let words = &self.relative;
+
+        match days {
+            0 => return words.today.to_string(),
+            1 => return words.tomorrow.to_string(),
+            -1 => return words.yesterday.to_string(),
+            _ => {}
+        }
+
+        let distance = days.unsigned_abs();
+        let months = months_between(date.min(today), date.max(today));
+        let (count, unit) = if distance < 7 {
+            (distance, 0)
+        } else if months < 1 {
+            (distance / 7, 1)
+        } else if months < 12 {
+            (months, 2)
+        } else {
+            (months / 12, 3)
+        };
+
+        let (singular, plural) = words.units[unit];
+        let phrase = format!("{} {}", count, if count == 1 { singular } else { plural });
+        let template = if days > 0 { words.future } else { words.past };
+        template.replace("{}", &phrase)
+    }
+
+    /// The announcement template for `kind`; see `AnnouncementWords`.
+    pub fn announcement(&self, kind: AnnouncementKind) -> &'static str {
+        let words = &self.announcements;
+        match kind {
+            AnnouncementKind::DueSoon => words.due_soon,
+            AnnouncementKind::Reminder => words.reminder,
+            AnnouncementKind::TimerFinished => words.timer_finished,
+            AnnouncementKind::SyncFailed => words.sync_failed,
+        }
+    }
+
+    /// Minutes as hours and minutes, e.g. "1h 30m" or "1 Std. 30 Min.".
+    pub fn format_duration(&self, minutes: i64) -> String {
+        let minutes = minutes.max(0);
+        let (hours, mins) = (minutes / 60, minutes % 60);
+        let h = self.hours.replace("{}", &hours.to_string());
+        let m = self.minutes.replace("{}", &mins.to_string());
+
+        match (hours, mins) {
+            (0, _) => m,
+            (_, 0) => h,
+            _ => format!("{} {}", h, m),
+        }
+    }
+
+    /// A number with the locale's grouping and decimal separators.
+    pub fn format_number(&self, value: f64, decimals: usize) -> String {
+        let formatted = format!("{:.*}", decimals, value.abs());
+        let (integer, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+
+        let mut grouped = String::new();
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                grouped.push(self.group_separator);
+            }
+            grouped.push(digit);
+        }
+
+        // No "-0" when a small negative rounds away
+        let rounds_to_zero = formatted.chars().all(|c| c == '0' || c == '.');
+        let sign = if value < 0.0 && !rounds_to_zero { "-" } else { "" };
+        if fraction.is_empty() {
+            format!("{}{}", sign, grouped)
+        } else {
+            format!("{}{}{}{}", sign, grouped, self.decimal_separator, fraction)
+        }
+    }
+}
+
+/// Whole calendar months from `from` to `to` (`from <= to`).
+fn months_between(from: NaiveDate, to: NaiveDate) -> u64 {
+    let months = (to.year() - from.year()) * 12 + to.month() as i32 - from.month() as i32;
+    let months = if to.day() < from.day() { months - 1 } else { months };
+    months.max(0) as u64
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    parse_task_date(date)
+        .or_else(|| chrono::DateTime::parse_from_rfc3339(date).ok().map(|d| d.date_naive()))
+        .ok_or_else(|| format!("Invalid date \"{}\"", date))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocaleInfo {
+    pub tag: &'static str,
+    pub example: String,
+}
+
+#[tauri::command]
+pub fn get_supported_locales() -> Vec<LocaleInfo> {
+    let example = NaiveDate::from_ymd_opt(2026, 3, 14).unwrap_or_default();
+    LOCALES
+        .iter()
+        .map(|locale| LocaleInfo {
+            tag: locale.tag,
+            example: locale.format_date(example, DateStyle::Full),
+        })
+        .collect()
+}
+
+/// Formats a `yyyy-MM-dd` or RFC 3339 date in the configured locale.
+#[tauri::command]
+pub fn format_date(app: AppHandle, date: String, style: DateStyle) -> Result<String, String> {
+    Ok(current(&app).format_date(parse_date(&date)?, style))
+}
+
+#[tauri::command]
+pub fn format_relative_date(app: AppHandle, date: String) -> Result<String, String> {
+    let today = clock::today();
+    Ok(current(&app).format_relative(parse_date(&date)?, today))
+}
+
+#[tauri::command]
+pub fn format_duration(app: AppHandle, minutes: i64) -> String {
+    current(&app).format_duration(minutes)
+}
+
+#[tauri::command]
+pub fn format_number(app: AppHandle, value: f64, decimals: Option<usize>) -> String {
+    current(&app).format_number(value, decimals.unwrap_or(0).min(MAX_DECIMALS))
+}
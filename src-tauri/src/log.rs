@@ -0,0 +1,42 @@
+//! Errors from background work that has nowhere else to show them, such as
+//! a file that failed to load at startup or a loop that will try again
+//! later. Release builds have no console on Windows, so each line also goes
+//! to afterglow.log in the app's log directory, which is kept on this
+//! machine even when the data directory is a network share. Once the file
+//! passes `MAX_LOG_BYTES` it moves to afterglow.log.1 and a new one starts.
+
+use chrono::Local;
+use std::fmt::Display;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::disk;
+
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+
+/// Keeps lines from two threads from interleaving, and the rotation safe.
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+fn get_log_path(app: &AppHandle) -> Option<PathBuf> {
+    let dir = disk::long_path(&app.path().app_log_dir().ok()?);
+    fs::create_dir_all(&dir).ok()?;
+    Some(dir.join("afterglow.log"))
+}
+
+pub fn error(app: &AppHandle, message: impl Display) {
+    eprintln!("{}", message);
+
+    let Some(path) = get_log_path(app) else {
+        return;
+    };
+    let _guard = WRITE_LOCK.lock().unwrap();
+    if fs::metadata(&path).is_ok_and(|m| m.len() > MAX_LOG_BYTES) {
+        fs::rename(&path, path.with_extension("log.1")).ok();
+    }
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&path) {
+        writeln!(file, "{} {}", Local::now().to_rfc3339(), message).ok();
+    }
+}
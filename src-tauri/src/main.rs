@@ -1,141 +1,407 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
-use chrono::Local;
+mod access;
+mod analytics;
+mod announcements;
+mod api;
+mod attachments;
+mod backups;
+mod calendar;
+mod calendar_feeds;
+mod channels;
+mod checklists;
+mod clock;
+mod crash;
+mod critical_path;
+mod daily;
+mod dates;
+mod disk;
+mod duplicates;
+mod email;
+mod email_import;
+mod estimates;
+mod events;
+mod external_ids;
+mod files;
+mod focus;
+mod goals;
+mod habits;
+mod http;
+mod integrations;
+mod jobs;
+mod locale;
+mod log;
+mod mcp;
+mod metrics;
+mod notification_history;
+mod oauth;
+mod ocr;
+mod onboarding;
+mod outbox;
+mod paste;
+mod periods;
+mod power;
+mod planning;
+mod projects;
+mod recurrence;
+mod redact;
+mod reminders;
+mod report;
+mod review;
+mod rollover;
+mod rule_audit;
+mod rules;
+mod scheduled_exports;
+mod scoring;
+mod secrets;
+mod settings;
+mod share;
+mod storage;
+mod sync;
+mod tasks;
+mod telemetry;
+mod templates;
+mod time_tracking;
+mod transfer;
+mod tray;
+mod voice;
+mod windows;
+mod workload;
 
-const MAX_BACKUPS: usize = 5;
+use tauri::webview::PageLoadEvent;
+use tauri::{AppHandle, DragDropEvent, Manager, RunEvent, Webview, WindowEvent};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct TaskData {
-    pub tasks: Vec<serde_json::Value>,
-    pub labels: Vec<String>,
-    pub stakeholders: Vec<String>,
-}
-
-impl Default for TaskData {
-    fn default() -> Self {
-        Self {
-            tasks: Vec::new(),
-            labels: Vec::new(),
-            stakeholders: Vec::new(),
-        }
-    }
-}
-
-fn get_data_path(app: &AppHandle) -> PathBuf {
-    let app_data = app.path().app_data_dir().expect("Failed to get app data dir");
-    fs::create_dir_all(&app_data).ok();
-    app_data.join("tasks.json")
-}
+use access::AccessLog;
+use api::ApiToken;
+use calendar_feeds::CalendarFeeds;
+use checklists::ChecklistTemplates;
+use dates::DatesStore;
+use events::EventStream;
+use external_ids::ExternalIdStore;
+use files::FileGrants;
+use focus::FocusState;
+use goals::GoalsStore;
+use habits::HabitStore;
+use integrations::IntegrationHealth;
+use jobs::JobQueue;
+use notification_history::NotificationHistory;
+use onboarding::OnboardingState;
+use outbox::OutboxStore;
+use reminders::ReminderScheduler;
+use review::ReviewStore;
+use rule_audit::RuleAudit;
+use rules::RulesStore;
+use settings::SettingsStore;
+use storage::{SharedTaskData, TaskData};
+use sync::SyncState;
+use telemetry::TelemetryStore;
+use templates::TemplatesStore;
+use time_tracking::TimeLog;
 
-fn get_backups_dir(app: &AppHandle) -> PathBuf {
-    let app_data = app.path().app_data_dir().expect("Failed to get app data dir");
-    let backups_dir = app_data.join("backups");
-    fs::create_dir_all(&backups_dir).ok();
-    backups_dir
-}
-
-fn create_backup(app: &AppHandle) -> Result<(), String> {
-    let data_path = get_data_path(app);
-    
-    // Only backup if the data file exists
-    if !data_path.exists() {
-        return Ok(());
-    }
-    
-    let backups_dir = get_backups_dir(app);
-    let timestamp = Local::now().format("%Y%m%d_%H%M%S");
-    let backup_path = backups_dir.join(format!("tasks_backup_{}.json", timestamp));
-    
-    fs::copy(&data_path, &backup_path)
-        .map_err(|e| format!("Failed to create backup: {}", e))?;
-    
-    // Clean up old backups, keeping only the most recent MAX_BACKUPS
-    cleanup_old_backups(&backups_dir);
-    
-    Ok(())
-}
-
-fn cleanup_old_backups(backups_dir: &PathBuf) {
-    let mut backups: Vec<_> = fs::read_dir(backups_dir)
-        .into_iter()
-        .flatten()
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| {
-            entry.file_name()
-                .to_string_lossy()
-                .starts_with("tasks_backup_")
-        })
-        .collect();
-    
-    // Sort by modification time (newest first)
-    backups.sort_by(|a, b| {
-        let a_time = a.metadata().and_then(|m| m.modified()).ok();
-        let b_time = b.metadata().and_then(|m| m.modified()).ok();
-        b_time.cmp(&a_time)
-    });
-    
-    // Remove old backups beyond MAX_BACKUPS
-    for backup in backups.into_iter().skip(MAX_BACKUPS) {
-        fs::remove_file(backup.path()).ok();
-    }
+/// The task data as a window loads it, with the revision to save it back at.
+#[derive(serde::Serialize)]
+struct LoadedTaskData {
+    #[serde(flatten)]
+    data: TaskData,
+    revision: u64,
 }
 
 #[tauri::command]
-fn load_tasks(app: AppHandle) -> Result<TaskData, String> {
-    let path = get_data_path(&app);
-    
-    if !path.exists() {
-        return Ok(TaskData::default());
-    }
-    
-    let content = fs::read_to_string(&path)
-        .map_err(|e| format!("Failed to read tasks file: {}", e))?;
-    
-    serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse tasks: {}", e))
+fn load_tasks(app: AppHandle) -> Result<LoadedTaskData, String> {
+    let (data, revision) = storage::read_task_data_at_revision(&app)?;
+    Ok(LoadedTaskData { data, revision })
 }
 
+/// Saves a window's whole snapshot. It's refused when anything else saved
+/// since `revision`, so a stale window can't overwrite newer changes.
+/// Returns the new revision.
 #[tauri::command]
-fn save_tasks(app: AppHandle, data: TaskData) -> Result<(), String> {
-    // Create backup before saving
-    create_backup(&app)?;
-    
-    let path = get_data_path(&app);
-    
-    let content = serde_json::to_string_pretty(&data)
-        .map_err(|e| format!("Failed to serialize tasks: {}", e))?;
-    
-    fs::write(&path, content)
-        .map_err(|e| format!("Failed to write tasks file: {}", e))?;
-    
-    Ok(())
-}
+fn save_tasks(app: AppHandle, webview: Webview, data: TaskData, revision: u64) -> Result<u64, String> {
+    let revision = storage::replace_task_data(&app, &data, revision)?;
 
-#[tauri::command]
-fn export_tasks(app: AppHandle, export_path: String) -> Result<(), String> {
-    let data_path = get_data_path(&app);
-    
-    if !data_path.exists() {
-        return Err("No data file to export".to_string());
-    }
-    
-    fs::copy(&data_path, &export_path)
-        .map_err(|e| format!("Failed to export tasks: {}", e))?;
-    
-    Ok(())
+    // Other windows pick up the change; the one that saved already has it
+    tasks::notify_changed(&app, Some(webview.label()));
+
+    // Reminder times may have changed
+    app.state::<ReminderScheduler>().reschedule();
+
+    Ok(revision)
 }
 
 fn main() {
     tauri::Builder::default()
-        .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
-        .invoke_handler(tauri::generate_handler![load_tasks, save_tasks, export_tasks])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .plugin(tauri_plugin_notification::init())
+        .manage(SharedTaskData::default())
+        .manage(ReminderScheduler::default())
+        .manage(SettingsStore::default())
+        .manage(NotificationHistory::default())
+        .manage(JobQueue::default())
+        .manage(TelemetryStore::default())
+        .manage(OnboardingState::default())
+        .manage(FocusState::default())
+        .manage(FileGrants::default())
+        .manage(DatesStore::default())
+        .manage(GoalsStore::default())
+        .manage(HabitStore::default())
+        .manage(TimeLog::default())
+        .manage(ExternalIdStore::default())
+        .manage(SyncState::default())
+        .manage(OutboxStore::default())
+        .manage(IntegrationHealth::default())
+        .manage(RulesStore::default())
+        .manage(RuleAudit::default())
+        .manage(TemplatesStore::default())
+        .manage(ChecklistTemplates::default())
+        .manage(CalendarFeeds::default())
+        .manage(EventStream::default())
+        .manage(ReviewStore::default())
+        .manage(AccessLog::default())
+        .manage(ApiToken::default())
+        .register_asynchronous_uri_scheme_protocol(
+            attachments::ASSET_PROTOCOL,
+            attachments::handle_protocol,
+        )
+        .setup(|app| {
+            // First, so a panic while anything below loads is still reported
+            crash::install(app.handle());
+            // A copy that's already running takes over from here
+            if !share::claim_instance(app.handle()) {
+                crash::end_session();
+                std::process::exit(0);
+            }
+            metrics::init();
+            if std::env::var("AFTERGLOW_STORAGE").as_deref() == Ok("memory") {
+                storage::set_backend(app.handle(), Box::new(storage::MemoryStorage::default()))?;
+            }
+            // A broken settings file shouldn't keep the app from starting
+            if let Err(e) = settings::load(app.handle()) {
+                log::error(app.handle(), e);
+            }
+            if let Err(e) = onboarding::bootstrap(app.handle()) {
+                log::error(app.handle(), e);
+            }
+            if let Err(e) = access::load(app.handle()) {
+                log::error(app.handle(), e);
+            }
+            if let Err(e) = notification_history::load(app.handle()) {
+                log::error(app.handle(), e);
+            }
+            if let Err(e) = telemetry::load(app.handle()) {
+                log::error(app.handle(), e);
+            }
+            if let Err(e) = dates::load(app.handle()) {
+                log::error(app.handle(), e);
+            }
+            if let Err(e) = goals::load(app.handle()) {
+                log::error(app.handle(), e);
+            }
+            if let Err(e) = habits::load(app.handle()) {
+                log::error(app.handle(), e);
+            }
+            if let Err(e) = time_tracking::load(app.handle()) {
+                log::error(app.handle(), e);
+            }
+            if let Err(e) = external_ids::load(app.handle()) {
+                log::error(app.handle(), e);
+            }
+            if let Err(e) = sync::load(app.handle()) {
+                log::error(app.handle(), e);
+            }
+            if let Err(e) = outbox::load(app.handle()) {
+                log::error(app.handle(), e);
+            }
+            if let Err(e) = integrations::load(app.handle()) {
+                log::error(app.handle(), e);
+            }
+            if let Err(e) = rules::load(app.handle()) {
+                log::error(app.handle(), e);
+            }
+            if let Err(e) = rule_audit::load(app.handle()) {
+                log::error(app.handle(), e);
+            }
+            if let Err(e) = templates::load(app.handle()) {
+                log::error(app.handle(), e);
+            }
+            if let Err(e) = checklists::load(app.handle()) {
+                log::error(app.handle(), e);
+            }
+            if let Err(e) = calendar_feeds::load(app.handle()) {
+                log::error(app.handle(), e);
+            }
+            if let Err(e) = review::load(app.handle()) {
+                log::error(app.handle(), e);
+            }
+            tray::init(app.handle())?;
+            share::start(app.handle().clone());
+            // Only now is it certain no other copy's session marker is live
+            crash::report_unclean_exits(app.handle());
+            jobs::start(app.handle().clone());
+            reminders::start(app.handle().clone());
+            email::start(app.handle().clone());
+            channels::start(app.handle().clone());
+            outbox::start(app.handle().clone());
+            dates::start(app.handle().clone());
+            rules::start(app.handle().clone());
+            rollover::start(app.handle().clone());
+            calendar_feeds::start(app.handle().clone());
+            scheduled_exports::start(app.handle().clone());
+            api::start(app.handle().clone());
+            storage::start_write_behind_flusher(app.handle().clone());
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            if let WindowEvent::DragDrop(DragDropEvent::Drop { paths, .. }) = event {
+                files::grant_dropped(window.app_handle(), paths);
+            }
+        })
+        .on_page_load(|webview, payload| {
+            if payload.event() == PageLoadEvent::Finished {
+                onboarding::announce(webview.app_handle());
+            }
+        })
+        .invoke_handler(tauri::generate_handler![
+            load_tasks,
+            save_tasks,
+            access::clear_access_log,
+            access::get_access_log,
+            analytics::export_label_cohorts,
+            analytics::forecast,
+            analytics::get_completion_heatmap,
+            analytics::get_label_cohorts,
+            api::get_api_token,
+            api::regenerate_api_token,
+            attachments::add_attachment,
+            attachments::list_attachments,
+            attachments::remove_attachment,
+            attachments::set_attachment_thumbnail,
+            calendar::get_calendar,
+            calendar_feeds::add_calendar_feed,
+            calendar_feeds::get_calendar_events,
+            calendar_feeds::list_calendar_feeds,
+            calendar_feeds::refresh_calendar_feeds,
+            calendar_feeds::remove_calendar_feed,
+            channels::set_channel_token,
+            channels::test_notification_channels,
+            checklists::apply_checklist_template,
+            checklists::delete_checklist_template,
+            checklists::list_checklist_templates,
+            checklists::save_checklist_template,
+            clock::advance_clock,
+            clock::get_clock,
+            clock::reset_clock,
+            crash::delete_crash_report,
+            crash::list_crash_reports,
+            crash::preview_crash_report,
+            crash::send_crash_report,
+            critical_path::get_critical_path,
+            dates::add_stakeholder_date,
+            dates::list_stakeholder_dates,
+            dates::remove_stakeholder_date,
+            dates::update_stakeholder_date,
+            email::set_smtp_password,
+            email::send_agenda_email,
+            email_import::import_email,
+            estimates::adjust_estimate,
+            estimates::get_estimation_bias,
+            external_ids::check_external_links,
+            external_ids::link_external_id,
+            external_ids::list_external_links,
+            external_ids::repair_external_links,
+            external_ids::unlink_external_id,
+            files::choose_file,
+            files::read_user_file,
+            focus::complete_focus_task,
+            focus::get_widget_state,
+            focus::pause_focus_timer,
+            focus::reset_focus_timer,
+            focus::set_focus_task,
+            focus::start_focus_timer,
+            goals::delete_goal,
+            goals::get_goal_progress,
+            goals::list_goals,
+            goals::save_goal,
+            habits::get_habit_matrix,
+            habits::get_habit_vacations,
+            habits::set_habit_vacations,
+            habits::skip_habit_day,
+            integrations::get_integrations_status,
+            integrations::set_integration_enabled,
+            jobs::cancel_job,
+            jobs::list_jobs,
+            locale::format_date,
+            locale::format_duration,
+            locale::format_number,
+            locale::format_relative_date,
+            locale::get_supported_locales,
+            metrics::get_metrics,
+            metrics::get_metrics_prometheus,
+            reminders::snooze_reminder,
+            reminders::acknowledge_reminder,
+            reminders::complete_reminder_task,
+            reminders::get_active_reminder,
+            report::preview_morning_digest,
+            report::preview_weekly_digest,
+            review::get_review_queue,
+            review::review_task,
+            rollover::roll_over_overdue,
+            rule_audit::get_rule_audit,
+            rules::delete_rule,
+            rules::list_rules,
+            rules::save_rule,
+            rules::simulate_rule,
+            scheduled_exports::run_scheduled_export,
+            scoring::score_tasks,
+            notification_history::get_notification_history,
+            oauth::disconnect_oauth,
+            oauth::get_oauth_status,
+            oauth::refresh_oauth_token,
+            oauth::start_oauth,
+            ocr::scan_task_list,
+            onboarding::dismiss_onboarding,
+            onboarding::get_onboarding,
+            outbox::discard_outbox_delivery,
+            outbox::get_outbox,
+            outbox::retry_outbox,
+            paste::preview_paste,
+            planning::plan_day,
+            power::get_power_state,
+            projects::get_effective_settings,
+            settings::get_settings,
+            settings::save_settings,
+            share::register_share_target,
+            share::unregister_share_target,
+            storage::get_storage_status,
+            sync::get_sync_state,
+            sync::reset_sync,
+            sync::set_github_token,
+            sync::start_sync,
+            tasks::quick_add_task,
+            tasks::quick_add_tasks,
+            telemetry::clear_telemetry,
+            telemetry::preview_telemetry,
+            telemetry::record_feature_usage,
+            telemetry::send_telemetry,
+            templates::delete_template,
+            templates::get_template_variables,
+            templates::instantiate_template,
+            templates::list_templates,
+            templates::save_template,
+            time_tracking::get_tracked_time,
+            transfer::export_tasks,
+            transfer::import_tasks,
+            voice::transcribe_to_task,
+            windows::open_window,
+            windows::toggle_widget_window,
+            workload::suggest_rebalancing,
+        ])
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_, event| {
+            if let RunEvent::Exit = event {
+                crash::end_session();
+            }
+        });
 }
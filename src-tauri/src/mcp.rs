@@ -0,0 +1,222 @@
+//! A Model Context Protocol endpoint for local AI assistants, served by the
+//! local API at `POST /mcp` (JSON-RPC over the streamable HTTP transport,
+//! without server-sent events). Off unless `api.mcp` is set.
+//!
+//! Assistants can list, search, create and complete switchbacks, as far as
+//! `settings.api.permissions.mcp` lets them (see `access.rs`). By default
+//! tools that change anything ask the user first with a dialog.
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+
+use crate::access::{self, Caller, Operation};
+use crate::reminders::ReminderScheduler;
+use crate::storage;
+use crate::tasks::{self, str_field};
+
+const PROTOCOL_VERSION: &str = "2025-03-26";
+const DEFAULT_LIMIT: usize = 50;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct ListArgs {
+    /// `not-started`, `in-progress`, `blocked` or `done`. Leave out for
+    /// every open task.
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    label: Option<String>,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct SearchArgs {
+    /// Text to look for in titles and notes
+    query: String,
+    #[serde(default)]
+    include_done: bool,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct CreateArgs {
+    title: String,
+    /// `YYYY-MM-DD`
+    #[serde(default)]
+    due_date: Option<String>,
+    /// `p0` (highest) to `p4`
+    #[serde(default)]
+    priority: Option<String>,
+    #[serde(default)]
+    notes: Option<String>,
+    #[serde(default)]
+    labels: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct CompleteArgs {
+    id: String,
+}
+
+fn input_schema<T: JsonSchema>() -> Value {
+    serde_json::to_value(schemars::schema_for!(T)).unwrap_or(Value::Null)
+}
+
+fn tool_list() -> Value {
+    json!([
+        {
+            "name": "list_tasks",
+            "description": "List the user's switchbacks (tasks), highest priority first.",
+            "inputSchema": input_schema::<ListArgs>(),
+        },
+        {
+            "name": "search_tasks",
+            "description": "Find switchbacks whose title or notes contain some text.",
+            "inputSchema": input_schema::<SearchArgs>(),
+        },
+        {
+            "name": "create_task",
+            "description": "Create a switchback. The user may be asked to allow it first.",
+            "inputSchema": input_schema::<CreateArgs>(),
+        },
+        {
+            "name": "complete_task",
+            "description": "Mark a switchback as done. The user may be asked to allow it first.",
+            "inputSchema": input_schema::<CompleteArgs>(),
+        },
+    ])
+}
+
+/// The fields an assistant needs, so results stay small.
+fn summary(task: &Value) -> Value {
+    let mut summary = json!({});
+    for field in ["id", "title", "status", "priority", "dueDate", "labels", "stakeholders", "project", "notes"] {
+        if let Some(value) = task.get(field).filter(|v| !v.is_null()) {
+            summary[field] = value.clone();
+        }
+    }
+    summary
+}
+
+fn sorted_summaries(mut matches: Vec<&Value>, limit: Option<usize>) -> Value {
+    crate::report::sort_for_report(&mut matches);
+    Value::Array(matches.into_iter().take(limit.unwrap_or(DEFAULT_LIMIT)).map(summary).collect())
+}
+
+fn parse_args<T: for<'de> Deserialize<'de>>(arguments: Value) -> Result<T, String> {
+    serde_json::from_value(arguments).map_err(|e| format!("Invalid arguments: {}", e))
+}
+
+fn call_tool(app: &AppHandle, name: &str, arguments: Value) -> Result<Value, String> {
+    match name {
+        "list_tasks" => {
+            let args: ListArgs = parse_args(arguments)?;
+            access::check(app, Caller::Mcp, Operation::Read, "list your switchbacks")?;
+            let data = storage::read_task_data(app)?;
+            let matches = data
+                .tasks
+                .iter()
+                .filter(|task| match &args.status {
+                    Some(status) => str_field(task, "status") == Some(status.as_str()),
+                    None => tasks::is_open(task),
+                })
+                .filter(|task| args.label.as_deref().is_none_or(|label| tasks::labels(task).any(|l| l == label)))
+                .collect();
+            Ok(sorted_summaries(matches, args.limit))
+        }
+        "search_tasks" => {
+            let args: SearchArgs = parse_args(arguments)?;
+            let query = args.query.trim().to_lowercase();
+            if query.is_empty() {
+                return Err("The query is empty".to_string());
+            }
+            access::check(app, Caller::Mcp, Operation::Read, &format!("search your switchbacks for \"{}\"", query))?;
+            let data = storage::read_task_data(app)?;
+            let matches = data
+                .tasks
+                .iter()
+                .filter(|task| args.include_done || tasks::is_open(task))
+                .filter(|task| {
+                    ["title", "notes"]
+                        .iter()
+                        .filter_map(|field| str_field(task, field))
+                        .any(|text| text.to_lowercase().contains(&query))
+                })
+                .collect();
+            Ok(sorted_summaries(matches, args.limit))
+        }
+        "create_task" => {
+            let args: CreateArgs = parse_args(arguments)?;
+            access::check(app, Caller::Mcp, Operation::Create, &format!("add \"{}\"", args.title.trim()))?;
+            let mut task = json!({ "title": args.title });
+            for (field, value) in [("dueDate", args.due_date), ("priority", args.priority), ("notes", args.notes)] {
+                if let Some(value) = value {
+                    task[field] = json!(value);
+                }
+            }
+            if let Some(labels) = args.labels {
+                task["labels"] = json!(labels);
+            }
+            let result = tasks::add_task(app, task, None)?;
+            Ok(summary(&result.task))
+        }
+        "complete_task" => {
+            let args: CompleteArgs = parse_args(arguments)?;
+            let data = storage::read_task_data(app)?;
+            let task = data
+                .tasks
+                .iter()
+                .find(|task| tasks::task_id(task) == Some(args.id.as_str()))
+                .ok_or_else(|| format!("Task not found: {}", args.id))?;
+            let title = str_field(task, "title").unwrap_or("Untitled").to_string();
+            access::check(app, Caller::Mcp, Operation::Complete, &format!("mark \"{}\" as done", title))?;
+            tasks::modify_task_data(app, |data| tasks::complete_task(data, &args.id))?;
+            app.state::<ReminderScheduler>().reschedule();
+            Ok(json!({ "completed": args.id }))
+        }
+        _ => Err(format!("Unknown tool: {}", name)),
+    }
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Handles one JSON-RPC message. Returns `None` for notifications, which get
+/// no reply.
+pub fn handle(app: &AppHandle, body: &[u8]) -> Option<Value> {
+    let message: Value = match serde_json::from_slice(body) {
+        Ok(message) => message,
+        Err(_) => return Some(rpc_error(Value::Null, -32700, "Parse error")),
+    };
+    let id = message.get("id").cloned()?;
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match str_field(&message, "method").unwrap_or("") {
+        "initialize" => json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "afterglow", "version": env!("CARGO_PKG_VERSION") },
+        }),
+        "ping" => json!({}),
+        "tools/list" => json!({ "tools": tool_list() }),
+        "tools/call" => {
+            let name = str_field(&params, "name").unwrap_or("");
+            let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+            // Tool failures are results the assistant can see, not protocol errors
+            match call_tool(app, name, arguments) {
+                Ok(output) => json!({ "content": [{ "type": "text", "text": output.to_string() }] }),
+                Err(e) => json!({ "content": [{ "type": "text", "text": e }], "isError": true }),
+            }
+        }
+        _ => return Some(rpc_error(id, -32601, "Method not found")),
+    };
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
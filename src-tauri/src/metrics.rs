@@ -0,0 +1,166 @@
+//! In-process counters and timings (saves, backups, sync requests, channel
+//! sends) for the power-user dashboard. Nothing leaves the machine unless
+//! asked for: `get_metrics` returns a snapshot, and `get_metrics_prometheus`
+//! and the local API's `/v1/metrics` the same in Prometheus text format.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+use crate::external_ids;
+use crate::jobs;
+use crate::storage;
+
+const PROMETHEUS_PREFIX: &str = "afterglow_";
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Timing {
+    pub count: u64,
+    pub total_ms: f64,
+    pub max_ms: f64,
+    pub last_ms: f64,
+}
+
+struct Registry {
+    counters: BTreeMap<&'static str, u64>,
+    timings: BTreeMap<&'static str, Timing>,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    counters: BTreeMap::new(),
+    timings: BTreeMap::new(),
+});
+
+static STARTED: OnceLock<Instant> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsSnapshot {
+    pub uptime_seconds: u64,
+    pub counters: BTreeMap<&'static str, u64>,
+    pub timings: BTreeMap<&'static str, Timing>,
+    /// Current sizes: tasks, data file, data directory and backups, external
+    /// id links, queued/running jobs
+    pub gauges: BTreeMap<&'static str, u64>,
+}
+
+/// Marks the start of the session for `uptime_seconds`.
+pub fn init() {
+    STARTED.get_or_init(Instant::now);
+}
+
+pub fn increment(counter: &'static str) {
+    *REGISTRY.lock().unwrap().counters.entry(counter).or_default() += 1;
+}
+
+pub fn record_duration(timing: &'static str, duration: Duration) {
+    let ms = duration.as_secs_f64() * 1000.0;
+    let mut registry = REGISTRY.lock().unwrap();
+    let entry = registry.timings.entry(timing).or_default();
+    entry.count += 1;
+    entry.total_ms += ms;
+    entry.max_ms = entry.max_ms.max(ms);
+    entry.last_ms = ms;
+}
+
+/// Runs `f` and records how long it took under `timing`.
+pub fn time<T>(timing: &'static str, f: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = f();
+    record_duration(timing, started.elapsed());
+    result
+}
+
+/// Sizes of the files directly in `dir`.
+fn file_sizes(dir: &Path) -> Vec<u64> {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok()?.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .collect()
+}
+
+fn gauges(app: &AppHandle) -> BTreeMap<&'static str, u64> {
+    let mut gauges = BTreeMap::new();
+
+    if let Ok(data) = storage::read_task_data(app) {
+        gauges.insert("tasks", data.tasks.len() as u64);
+    }
+    if let Some(bytes) = storage::saved_size(app) {
+        gauges.insert("data_file_bytes", bytes);
+    }
+    gauges.insert("external_links", external_ids::link_count(app) as u64);
+
+    let data_dir = storage::get_app_data_dir(app);
+    gauges.insert("data_dir_bytes", file_sizes(&data_dir).iter().sum());
+    let backups = file_sizes(&data_dir.join("backups"));
+    gauges.insert("backup_files", backups.len() as u64);
+    gauges.insert("backup_bytes", backups.iter().sum());
+
+    let (queued, running) = jobs::active_counts(app);
+    gauges.insert("jobs_queued", queued as u64);
+    gauges.insert("jobs_running", running as u64);
+
+    gauges
+}
+
+pub fn snapshot(app: &AppHandle) -> MetricsSnapshot {
+    let (counters, timings) = {
+        let registry = REGISTRY.lock().unwrap();
+        (registry.counters.clone(), registry.timings.clone())
+    };
+
+    MetricsSnapshot {
+        uptime_seconds: STARTED.get().map_or(0, |started| started.elapsed().as_secs()),
+        counters,
+        timings,
+        gauges: gauges(app),
+    }
+}
+
+/// Renders a snapshot in the Prometheus text exposition format. Timings
+/// become summaries in seconds.
+pub fn to_prometheus(snapshot: &MetricsSnapshot) -> String {
+    let mut out = String::new();
+
+    let _ = writeln!(out, "# TYPE {}uptime_seconds gauge", PROMETHEUS_PREFIX);
+    let _ = writeln!(out, "{}uptime_seconds {}", PROMETHEUS_PREFIX, snapshot.uptime_seconds);
+
+    for (name, value) in &snapshot.counters {
+        let _ = writeln!(out, "# TYPE {}{} counter", PROMETHEUS_PREFIX, name);
+        let _ = writeln!(out, "{}{} {}", PROMETHEUS_PREFIX, name, value);
+    }
+
+    for (name, timing) in &snapshot.timings {
+        let metric = format!("{}{}_seconds", PROMETHEUS_PREFIX, name);
+        let _ = writeln!(out, "# TYPE {} summary", metric);
+        let _ = writeln!(out, "{}_count {}", metric, timing.count);
+        let _ = writeln!(out, "{}_sum {}", metric, timing.total_ms / 1000.0);
+    }
+
+    for (name, value) in &snapshot.gauges {
+        let _ = writeln!(out, "# TYPE {}{} gauge", PROMETHEUS_PREFIX, name);
+        let _ = writeln!(out, "{}{} {}", PROMETHEUS_PREFIX, name, value);
+    }
+
+    out
+}
+
+#[tauri::command]
+pub fn get_metrics(app: AppHandle) -> MetricsSnapshot {
+    snapshot(&app)
+}
+
+/// The metrics as Prometheus text, for self-hosters scraping them.
+#[tauri::command]
+pub fn get_metrics_prometheus(app: AppHandle) -> String {
+    to_prometheus(&snapshot(&app))
+}
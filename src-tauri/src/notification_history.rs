@@ -0,0 +1,135 @@
+//! Log of every notification the app showed and what was done about it,
+//! persisted as notification_history.json.
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::reminders::FiredReminder;
+use crate::storage;
+use crate::tasks::now_iso;
+
+/// Oldest entries are dropped beyond this many.
+const MAX_HISTORY_ENTRIES: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ReminderAction {
+    Snoozed { until: String },
+    Completed,
+    /// Seen, so repeat reminders stop; the task stays as it is
+    Acknowledged,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationRecord {
+    pub id: String,
+    pub fired_at: String,
+    pub title: String,
+    pub body: String,
+    /// The reminders this notification covered (several for a batch)
+    pub reminders: Vec<FiredReminder>,
+    /// `None` means the notification was dismissed or ignored
+    pub action: Option<ReminderAction>,
+    pub action_at: Option<String>,
+}
+
+#[derive(Default)]
+pub struct NotificationHistory(Mutex<Vec<NotificationRecord>>);
+
+impl NotificationHistory {
+    pub fn records(&self) -> Vec<NotificationRecord> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+fn get_history_path(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("notification_history.json")
+}
+
+pub fn load(app: &AppHandle) -> Result<(), String> {
+    let path = get_history_path(app);
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read notification history: {}", e))?;
+
+    let records: Vec<NotificationRecord> = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse notification history: {}", e))?;
+
+    *app.state::<NotificationHistory>().0.lock().unwrap() = records;
+    Ok(())
+}
+
+fn save(app: &AppHandle, records: &[NotificationRecord]) {
+    if let Ok(content) = serde_json::to_string_pretty(records) {
+        fs::write(get_history_path(app), content).ok();
+    }
+}
+
+pub fn record(app: &AppHandle, title: &str, body: &str, reminders: &[FiredReminder]) {
+    let history = app.state::<NotificationHistory>();
+    let mut records = history.0.lock().unwrap();
+
+    records.push(NotificationRecord {
+        id: uuid::Uuid::new_v4().to_string(),
+        fired_at: now_iso(),
+        title: title.to_string(),
+        body: body.to_string(),
+        reminders: reminders.to_vec(),
+        action: None,
+        action_at: None,
+    });
+
+    let overflow = records.len().saturating_sub(MAX_HISTORY_ENTRIES);
+    records.drain(..overflow);
+
+    save(app, &records);
+}
+
+/// How many notifications covered `task_id` since `since`.
+pub fn count_since(app: &AppHandle, task_id: &str, since: DateTime<Local>) -> usize {
+    let history = app.state::<NotificationHistory>();
+    let records = history.0.lock().unwrap();
+    records
+        .iter()
+        .rev()
+        .take_while(|record| {
+            DateTime::parse_from_rfc3339(&record.fired_at).is_ok_and(|fired_at| fired_at >= since)
+        })
+        .filter(|record| record.reminders.iter().any(|r| r.task_id == task_id))
+        .count()
+}
+
+/// Attaches an action to the latest notification for `task_id` that hasn't
+/// been acted on yet.
+pub fn record_action(app: &AppHandle, task_id: &str, action: ReminderAction) {
+    let history = app.state::<NotificationHistory>();
+    let mut records = history.0.lock().unwrap();
+
+    let Some(record) = records.iter_mut().rev().find(|record| {
+        record.action.is_none() && record.reminders.iter().any(|r| r.task_id == task_id)
+    }) else {
+        return;
+    };
+
+    record.action = Some(action);
+    record.action_at = Some(now_iso());
+    save(app, &records);
+}
+
+/// Returns notifications newest first.
+#[tauri::command]
+pub fn get_notification_history(app: AppHandle, limit: Option<usize>) -> Vec<NotificationRecord> {
+    let mut records = app.state::<NotificationHistory>().records();
+    records.reverse();
+    records.truncate(limit.unwrap_or(MAX_HISTORY_ENTRIES));
+    records
+}
@@ -0,0 +1,410 @@
+//! OAuth2 for cloud integrations, shared so each one doesn't hand-roll its
+//! own token handling. Uses the authorization code flow with PKCE and a
+//! loopback redirect (`http://127.0.0.1:<port>/callback`), so no client
+//! secret needs to be shipped. Tokens are kept in the keychain and refreshed
+//! shortly before they expire.
+//!
+//! `start_oauth` returns the URL for the frontend to open in the browser,
+//! then waits for the redirect on a background thread and emits
+//! `oauth-finished` when the account is connected or the attempt fails.
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use crate::files::base64_encode;
+use crate::http;
+use crate::integrations::{self, Integration};
+use crate::secrets;
+use crate::settings::{self, OAuthClientSettings};
+
+pub const OAUTH_FINISHED_EVENT: &str = "oauth-finished";
+
+/// How long to wait for the browser to come back.
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Tokens this close to expiring are refreshed before use.
+const REFRESH_MARGIN_SECONDS: i64 = 60;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OAuthProvider {
+    Google,
+    Jira,
+}
+
+impl OAuthProvider {
+    const ALL: [OAuthProvider; 2] = [OAuthProvider::Google, OAuthProvider::Jira];
+
+    fn label(self) -> &'static str {
+        match self {
+            OAuthProvider::Google => "Google",
+            OAuthProvider::Jira => "Jira",
+        }
+    }
+
+    fn secret_name(self) -> &'static str {
+        match self {
+            OAuthProvider::Google => "oauth-google",
+            OAuthProvider::Jira => "oauth-jira",
+        }
+    }
+
+    fn auth_url(self) -> &'static str {
+        match self {
+            OAuthProvider::Google => "https://accounts.google.com/o/oauth2/v2/auth",
+            OAuthProvider::Jira => "https://auth.atlassian.com/authorize",
+        }
+    }
+
+    fn token_url(self) -> &'static str {
+        match self {
+            OAuthProvider::Google => "https://oauth2.googleapis.com/token",
+            OAuthProvider::Jira => "https://auth.atlassian.com/oauth/token",
+        }
+    }
+
+    fn scopes(self) -> &'static str {
+        match self {
+            OAuthProvider::Google => {
+                "https://www.googleapis.com/auth/calendar.readonly https://www.googleapis.com/auth/tasks.readonly"
+            }
+            OAuthProvider::Jira => "read:jira-work read:jira-user offline_access",
+        }
+    }
+
+    /// Extra parameters the provider needs to hand out a refresh token.
+    fn extra_params(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            OAuthProvider::Google => &[("access_type", "offline"), ("prompt", "consent")],
+            OAuthProvider::Jira => &[("audience", "api.atlassian.com"), ("prompt", "consent")],
+        }
+    }
+
+    fn client(self, app: &AppHandle) -> Result<OAuthClientSettings, String> {
+        let sync = settings::current(app).sync;
+        let client = match self {
+            OAuthProvider::Google => sync.google,
+            OAuthProvider::Jira => sync.jira,
+        };
+        if !client.enabled {
+            return Err(format!("The {} integration is turned off", self.label()));
+        }
+        if client.client_id.trim().is_empty() {
+            return Err(format!("Set a {} client id in settings first", self.label()));
+        }
+        Ok(client)
+    }
+
+    fn integration(self) -> Integration {
+        match self {
+            OAuthProvider::Google => Integration::Google,
+            OAuthProvider::Jira => Integration::Jira,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenSet {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthStatus {
+    pub provider: OAuthProvider,
+    pub connected: bool,
+    pub expires_at: Option<String>,
+    /// Without one the user has to reconnect when the token expires
+    pub can_refresh: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthFinished {
+    pub provider: OAuthProvider,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthStart {
+    /// For the frontend to open in the browser
+    pub auth_url: String,
+}
+
+fn base64_url(bytes: &[u8]) -> String {
+    base64_encode(bytes)
+        .replace('+', "-")
+        .replace('/', "_")
+        .trim_end_matches('=')
+        .to_string()
+}
+
+/// 32 random bytes, base64url-encoded.
+pub fn random_token() -> String {
+    let mut bytes = Vec::with_capacity(32);
+    bytes.extend_from_slice(uuid::Uuid::new_v4().as_bytes());
+    bytes.extend_from_slice(uuid::Uuid::new_v4().as_bytes());
+    base64_url(&bytes)
+}
+
+fn pkce_challenge(verifier: &str) -> String {
+    base64_url(&Sha256::digest(verifier.as_bytes()))
+}
+
+fn load_tokens(provider: OAuthProvider) -> Result<Option<TokenSet>, String> {
+    match secrets::get_secret(provider.secret_name())? {
+        Some(json) => serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| format!("Failed to parse stored {} token: {}", provider.label(), e)),
+        None => Ok(None),
+    }
+}
+
+fn store_tokens(provider: OAuthProvider, tokens: &TokenSet) -> Result<(), String> {
+    let json = serde_json::to_string(tokens)
+        .map_err(|e| format!("Failed to serialize {} token: {}", provider.label(), e))?;
+    secrets::set_secret(provider.secret_name(), Some(&json))
+}
+
+/// Posts to the token endpoint. A refresh response may leave out the refresh
+/// token, in which case `previous_refresh` is kept.
+fn request_tokens(
+    provider: OAuthProvider,
+    params: &[(&str, &str)],
+    previous_refresh: Option<String>,
+) -> Result<TokenSet, String> {
+    let response: TokenResponse = http::agent()
+        .post(provider.token_url())
+        .send_form(params.iter().copied())
+        .map_err(|e| format!("{} token request failed: {}", provider.label(), e))?
+        .body_mut()
+        .read_json()
+        .map_err(|e| format!("Failed to parse {} token response: {}", provider.label(), e))?;
+
+    Ok(TokenSet {
+        access_token: response.access_token,
+        refresh_token: response.refresh_token.or(previous_refresh),
+        expires_at: response
+            .expires_in
+            .map(|seconds| (Utc::now() + ChronoDuration::seconds(seconds)).to_rfc3339()),
+    })
+}
+
+/// A current access token for `provider`, refreshed first if it's about to
+/// expire. Integrations call this before each batch of requests.
+pub fn access_token(app: &AppHandle, provider: OAuthProvider) -> Result<String, String> {
+    let tokens = load_tokens(provider)?
+        .ok_or_else(|| format!("Not connected to {}", provider.label()))?;
+
+    let expires_at = tokens
+        .expires_at
+        .as_deref()
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok());
+    let fresh = expires_at
+        .is_none_or(|at| at.with_timezone(&Utc) - Utc::now() > ChronoDuration::seconds(REFRESH_MARGIN_SECONDS));
+    if fresh {
+        return Ok(tokens.access_token);
+    }
+
+    let refresh_token = tokens
+        .refresh_token
+        .ok_or_else(|| format!("The {} connection expired; connect again", provider.label()))?;
+    let client = provider.client(app)?;
+    let mut params = vec![
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token.as_str()),
+        ("client_id", client.client_id.as_str()),
+    ];
+    if let Some(secret) = client.client_secret.as_deref() {
+        params.push(("client_secret", secret));
+    }
+
+    let refreshed = request_tokens(provider, &params, Some(refresh_token.clone()))
+        .and_then(|tokens| store_tokens(provider, &tokens).map(|_| tokens));
+    integrations::record(app, provider.integration(), &refreshed);
+    Ok(refreshed?.access_token)
+}
+
+fn respond(stream: &mut TcpStream, status: &str, message: &str) {
+    let body = format!(
+        "<!doctype html><meta charset=\"utf-8\"><title>Afterglow</title><p>{}</p>",
+        message
+    );
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).ok();
+}
+
+fn query_param(query: &str, name: &str) -> Option<String> {
+    query.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        (key == name).then(|| http::percent_decode(&value.replace('+', " ")))?
+    })
+}
+
+/// Waits for the browser's redirect and returns the authorization code.
+fn wait_for_code(listener: TcpListener, state: &str) -> Result<String, String> {
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| format!("Failed to wait for the sign-in: {}", e))?;
+    let deadline = Instant::now() + CALLBACK_TIMEOUT;
+
+    loop {
+        let mut stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                if Instant::now() > deadline {
+                    return Err("Timed out waiting for the sign-in to finish".to_string());
+                }
+                thread::sleep(Duration::from_millis(200));
+                continue;
+            }
+            Err(e) => return Err(format!("Failed to wait for the sign-in: {}", e)),
+        };
+        stream.set_nonblocking(false).ok();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).ok();
+
+        let mut request_line = String::new();
+        if BufReader::new(&stream).read_line(&mut request_line).is_err() {
+            continue;
+        }
+        // "GET /callback?code=...&state=... HTTP/1.1"
+        let target = request_line.split_whitespace().nth(1).unwrap_or("");
+        let Some(query) = target.strip_prefix("/callback?") else {
+            // Browsers also ask for /favicon.ico
+            respond(&mut stream, "404 Not Found", "Not found");
+            continue;
+        };
+
+        if query_param(query, "state").as_deref() != Some(state) {
+            respond(&mut stream, "400 Bad Request", "This sign-in link doesn't match. Try connecting again.");
+            return Err("The sign-in response didn't match the request".to_string());
+        }
+        if let Some(error) = query_param(query, "error") {
+            respond(&mut stream, "200 OK", "Sign-in was cancelled. You can close this tab.");
+            return Err(format!("Sign-in was denied: {}", error));
+        }
+        return match query_param(query, "code") {
+            Some(code) => {
+                respond(&mut stream, "200 OK", "Connected to Afterglow. You can close this tab.");
+                Ok(code)
+            }
+            None => {
+                respond(&mut stream, "400 Bad Request", "The sign-in response had no code.");
+                Err("The sign-in response had no code".to_string())
+            }
+        };
+    }
+}
+
+/// Starts connecting an account and returns the URL to open. The result
+/// arrives as an `oauth-finished` event.
+#[tauri::command]
+pub fn start_oauth(app: AppHandle, provider: OAuthProvider) -> Result<OAuthStart, String> {
+    let client = provider.client(&app)?;
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .map_err(|e| format!("Failed to start the sign-in listener: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to start the sign-in listener: {}", e))?
+        .port();
+
+    let redirect_uri = format!("http://127.0.0.1:{}/callback", port);
+    let state = random_token();
+    let verifier = random_token();
+
+    let mut params = vec![
+        ("response_type", "code".to_string()),
+        ("client_id", client.client_id.clone()),
+        ("redirect_uri", redirect_uri.clone()),
+        ("scope", provider.scopes().to_string()),
+        ("state", state.clone()),
+        ("code_challenge", pkce_challenge(&verifier)),
+        ("code_challenge_method", "S256".to_string()),
+    ];
+    params.extend(provider.extra_params().iter().map(|&(k, v)| (k, v.to_string())));
+    let query: Vec<String> = params
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, http::encode_path_segment(value)))
+        .collect();
+    let auth_url = format!("{}?{}", provider.auth_url(), query.join("&"));
+
+    thread::spawn(move || {
+        let result = wait_for_code(listener, &state).and_then(|code| {
+            let mut params = vec![
+                ("grant_type", "authorization_code"),
+                ("code", code.as_str()),
+                ("redirect_uri", redirect_uri.as_str()),
+                ("client_id", client.client_id.as_str()),
+                ("code_verifier", verifier.as_str()),
+            ];
+            if let Some(secret) = client.client_secret.as_deref() {
+                params.push(("client_secret", secret));
+            }
+            let tokens = request_tokens(provider, &params, None)?;
+            store_tokens(provider, &tokens)
+        });
+        integrations::record(&app, provider.integration(), &result);
+
+        let payload = OAuthFinished {
+            provider,
+            error: result.err(),
+        };
+        app.emit(OAUTH_FINISHED_EVENT, payload).ok();
+    });
+
+    Ok(OAuthStart { auth_url })
+}
+
+pub fn is_connected(provider: OAuthProvider) -> bool {
+    load_tokens(provider).ok().flatten().is_some()
+}
+
+fn status(provider: OAuthProvider) -> Result<OAuthStatus, String> {
+    let tokens = load_tokens(provider)?;
+    Ok(OAuthStatus {
+        provider,
+        connected: tokens.is_some(),
+        expires_at: tokens.as_ref().and_then(|t| t.expires_at.clone()),
+        can_refresh: tokens.is_some_and(|t| t.refresh_token.is_some()),
+    })
+}
+
+#[tauri::command]
+pub fn get_oauth_status() -> Result<Vec<OAuthStatus>, String> {
+    OAuthProvider::ALL.into_iter().map(status).collect()
+}
+
+/// Refreshes the token if it's close to expiring, to check the connection
+/// still works.
+#[tauri::command]
+pub async fn refresh_oauth_token(app: AppHandle, provider: OAuthProvider) -> Result<OAuthStatus, String> {
+    access_token(&app, provider)?;
+    status(provider)
+}
+
+#[tauri::command]
+pub fn disconnect_oauth(provider: OAuthProvider) -> Result<(), String> {
+    secrets::set_secret(provider.secret_name(), None)
+}
@@ -0,0 +1,78 @@
+//! Turns a photo of a whiteboard or paper list into candidate switchbacks.
+//! The text is read by a local Tesseract install (`settings.ocr`), parsed
+//! like pasted text (`paste.rs`) and returned as a preview; nothing is
+//! created until the user adds the candidates they want.
+//!
+//! Reading is behind the `ocr` feature (on by default). Without it,
+//! `scan_task_list` only says so.
+
+use serde::Serialize;
+#[cfg(feature = "ocr")]
+use std::path::Path;
+#[cfg(feature = "ocr")]
+use std::process::Command;
+use tauri::AppHandle;
+
+#[cfg(feature = "ocr")]
+use crate::files::{self, FilePurpose};
+#[cfg(feature = "ocr")]
+use crate::paste;
+use crate::paste::PastedTask;
+#[cfg(feature = "ocr")]
+use crate::settings::{self, OcrSettings};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanPreview {
+    /// Everything Tesseract read, for when the split into lines is wrong
+    pub text: String,
+    pub candidates: Vec<PastedTask>,
+}
+
+#[cfg(feature = "ocr")]
+fn recognize(ocr: &OcrSettings, image: &Path) -> Result<String, String> {
+    let program = if ocr.tesseract_path.is_empty() { "tesseract" } else { &ocr.tesseract_path };
+    let mut command = Command::new(program);
+    command.arg(image).arg("stdout").args(["-l", ocr.language.as_str()]);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW, so no console flashes up
+        command.creation_flags(0x0800_0000);
+    }
+
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to run Tesseract: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or("no output");
+        return Err(format!("Tesseract failed: {}", reason.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Reads a photo picked with `choose_file` or dropped on the window and
+/// returns the tasks found on it, without creating them.
+#[cfg(feature = "ocr")]
+#[tauri::command]
+pub async fn scan_task_list(app: AppHandle, path: String) -> Result<ScanPreview, String> {
+    let ocr = settings::current(&app).ocr;
+    if !ocr.enabled {
+        return Err("Turn on reading lists from photos in settings first".to_string());
+    }
+    let image = files::take_grant(&app, &path, FilePurpose::Image)?;
+
+    let text = recognize(&ocr, &image)?;
+    let candidates = paste::parse(&text);
+    if candidates.is_empty() {
+        return Err("No tasks were found in the photo".to_string());
+    }
+    Ok(ScanPreview { text, candidates })
+}
+
+#[cfg(not(feature = "ocr"))]
+#[tauri::command]
+pub async fn scan_task_list(_app: AppHandle, _path: String) -> Result<ScanPreview, String> {
+    Err("This build of Afterglow was made without reading lists from photos".to_string())
+}
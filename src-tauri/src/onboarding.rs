@@ -0,0 +1,167 @@
+//! First-launch setup. On a fresh install (no tasks.json, no settings.json)
+//! the backend writes a starter workspace: a short tutorial of switchbacks,
+//! a few labels, and settings seeded from the system locale. The frontend is
+//! told with an `onboarding` event instead of landing on an empty board.
+
+use chrono::{Datelike, Duration};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::clock;
+use crate::locale;
+use crate::recurrence::format_task_date;
+use crate::settings::{self, Settings};
+use crate::storage::{self, TaskData};
+use crate::tasks::now_iso;
+
+/// Emitted when the main window loads after a first-launch bootstrap.
+pub const ONBOARDING_EVENT: &str = "onboarding";
+
+const DEFAULT_LABELS: &[&str] = &["work", "personal", "errands"];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnboardingInfo {
+    /// Ids of the tutorial tasks, so the frontend can highlight them
+    pub tutorial_task_ids: Vec<String>,
+    pub labels: Vec<String>,
+}
+
+/// Set after a bootstrap until the frontend dismisses onboarding.
+#[derive(Default)]
+pub struct OnboardingState(Mutex<Option<OnboardingInfo>>);
+
+fn is_first_launch(app: &AppHandle) -> bool {
+    !storage::has_saved_data(app) && !storage::get_app_data_dir(app).join("settings.json").exists()
+}
+
+fn tutorial_task(title: &str, notes: &str, extra: Value, sort_order: i64) -> Value {
+    let mut task = json!({
+        "id": uuid::Uuid::new_v4().to_string(),
+        "title": title,
+        "type": "one-off",
+        "priority": "p2",
+        "status": "not-started",
+        "createdAt": now_iso(),
+        "notes": notes,
+        "labels": [],
+        "stakeholders": [],
+        "sortOrder": sort_order,
+    });
+    if let (Some(task), Some(extra)) = (task.as_object_mut(), extra.as_object()) {
+        task.extend(extra.clone());
+    }
+    task
+}
+
+fn starter_data() -> TaskData {
+    let today = clock::today();
+    let due = |days: i64| format_task_date(today + Duration::days(days));
+    // The weekly review lands on the coming Friday
+    let days_to_friday = (5 + 7 - today.weekday().num_days_from_sunday() as i64 - 1) % 7 + 1;
+
+    let tasks = vec![
+        tutorial_task(
+            "Welcome to Afterglow",
+            "Switchbacks due today show up here. Mark this one done when you've read it.",
+            json!({ "dueDate": due(0), "priority": "p1" }),
+            1,
+        ),
+        tutorial_task(
+            "Add your first switchback",
+            "Press Ctrl+N to create one. Give it a due date to see it in Today and Week.",
+            json!({ "dueDate": due(0), "estimatedMinutes": 5 }),
+            2,
+        ),
+        tutorial_task(
+            "Explore the Canopy",
+            "Press A to see every open switchback, grouped and filterable by label.",
+            json!({ "dueDate": due(1), "labels": ["personal"] }),
+            3,
+        ),
+        tutorial_task(
+            "Plan a weekly review",
+            "Recurring switchbacks come back on their own. Press R to see them.",
+            json!({
+                "type": "recurring",
+                "dueDate": due(days_to_friday),
+                "recurrence": { "pattern": "weekly", "weekdays": [5] },
+                "labels": ["work"],
+            }),
+            4,
+        ),
+        tutorial_task(
+            "Someday: learn a new skill",
+            "Someday keeps ideas out of the way until you're ready. Press S to find them.",
+            json!({ "status": "someday", "priority": "p4" }),
+            5,
+        ),
+    ];
+
+    TaskData {
+        tasks,
+        labels: DEFAULT_LABELS.iter().map(|l| l.to_string()).collect(),
+        stakeholders: Vec::new(),
+    }
+}
+
+/// Default settings with the locale taken from the environment when it's
+/// one we support.
+fn starter_settings() -> Settings {
+    let mut settings = Settings::default();
+
+    let system_locale = ["LC_ALL", "LC_TIME", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty() && v != "C"));
+    if let Some(system_locale) = system_locale {
+        // "de_DE.UTF-8" -> "de-DE"
+        let tag = system_locale.split('.').next().unwrap_or_default().replace('_', "-");
+        settings.regional.locale = locale::resolve(&tag).tag.to_string();
+    }
+
+    settings
+}
+
+/// Writes the starter workspace on a fresh install. Runs during setup,
+/// before the window loads tasks.json.
+pub fn bootstrap(app: &AppHandle) -> Result<(), String> {
+    if !is_first_launch(app) {
+        return Ok(());
+    }
+
+    let data = starter_data();
+    storage::write_task_data(app, &data)?;
+    settings::save(app, starter_settings())?;
+
+    let info = OnboardingInfo {
+        tutorial_task_ids: data
+            .tasks
+            .iter()
+            .filter_map(|task| task["id"].as_str().map(str::to_string))
+            .collect(),
+        labels: data.labels,
+    };
+    *app.state::<OnboardingState>().0.lock().unwrap() = Some(info);
+
+    Ok(())
+}
+
+/// Tells a freshly loaded window about a pending onboarding. Windows that
+/// load before their listener is ready can ask with `get_onboarding`.
+pub fn announce(app: &AppHandle) {
+    if let Some(info) = app.state::<OnboardingState>().0.lock().unwrap().clone() {
+        app.emit(ONBOARDING_EVENT, info).ok();
+    }
+}
+
+#[tauri::command]
+pub fn get_onboarding(app: AppHandle) -> Option<OnboardingInfo> {
+    app.state::<OnboardingState>().0.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn dismiss_onboarding(app: AppHandle) {
+    *app.state::<OnboardingState>().0.lock().unwrap() = None;
+}
@@ -0,0 +1,210 @@
+//! Durable queue for outbound deliveries that failed, persisted in
+//! outbox.json. A background thread retries due deliveries with exponential
+//! backoff, so messages sent while offline go out once the network is back.
+//! Deliveries that keep failing are kept, marked failed, until retried or
+//! discarded.
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::channels::{self, ChannelKind};
+use crate::disk;
+use crate::log;
+use crate::power;
+use crate::storage;
+use crate::tasks::now_iso;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Keeps the background thread and `retry_outbox` from sending the same
+/// delivery twice.
+static FLUSH_LOCK: Mutex<()> = Mutex::new(());
+
+/// Attempts, including the original send, before a delivery is marked failed.
+const MAX_ATTEMPTS: u32 = 12;
+
+const FIRST_RETRY_SECONDS: i64 = 60;
+const MAX_RETRY_SECONDS: i64 = 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum OutboxTarget {
+    /// A message to a chat channel
+    #[serde(rename_all = "camelCase")]
+    Channel {
+        channel: ChannelKind,
+        title: String,
+        body: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Delivery {
+    pub id: String,
+    pub target: OutboxTarget,
+    pub created_at: String,
+    pub attempts: u32,
+    pub next_attempt_at: String,
+    pub last_error: String,
+    /// Gave up after `MAX_ATTEMPTS`; only retried on request
+    #[serde(default)]
+    pub failed: bool,
+}
+
+#[derive(Default)]
+pub struct OutboxStore(Mutex<Vec<Delivery>>);
+
+fn get_outbox_path(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("outbox.json")
+}
+
+pub fn load(app: &AppHandle) -> Result<(), String> {
+    let path = get_outbox_path(app);
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read outbox: {}", e))?;
+
+    let deliveries: Vec<Delivery> = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse outbox: {}", e))?;
+
+    *app.state::<OutboxStore>().0.lock().unwrap() = deliveries;
+    Ok(())
+}
+
+fn save(app: &AppHandle, deliveries: &[Delivery]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(deliveries)
+        .map_err(|e| format!("Failed to serialize outbox: {}", e))?;
+    disk::write_atomic(&get_outbox_path(app), content.as_bytes())
+        .map_err(|e| format!("Failed to save outbox: {}", e))
+}
+
+fn save_or_log(app: &AppHandle, deliveries: &[Delivery]) {
+    if let Err(e) = save(app, deliveries) {
+        log::error(app, e);
+    }
+}
+
+fn retry_at(attempts: u32) -> String {
+    let seconds = (FIRST_RETRY_SECONDS << attempts.saturating_sub(1).min(10)).min(MAX_RETRY_SECONDS);
+    (Utc::now() + ChronoDuration::seconds(seconds)).to_rfc3339()
+}
+
+fn is_due(delivery: &Delivery, now: DateTime<Utc>) -> bool {
+    !delivery.failed
+        && DateTime::parse_from_rfc3339(&delivery.next_attempt_at).map_or(true, |at| at <= now)
+}
+
+/// Queues a delivery whose first attempt failed with `error`.
+pub fn enqueue(app: &AppHandle, target: OutboxTarget, error: String) {
+    let store = app.state::<OutboxStore>();
+    let mut deliveries = store.0.lock().unwrap();
+    deliveries.push(Delivery {
+        id: uuid::Uuid::new_v4().to_string(),
+        target,
+        created_at: now_iso(),
+        attempts: 1,
+        next_attempt_at: retry_at(1),
+        last_error: error,
+        failed: false,
+    });
+    save_or_log(app, &deliveries);
+}
+
+fn deliver(app: &AppHandle, target: &OutboxTarget) -> Result<(), String> {
+    match target {
+        OutboxTarget::Channel { channel, title, body } => channels::send_one(app, *channel, title, body),
+    }
+}
+
+/// Retries every due delivery, oldest first.
+fn flush(app: &AppHandle) {
+    let _flushing = FLUSH_LOCK.lock().unwrap();
+    let now = Utc::now();
+    let store = app.state::<OutboxStore>();
+    let due: Vec<Delivery> = store.0.lock().unwrap().iter().filter(|d| is_due(d, now)).cloned().collect();
+    if due.is_empty() {
+        return;
+    }
+
+    // Sent without holding the lock; new deliveries may be queued meanwhile
+    let results: Vec<(String, Result<(), String>)> = due
+        .iter()
+        .map(|delivery| (delivery.id.clone(), deliver(app, &delivery.target)))
+        .collect();
+
+    let mut deliveries = store.0.lock().unwrap();
+    for (id, result) in results {
+        let Some(position) = deliveries.iter().position(|d| d.id == id) else {
+            continue;
+        };
+        match result {
+            Ok(()) => {
+                deliveries.remove(position);
+            }
+            Err(e) => {
+                let delivery = &mut deliveries[position];
+                delivery.attempts += 1;
+                delivery.last_error = e;
+                delivery.failed = delivery.attempts >= MAX_ATTEMPTS;
+                delivery.next_attempt_at = retry_at(delivery.attempts);
+            }
+        }
+    }
+    save_or_log(app, &deliveries);
+}
+
+/// Starts the thread that retries queued deliveries.
+pub fn start(app: AppHandle) {
+    thread::spawn(move || loop {
+        flush(&app);
+        power::sleep(&app, CHECK_INTERVAL);
+    });
+}
+
+/// Pending and failed deliveries, oldest first.
+#[tauri::command]
+pub fn get_outbox(app: AppHandle) -> Vec<Delivery> {
+    app.state::<OutboxStore>().0.lock().unwrap().clone()
+}
+
+/// Retries everything now, including deliveries that were given up on.
+#[tauri::command]
+pub async fn retry_outbox(app: AppHandle) -> Result<Vec<Delivery>, String> {
+    {
+        let store = app.state::<OutboxStore>();
+        let mut deliveries = store.0.lock().unwrap();
+        for delivery in deliveries.iter_mut() {
+            if delivery.failed {
+                delivery.failed = false;
+                delivery.attempts = 0;
+            }
+            delivery.next_attempt_at = now_iso();
+        }
+        save(&app, &deliveries)?;
+    }
+    flush(&app);
+    Ok(get_outbox(app))
+}
+
+#[tauri::command]
+pub fn discard_outbox_delivery(app: AppHandle, id: String) -> Result<(), String> {
+    let store = app.state::<OutboxStore>();
+    let mut deliveries = store.0.lock().unwrap();
+    let before = deliveries.len();
+    deliveries.retain(|d| d.id != id);
+    if deliveries.len() == before {
+        return Err(format!("Delivery not found: {}", id));
+    }
+    save(&app, &deliveries)
+}
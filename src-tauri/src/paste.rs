@@ -0,0 +1,271 @@
+//! Smart paste: multi-line text (bullets, numbered lists, Markdown
+//! checkboxes, "Name: task" lines) parsed into switchbacks for a preview.
+//! `#tags` become labels, `@name` and "Name:" prefixes become stakeholders,
+//! and a heading ("## Trip" or "Groceries:") labels the items under it.
+//! Nothing is created until the kept tasks go to `quick_add_tasks`.
+//!
+//! Scanned lists (`ocr.rs`) go through the same parser.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Lines with fewer letters or digits than this are noise, not tasks.
+const MIN_CHARS: usize = 3;
+
+/// Text before a colon that looks like a name but isn't one.
+const NOT_NAMES: [&str; 10] = ["todo", "to do", "note", "notes", "fyi", "re", "action", "next", "urgent", "important"];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PastedTask {
+    /// Ready for `quick_add_tasks`
+    pub task: Value,
+    /// It was ticked off on the list (`[x]`, ✓)
+    pub checked: bool,
+    /// The line (or lines) it came from
+    pub source: String,
+}
+
+struct Item {
+    title: String,
+    labels: Vec<String>,
+    stakeholders: Vec<String>,
+    checked: bool,
+    source: String,
+}
+
+/// Strips a bullet, number or checkbox from the start of a line. Returns the
+/// rest, whether there was a marker, and whether it was checked.
+fn strip_marker(line: &str) -> (&str, bool, bool) {
+    const CHECKED: [&str; 6] = ["[x]", "[X]", "☑", "☒", "✓", "✔"];
+    const UNCHECKED: [&str; 3] = ["[ ]", "☐", "□"];
+    const BULLETS: [char; 9] = ['-', '*', '•', '·', '○', '●', '▪', '–', '>'];
+
+    for marker in CHECKED {
+        if let Some(rest) = line.strip_prefix(marker) {
+            return (rest.trim_start(), true, true);
+        }
+    }
+    for marker in UNCHECKED {
+        if let Some(rest) = line.strip_prefix(marker) {
+            return (rest.trim_start(), true, false);
+        }
+    }
+    if let Some(rest) = line.strip_prefix(BULLETS) {
+        // "- [ ] task"
+        let (rest, _, checked) = strip_marker(rest.trim_start());
+        return (rest, true, checked);
+    }
+
+    // "1." "2)" "(3)"
+    let digits = line.trim_start_matches('(');
+    let number_end = digits.find(|c: char| !c.is_ascii_digit()).unwrap_or(digits.len());
+    if number_end > 0 {
+        if let Some(rest) = digits[number_end..].strip_prefix(['.', ')']) {
+            return (rest.trim_start(), true, false);
+        }
+    }
+    (line, false, false)
+}
+
+/// "## Trip" or "Groceries:" (a short line ending in a colon).
+fn heading(line: &str) -> Option<&str> {
+    if let Some(text) = line.strip_prefix('#') {
+        let text = text.trim_start_matches('#');
+        return text.starts_with(' ').then(|| text.trim());
+    }
+    let text = line.strip_suffix(':')?.trim();
+    (!text.is_empty() && text.split_whitespace().count() <= 4).then_some(text)
+}
+
+fn to_label(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
+
+/// Splits "Priya: send the deck" into the name and the task.
+fn split_name(text: &str) -> Option<(&str, &str)> {
+    let (name, rest) = text.split_once(':')?;
+    let (name, rest) = (name.trim(), rest.trim());
+    let words: Vec<&str> = name.split_whitespace().collect();
+    let looks_like_name = (1..=3).contains(&words.len())
+        && words.iter().all(|word| {
+            word.starts_with(|c: char| c.is_uppercase())
+                && word.chars().all(|c| c.is_alphabetic() || matches!(c, '.' | '-' | '\''))
+        })
+        && !NOT_NAMES.contains(&name.to_lowercase().as_str());
+    (looks_like_name && !rest.is_empty()).then_some((name, rest))
+}
+
+fn trim_word(word: &str) -> &str {
+    word.trim_end_matches([',', '.', ';', ':', '!', '?', ')'])
+}
+
+/// `name_prefix` also takes a "Name:" prefix as the stakeholder.
+fn parse_item(text: &str, checked: bool, source: &str, section: Option<&str>, name_prefix: bool) -> Option<Item> {
+    let mut labels: Vec<String> = section.map(to_label).into_iter().collect();
+    let mut stakeholders = Vec::new();
+
+    let text = match split_name(text).filter(|_| name_prefix) {
+        Some((name, rest)) => {
+            stakeholders.push(name.to_string());
+            rest
+        }
+        None => text,
+    };
+
+    let mut words = Vec::new();
+    for word in text.split_whitespace() {
+        if let Some(tag) = word.strip_prefix('#').map(trim_word) {
+            if !tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit()) {
+                labels.push(tag.to_lowercase());
+                continue;
+            }
+        }
+        if let Some(name) = word.strip_prefix('@').map(trim_word) {
+            if !name.is_empty() {
+                stakeholders.push(name.to_string());
+                continue;
+            }
+        }
+        words.push(word);
+    }
+    let title = words.join(" ");
+    if title.chars().filter(|c| c.is_alphanumeric()).count() < MIN_CHARS {
+        return None;
+    }
+
+    labels.dedup();
+    stakeholders.dedup();
+    Some(Item {
+        title,
+        labels,
+        stakeholders,
+        checked,
+        source: source.to_string(),
+    })
+}
+
+fn to_pasted(item: Item) -> PastedTask {
+    let mut task = json!({ "title": item.title });
+    if !item.labels.is_empty() {
+        task["labels"] = json!(item.labels);
+    }
+    if !item.stakeholders.is_empty() {
+        task["stakeholders"] = json!(item.stakeholders);
+    }
+    PastedTask {
+        task,
+        checked: item.checked,
+        source: item.source,
+    }
+}
+
+pub fn parse(text: &str) -> Vec<PastedTask> {
+    let lines: Vec<&str> = text.lines().filter(|line| !line.trim().is_empty()).collect();
+    let uses_markers = lines.iter().any(|line| strip_marker(line.trim()).1);
+
+    let mut items: Vec<Item> = Vec::new();
+    let mut section: Option<String> = None;
+    for line in lines {
+        let trimmed = line.trim();
+        let (text, had_marker, checked) = strip_marker(trimmed);
+
+        if !had_marker {
+            if let Some(heading) = heading(trimmed) {
+                section = Some(heading.to_string());
+                continue;
+            }
+        }
+
+        // In a bulleted list, an unmarked indented or lowercase line wraps
+        // the item above
+        let indented = line.starts_with([' ', '\t']);
+        let continues = uses_markers && !had_marker && (indented || text.starts_with(char::is_lowercase));
+        if let (true, Some(previous)) = (continues, items.last_mut()) {
+            previous.title.push(' ');
+            previous.title.push_str(text);
+            previous.source.push('\n');
+            previous.source.push_str(trimmed);
+            continue;
+        }
+
+        items.extend(parse_item(text, checked, trimmed, section.as_deref(), true));
+    }
+    items.into_iter().map(to_pasted).collect()
+}
+
+/// Parses one quick-add line, taking `#tags` and `@mentions` out of the
+/// title. A "Name:" prefix is left alone, since titles like "GitHub: ..."
+/// aren't people. `None` if no title is left.
+pub fn parse_line(text: &str) -> Option<Value> {
+    let text = text.trim();
+    parse_item(text, false, text, None, false).map(|item| to_pasted(item).task)
+}
+
+/// Parses pasted text into the tasks it would create, without creating them.
+#[tauri::command]
+pub fn preview_paste(text: String) -> Vec<PastedTask> {
+    parse(&text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles(tasks: &[PastedTask]) -> Vec<&str> {
+        tasks.iter().map(|pasted| pasted.task["title"].as_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn strips_checkboxes_bullets_and_numbers() {
+        assert_eq!(strip_marker("[x] Book flights"), ("Book flights", true, true));
+        assert_eq!(strip_marker("[ ] Pack"), ("Pack", true, false));
+        assert_eq!(strip_marker("- [X] Renew passport"), ("Renew passport", true, true));
+        assert_eq!(strip_marker("* Call the bank"), ("Call the bank", true, false));
+        assert_eq!(strip_marker("1. First"), ("First", true, false));
+        assert_eq!(strip_marker("2) Second"), ("Second", true, false));
+        assert_eq!(strip_marker("(3) Third"), ("Third", true, false));
+        assert_eq!(strip_marker("2026 budget review"), ("2026 budget review", false, false));
+    }
+
+    #[test]
+    fn headings_label_the_items_under_them() {
+        let tasks = parse("## Weekend trip\n- Book hotel\nGroceries:\n- Oat milk #urgent");
+        assert_eq!(titles(&tasks), ["Book hotel", "Oat milk"]);
+        assert_eq!(tasks[0].task["labels"], json!(["weekend-trip"]));
+        assert_eq!(tasks[1].task["labels"], json!(["groceries", "urgent"]));
+    }
+
+    #[test]
+    fn unmarked_lines_continue_the_item_above() {
+        let tasks = parse("- Draft the launch email\n  for the beta list\n- [x] Ship it\nand confirm");
+        assert_eq!(titles(&tasks), ["Draft the launch email for the beta list", "Ship it and confirm"]);
+        assert_eq!(tasks[0].source, "- Draft the launch email\nfor the beta list");
+        assert!(!tasks[0].checked && tasks[1].checked);
+    }
+
+    #[test]
+    fn name_prefixes_and_mentions_become_stakeholders() {
+        let tasks = parse("Priya Shah: send the deck\nTODO: water plants\n@sam review the PR");
+        assert_eq!(titles(&tasks), ["send the deck", "TODO: water plants", "review the PR"]);
+        assert_eq!(tasks[0].task["stakeholders"], json!(["Priya Shah"]));
+        assert!(tasks[1].task.get("stakeholders").is_none());
+        assert_eq!(tasks[2].task["stakeholders"], json!(["sam"]));
+    }
+
+    #[test]
+    fn quick_add_lines_keep_a_name_prefix() {
+        let task = parse_line("GitHub: triage issues #work").unwrap();
+        assert_eq!(task, json!({ "title": "GitHub: triage issues", "labels": ["work"] }));
+    }
+
+    #[test]
+    fn empty_and_noise_input_makes_nothing() {
+        assert!(parse("").is_empty());
+        assert!(parse("\n  \n- \n* ok\n").is_empty());
+        assert!(parse_line("#tag @name").is_none());
+    }
+}
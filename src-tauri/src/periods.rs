@@ -0,0 +1,61 @@
+//! Weeks and fiscal quarters as the user's company counts them
+//! (`settings.regional`), shared by the calendar, digests and reports.
+//!
+//! US weeks start on Sunday and week 1 is the one with January 1st in it;
+//! ISO weeks start on Monday and week 1 is the one with the year's first
+//! Thursday. A fiscal year is named after the calendar year it ends in, so
+//! with an October start, October 2026 is in FY2027.
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::settings::{RegionalSettings, WeekNumbering};
+
+/// The first day of the week `day` is in.
+pub fn week_start(day: NaiveDate, numbering: WeekNumbering) -> NaiveDate {
+    let offset = match numbering {
+        WeekNumbering::Us => day.weekday().num_days_from_sunday(),
+        WeekNumbering::Iso => day.weekday().num_days_from_monday(),
+    };
+    day - Duration::days(offset.into())
+}
+
+/// The week-numbering year and week of `day`.
+pub fn week_number(day: NaiveDate, numbering: WeekNumbering) -> (i32, u32) {
+    match numbering {
+        WeekNumbering::Iso => {
+            let week = day.iso_week();
+            (week.year(), week.week())
+        }
+        WeekNumbering::Us => {
+            let jan_first = NaiveDate::from_ymd_opt(day.year(), 1, 1).unwrap_or(day);
+            (day.year(), (day.ordinal0() + jan_first.weekday().num_days_from_sunday()) / 7 + 1)
+        }
+    }
+}
+
+/// "W42", or "2026-W01" style when `with_year` is set.
+pub fn format_week(day: NaiveDate, numbering: WeekNumbering, with_year: bool) -> String {
+    let (year, week) = week_number(day, numbering);
+    if with_year {
+        format!("{}-W{:02}", year, week)
+    } else {
+        format!("W{}", week)
+    }
+}
+
+/// The fiscal year and quarter (1-4) of `day`.
+pub fn fiscal_quarter(day: NaiveDate, regional: &RegionalSettings) -> (i32, u32) {
+    let start = regional.fiscal_year_start_month;
+    let year = if start > 1 && day.month() >= start { day.year() + 1 } else { day.year() };
+    (year, (day.month() + 12 - start) % 12 / 3 + 1)
+}
+
+/// `2026-Q3` for calendar years, `FY2027-Q1` for other fiscal years.
+pub fn format_quarter(day: NaiveDate, regional: &RegionalSettings) -> String {
+    let (year, quarter) = fiscal_quarter(day, regional);
+    if regional.fiscal_year_start_month > 1 {
+        format!("FY{}-Q{}", year, quarter)
+    } else {
+        format!("{}-Q{}", year, quarter)
+    }
+}
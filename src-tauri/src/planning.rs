@@ -0,0 +1,158 @@
+//! Day planning: picks the switchbacks that fit into a day. What's overdue
+//! or due by then goes in first, the rest follows the "what next" ranking
+//! (`scoring.rs`), and whatever doesn't fit is skipped for something smaller.
+//! Timed events from calendar feeds come out of the capacity, and an
+//! out-of-office day has none. Nothing changes until the UI applies the plan.
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+use crate::calendar_feeds::{self, CalendarEvent};
+use crate::recurrence::{format_task_date, parse_task_date};
+use crate::scoring;
+use crate::settings;
+use crate::storage;
+use crate::tasks::{str_field, task_id};
+
+/// Minutes assumed for a task without an estimate.
+const UNESTIMATED_MINUTES: i64 = 30;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PlanReason {
+    Overdue,
+    Due,
+    Ranked,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedTask {
+    pub id: String,
+    pub title: String,
+    pub minutes: i64,
+    /// `false` when `minutes` is the default for unestimated tasks
+    pub estimated: bool,
+    pub reason: PlanReason,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DayPlan {
+    pub date: String,
+    pub capacity_minutes: i64,
+    /// Timed calendar events on the day
+    pub meeting_minutes: i64,
+    pub available_minutes: i64,
+    pub planned_minutes: i64,
+    pub tasks: Vec<PlannedTask>,
+    /// Overdue or due tasks that didn't fit
+    pub left_out: Vec<PlannedTask>,
+}
+
+/// Minutes of `day` taken by timed events, overlaps counted once.
+fn meeting_minutes(events: &[CalendarEvent], day: NaiveDate) -> i64 {
+    let parse = |value: &str| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M").ok();
+    let day_start = day.and_hms_opt(0, 0, 0).unwrap_or_default();
+    let day_end = day_start + Duration::days(1);
+
+    let mut spans: Vec<(NaiveDateTime, NaiveDateTime)> = events
+        .iter()
+        .filter(|event| !event.event.all_day)
+        .filter_map(|event| Some((parse(&event.event.start)?, parse(&event.event.end)?)))
+        .map(|(start, end)| (start.max(day_start), end.min(day_end)))
+        .filter(|(start, end)| start < end)
+        .collect();
+    spans.sort();
+
+    let mut total = 0;
+    let mut covered_until = day_start;
+    for (start, end) in spans {
+        let start = start.max(covered_until);
+        if end > start {
+            total += (end - start).num_minutes();
+            covered_until = end;
+        }
+    }
+    total
+}
+
+fn plan(
+    tasks: &[Value],
+    config: &settings::ScoringSettings,
+    day: NaiveDate,
+    available: i64,
+) -> (Vec<PlannedTask>, Vec<PlannedTask>) {
+    let by_id: HashMap<&str, &Value> = tasks.iter().filter_map(|t| Some((task_id(t)?, t))).collect();
+
+    let mut candidates: Vec<PlannedTask> = scoring::score(tasks, config, day)
+        .into_iter()
+        .filter(|scored| !scored.blocked)
+        .filter_map(|scored| {
+            let task = by_id.get(scored.id.as_str())?;
+            let estimate = task.get("estimatedMinutes").and_then(Value::as_i64).filter(|m| *m > 0);
+            let due = str_field(task, "dueDate").and_then(parse_task_date);
+            let reason = match due {
+                Some(due) if due < day => PlanReason::Overdue,
+                Some(due) if due == day => PlanReason::Due,
+                _ => PlanReason::Ranked,
+            };
+            Some(PlannedTask {
+                id: scored.id,
+                title: scored.title,
+                minutes: estimate.unwrap_or(UNESTIMATED_MINUTES),
+                estimated: estimate.is_some(),
+                reason,
+            })
+        })
+        .collect();
+    // Stable, so the ranking holds within each group
+    candidates.sort_by_key(|task| !matches!(task.reason, PlanReason::Overdue | PlanReason::Due));
+
+    let mut remaining = available;
+    let mut planned = Vec::new();
+    let mut left_out = Vec::new();
+    for task in candidates {
+        if task.minutes <= remaining {
+            remaining -= task.minutes;
+            planned.push(task);
+        } else if !matches!(task.reason, PlanReason::Ranked) {
+            left_out.push(task);
+        }
+    }
+    (planned, left_out)
+}
+
+/// Suggests what to work on `date` (`yyyy-MM-dd`) within `capacity_minutes`
+/// of working time, less that day's meetings.
+#[tauri::command]
+pub fn plan_day(app: AppHandle, date: String, capacity_minutes: i64) -> Result<DayPlan, String> {
+    let day = parse_task_date(&date).ok_or_else(|| format!("Invalid date \"{}\"", date))?;
+    if capacity_minutes < 0 {
+        return Err("Capacity can't be negative".to_string());
+    }
+    let data = storage::read_task_data(&app)?;
+    let config = settings::current(&app).scoring;
+
+    let events = calendar_feeds::events_between(&app, day, day);
+    let meeting_minutes = meeting_minutes(&events, day);
+    let available_minutes = if calendar_feeds::is_out_of_office(&app, day) {
+        0
+    } else {
+        (capacity_minutes - meeting_minutes).max(0)
+    };
+
+    let (tasks, left_out) = plan(&data.tasks, &config, day, available_minutes);
+    Ok(DayPlan {
+        date: format_task_date(day),
+        capacity_minutes,
+        meeting_minutes,
+        available_minutes,
+        planned_minutes: tasks.iter().map(|task| task.minutes).sum(),
+        tasks,
+        left_out,
+    })
+}
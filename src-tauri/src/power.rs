@@ -0,0 +1,137 @@
+//! Low-power mode: while the laptop runs on battery (or always, or never,
+//! per `settings.power`), background loops such as calendar feed refreshes,
+//! outbox retries, rules, rollover, the agenda email, scheduled exports,
+//! stakeholder dates, the write-behind flush and the share inbox poll wait
+//! `stretch_factor` times longer, and backups are taken at most every
+//! `LOW_POWER_BACKUP_INTERVAL`. The normal pace comes back by itself on AC
+//! power.
+//!
+//! The power source is asked for at most every `CHECK_INTERVAL`.
+
+use chrono::Duration as ChronoDuration;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+use crate::settings::{self, PowerMode};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(2 * 60);
+
+/// Least time between backups in low-power mode.
+pub const LOW_POWER_BACKUP_INTERVAL: ChronoDuration = ChronoDuration::minutes(15);
+
+/// When the power source was last checked, and whether it was the battery.
+static LAST_CHECK: Mutex<Option<(Instant, Option<bool>)>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerState {
+    /// `None` when the system doesn't say
+    pub on_battery: Option<bool>,
+    pub low_power: bool,
+}
+
+/// On Linux, any online mains supply means AC; otherwise a discharging
+/// battery means battery.
+#[cfg(target_os = "linux")]
+fn read_on_battery() -> Option<bool> {
+    let read = |path: std::path::PathBuf| std::fs::read_to_string(path).ok().map(|s| s.trim().to_string());
+    let mut discharging = None;
+    for entry in std::fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+        let path = entry.path();
+        match read(path.join("type")).as_deref() {
+            Some("Mains") | Some("USB") if read(path.join("online")).as_deref() == Some("1") => return Some(false),
+            Some("Battery") => {
+                let status = read(path.join("status"));
+                discharging = Some(discharging.unwrap_or(false) || status.as_deref() == Some("Discharging"));
+            }
+            _ => {}
+        }
+    }
+    discharging
+}
+
+#[cfg(target_os = "macos")]
+fn read_on_battery() -> Option<bool> {
+    let output = std::process::Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    if text.contains("'Battery Power'") {
+        Some(true)
+    } else if text.contains("'AC Power'") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// `BatteryStatus` 1 is "discharging"; no output means there is no battery.
+#[cfg(target_os = "windows")]
+fn read_on_battery() -> Option<bool> {
+    use std::os::windows::process::CommandExt;
+
+    let mut command = std::process::Command::new("powershell");
+    command.args([
+        "-NoProfile",
+        "-NonInteractive",
+        "-Command",
+        "(Get-CimInstance -ClassName Win32_Battery).BatteryStatus",
+    ]);
+    // CREATE_NO_WINDOW, so no console flashes up
+    command.creation_flags(0x0800_0000);
+    let output = command.output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let statuses: Vec<&str> = text.split_whitespace().collect();
+    if statuses.is_empty() {
+        return Some(false);
+    }
+    Some(statuses.iter().all(|status| *status == "1"))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn read_on_battery() -> Option<bool> {
+    None
+}
+
+pub fn on_battery() -> Option<bool> {
+    let mut last = LAST_CHECK.lock().unwrap();
+    if let Some((at, on_battery)) = *last {
+        if at.elapsed() < CHECK_INTERVAL {
+            return on_battery;
+        }
+    }
+    let on_battery = read_on_battery();
+    *last = Some((Instant::now(), on_battery));
+    on_battery
+}
+
+pub fn is_low_power(app: &AppHandle) -> bool {
+    match settings::current(app).power.low_power {
+        PowerMode::Auto => on_battery().unwrap_or(false),
+        PowerMode::On => true,
+        PowerMode::Off => false,
+    }
+}
+
+/// Sleeps for `interval`, or `stretch_factor` times as long in low-power
+/// mode. The mode is checked after each `interval`, so plugging in brings
+/// the normal pace back within one.
+pub fn sleep(app: &AppHandle, interval: Duration) {
+    let mut slept = 0;
+    loop {
+        thread::sleep(interval);
+        slept += 1;
+        if !is_low_power(app) || slept >= settings::current(app).power.stretch_factor {
+            return;
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_power_state(app: AppHandle) -> PowerState {
+    PowerState {
+        on_battery: on_battery(),
+        low_power: is_low_power(&app),
+    }
+}
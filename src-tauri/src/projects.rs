@@ -0,0 +1,115 @@
+//! Effective task defaults for a project. A switchback belongs to the
+//! project named in its optional `project` field; each project's settings
+//! override its parent's, which override `task_defaults`. The chain is
+//! resolved here so everything that creates tasks applies the same values.
+
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+use crate::recurrence::{format_task_date, parse_task_date};
+use crate::settings::{self, ProjectSettings, ReminderDefault, Settings};
+use crate::tasks::{self, str_field};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveDefaults {
+    pub project: Option<String>,
+    pub labels: Vec<String>,
+    pub reminder: ReminderDefault,
+    pub working_days: Vec<u32>,
+    /// The project and its ancestors, nearest first
+    pub chain: Vec<String>,
+}
+
+/// The project and its ancestors, nearest first. Unknown projects end the
+/// chain; settings validation keeps it free of cycles.
+fn chain<'a>(settings: &'a Settings, project: Option<&'a str>) -> Vec<(&'a str, &'a ProjectSettings)> {
+    let mut chain: Vec<(&str, &ProjectSettings)> = Vec::new();
+    let mut next = project;
+    while let Some(name) = next {
+        let Some(project) = settings.projects.get(name) else {
+            break;
+        };
+        if chain.iter().any(|(seen, _)| *seen == name) {
+            break;
+        }
+        chain.push((name, project));
+        next = project.parent.as_deref();
+    }
+    chain
+}
+
+pub fn effective(settings: &Settings, project: Option<&str>) -> EffectiveDefaults {
+    let chain = chain(settings, project);
+    let defaults = &settings.task_defaults;
+    EffectiveDefaults {
+        project: project.map(str::to_string),
+        labels: chain
+            .iter()
+            .find_map(|(_, p)| p.labels.clone())
+            .unwrap_or_else(|| defaults.labels.clone()),
+        reminder: chain
+            .iter()
+            .find_map(|(_, p)| p.reminder.clone())
+            .unwrap_or_else(|| defaults.reminder.clone()),
+        working_days: chain
+            .iter()
+            .find_map(|(_, p)| p.working_days.clone())
+            .unwrap_or_else(|| defaults.working_days.clone()),
+        chain: chain.iter().map(|(name, _)| name.to_string()).collect(),
+    }
+}
+
+/// `day`, or the last working day before it.
+pub fn working_day_on_or_before(day: NaiveDate, working_days: &[u32]) -> NaiveDate {
+    (0..7)
+        .map(|back| day - Duration::days(back))
+        .find(|d| working_days.contains(&d.weekday().num_days_from_sunday()))
+        .unwrap_or(day)
+}
+
+/// `day`, or the first working day after it.
+pub fn working_day_on_or_after(day: NaiveDate, working_days: &[u32]) -> NaiveDate {
+    (0..7)
+        .map(|ahead| day + Duration::days(ahead))
+        .find(|d| working_days.contains(&d.weekday().num_days_from_sunday()))
+        .unwrap_or(day)
+}
+
+/// Adds the task's effective default labels and, for a task with a due date
+/// and no reminder, the default reminder.
+pub fn apply_defaults(settings: &Settings, task: &mut Value) {
+    let defaults = effective(settings, str_field(task, "project"));
+
+    if !defaults.labels.is_empty() {
+        let mut labels: Vec<String> = tasks::labels(task).map(str::to_string).collect();
+        for label in &defaults.labels {
+            if !labels.contains(label) {
+                labels.push(label.clone());
+            }
+        }
+        task["labels"] = json!(labels);
+    }
+
+    let has_reminder = task.get("reminderAt").is_some_and(|v| !v.is_null());
+    let due = str_field(task, "dueDate").and_then(parse_task_date);
+    if let (false, true, Some(due)) = (has_reminder, defaults.reminder.enabled, due) {
+        let day = due - Duration::days(defaults.reminder.days_before as i64);
+        let day = working_day_on_or_before(day, &defaults.working_days);
+        task["reminderAt"] = json!(format!("{}T{}", format_task_date(day), defaults.reminder.at));
+    }
+}
+
+/// The values new tasks in `project` get; no project gives the global ones.
+#[tauri::command]
+pub fn get_effective_settings(app: AppHandle, project: Option<String>) -> Result<EffectiveDefaults, String> {
+    let settings = settings::current(&app);
+    if let Some(project) = &project {
+        if !settings.projects.contains_key(project) {
+            return Err(format!("Project not found: {}", project));
+        }
+    }
+    Ok(effective(&settings, project.as_deref()))
+}
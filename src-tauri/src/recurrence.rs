@@ -0,0 +1,344 @@
+//! Rust port of `src/utils/recurrence.ts`.
+//!
+//! The frontend remains the primary place recurring tasks are advanced, but the
+//! backend needs the same rules when it completes tasks on its own (e.g. from a
+//! reminder). Keep the two implementations in sync, quirks included.
+
+use chrono::{Datelike, Duration, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RecurrencePattern {
+    Weekly,
+    Biweekly,
+    Monthly,
+    Quarterly,
+    Yearly,
+    BusinessDays,
+    NthWeekday,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecurrenceScope {
+    Month,
+    Quarter,
+    Year,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurrenceRule {
+    pub pattern: RecurrencePattern,
+    pub weekdays: Option<Vec<u32>>,
+    pub interval: Option<u32>,
+    pub nth_week: Option<u32>,
+    pub day_of_month: Option<u32>,
+    pub scope: Option<RecurrenceScope>,
+}
+
+/// Parses a task date (`yyyy-MM-dd`, optionally followed by a time part).
+pub fn parse_task_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()
+}
+
+pub fn format_task_date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+/// Calculate the next due date based on a recurrence rule.
+///
+/// `today` is used as the base date when the task has no due date yet.
+pub fn next_recurrence_date(
+    rule: &RecurrenceRule,
+    current_due_date: Option<&str>,
+    today: NaiveDate,
+) -> Option<String> {
+    let base_date = current_due_date.and_then(parse_task_date).unwrap_or(today);
+
+    let next_date = match rule.pattern {
+        RecurrencePattern::Weekly => next_weekly_date(base_date, rule.weekdays.as_deref()),
+        RecurrencePattern::Biweekly => {
+            next_weekly_date(base_date, rule.weekdays.as_deref()) + Duration::weeks(1)
+        }
+        RecurrencePattern::Monthly => next_monthly_date(base_date, rule.day_of_month),
+        RecurrencePattern::Quarterly => {
+            let next = base_date.checked_add_months(Months::new(3))?;
+            match rule.day_of_month {
+                // Like `new Date(y, m, day)`, days past the end of the month roll over
+                Some(day) if day > 0 => next.with_day(1)? + Duration::days(day as i64 - 1),
+                _ => next,
+            }
+        }
+        RecurrencePattern::Yearly => base_date.checked_add_months(Months::new(12))?,
+        RecurrencePattern::BusinessDays => {
+            next_business_day(base_date, non_zero_or_one(rule.interval))
+        }
+        RecurrencePattern::NthWeekday => {
+            // Mirrors `rule.weekdays?.[0] || 1` on the frontend
+            let weekday = rule
+                .weekdays
+                .as_ref()
+                .and_then(|days| days.first().copied())
+                .filter(|&day| day != 0)
+                .unwrap_or(1);
+            next_nth_weekday(
+                base_date,
+                non_zero_or_one(rule.nth_week),
+                weekday,
+                rule.scope.unwrap_or(RecurrenceScope::Month),
+            )?
+        }
+    };
+
+    Some(format_task_date(next_date))
+}
+
+fn non_zero_or_one(value: Option<u32>) -> u32 {
+    value.filter(|&v| v != 0).unwrap_or(1)
+}
+
+fn day_of_week(date: NaiveDate) -> u32 {
+    date.weekday().num_days_from_sunday()
+}
+
+/// Get the next occurrence of specific weekdays (0-6, Sun-Sat)
+fn next_weekly_date(from_date: NaiveDate, weekdays: Option<&[u32]>) -> NaiveDate {
+    let mut sorted_days = match weekdays {
+        Some(days) if !days.is_empty() => days.to_vec(),
+        _ => return from_date + Duration::weeks(1),
+    };
+    sorted_days.sort_unstable();
+
+    let today = day_of_week(from_date) as i64;
+
+    // Find next weekday after current day
+    if let Some(&day) = sorted_days.iter().find(|&&day| day as i64 > today) {
+        return from_date + Duration::days(day as i64 - today);
+    }
+
+    // If no day found this week, go to next week's first occurrence
+    from_date + Duration::weeks(1) + Duration::days(sorted_days[0] as i64 - today)
+}
+
+fn next_monthly_date(from_date: NaiveDate, day_of_month: Option<u32>) -> NaiveDate {
+    let target_day = day_of_month.filter(|&d| d != 0).unwrap_or(from_date.day());
+    let next_month = from_date
+        .checked_add_months(Months::new(1))
+        .unwrap_or(from_date);
+
+    // Handle months with fewer days
+    let actual_day = target_day.min(days_in_month(next_month));
+    next_month.with_day(actual_day).unwrap_or(next_month)
+}
+
+pub fn days_in_month(date: NaiveDate) -> u32 {
+    let first = date.with_day(1).unwrap_or(date);
+    let next_first = first
+        .checked_add_months(Months::new(1))
+        .unwrap_or(first);
+    (next_first - first).num_days() as u32
+}
+
+pub fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// Get next business day (skip weekends)
+fn next_business_day(from_date: NaiveDate, skip_days: u32) -> NaiveDate {
+    let mut current = from_date + Duration::days(1);
+    let mut days_skipped = 0;
+
+    while days_skipped < skip_days {
+        if !is_weekend(current) {
+            days_skipped += 1;
+            if days_skipped >= skip_days {
+                break;
+            }
+        }
+        current += Duration::days(1);
+    }
+
+    // Make sure we land on a business day
+    while is_weekend(current) {
+        current += Duration::days(1);
+    }
+
+    current
+}
+
+/// Get the Nth weekday of the next month, quarter, or year
+fn next_nth_weekday(
+    from_date: NaiveDate,
+    nth_week: u32,
+    weekday: u32,
+    scope: RecurrenceScope,
+) -> Option<NaiveDate> {
+    let scope_start = match scope {
+        RecurrenceScope::Month => from_date.with_day(1)?.checked_add_months(Months::new(1))?,
+        RecurrenceScope::Quarter => {
+            let quarter_month = (from_date.month0() / 3) * 3 + 1;
+            NaiveDate::from_ymd_opt(from_date.year(), quarter_month, 1)?
+                .checked_add_months(Months::new(3))?
+        }
+        RecurrenceScope::Year => NaiveDate::from_ymd_opt(from_date.year() + 1, 1, 1)?,
+    };
+
+    // Find the first occurrence of the weekday, then move to the Nth occurrence
+    let offset = (weekday as i64 - day_of_week(scope_start) as i64).rem_euclid(7);
+    Some(scope_start + Duration::days(offset) + Duration::weeks(nth_week as i64 - 1))
+}
+
+/// Last weekday of the month, as a day of the month.
+fn last_business_day_of_month(date: NaiveDate) -> u32 {
+    let mut day = days_in_month(date);
+    while day > 1 && date.with_day(day).is_some_and(is_weekend) {
+        day -= 1;
+    }
+    day
+}
+
+/// Matches a monthly day, falling back to the last business day in months
+/// too short for it.
+fn is_day_of_month(date: NaiveDate, target_day: u32) -> bool {
+    if target_day > days_in_month(date) {
+        return date.day() == last_business_day_of_month(date);
+    }
+    date.day() == target_day
+}
+
+/// Whether an active recurring task shows on `date`. Mirrors
+/// `doesRecurringTaskApplyToDate`.
+pub fn applies_to_date(rule: &RecurrenceRule, date: NaiveDate) -> bool {
+    let weekday = day_of_week(date);
+    let weekdays = rule.weekdays.as_deref().unwrap_or_default();
+
+    match rule.pattern {
+        RecurrencePattern::Weekly | RecurrencePattern::Biweekly => {
+            // No weekdays shows every day
+            weekdays.is_empty() || weekdays.contains(&weekday)
+        }
+        RecurrencePattern::Monthly => rule
+            .day_of_month
+            .filter(|&d| d != 0)
+            .is_some_and(|target| is_day_of_month(date, target)),
+        RecurrencePattern::Quarterly => {
+            date.month0().is_multiple_of(3)
+                && rule
+                    .day_of_month
+                    .filter(|&d| d != 0)
+                    .is_some_and(|target| is_day_of_month(date, target))
+        }
+        // Would need the original date to compare
+        RecurrencePattern::Yearly => false,
+        RecurrencePattern::BusinessDays => !is_weekend(date),
+        RecurrencePattern::NthWeekday => {
+            let (Some(&target), Some(nth)) = (weekdays.first(), rule.nth_week.filter(|&n| n != 0)) else {
+                return false;
+            };
+            let in_scope = match rule.scope.unwrap_or(RecurrenceScope::Month) {
+                RecurrenceScope::Month => true,
+                RecurrenceScope::Quarter => date.month0().is_multiple_of(3),
+                RecurrenceScope::Year => date.month0() == 0,
+            };
+            in_scope && weekday == target && (date.day() - 1) / 7 + 1 == nth
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: RecurrencePattern) -> RecurrenceRule {
+        RecurrenceRule {
+            pattern,
+            weekdays: None,
+            interval: None,
+            nth_week: None,
+            day_of_month: None,
+            scope: None,
+        }
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        parse_task_date(value).unwrap()
+    }
+
+    fn next(rule: &RecurrenceRule, due: &str) -> String {
+        next_recurrence_date(rule, Some(due), date("2026-10-14")).unwrap()
+    }
+
+    #[test]
+    fn task_dates_ignore_a_time_part() {
+        assert_eq!(parse_task_date("2026-10-14T09:30"), Some(date("2026-10-14")));
+        assert_eq!(parse_task_date(""), None);
+        assert_eq!(parse_task_date("2026-02-30"), None);
+        assert_eq!(parse_task_date("10/14/2026"), None);
+    }
+
+    #[test]
+    fn no_due_date_counts_from_today() {
+        let weekly = rule(RecurrencePattern::Weekly);
+        assert_eq!(next_recurrence_date(&weekly, None, date("2026-10-14")).as_deref(), Some("2026-10-21"));
+    }
+
+    #[test]
+    fn weekly_wraps_to_the_first_weekday_of_next_week() {
+        let mut monday_and_wednesday = rule(RecurrencePattern::Weekly);
+        monday_and_wednesday.weekdays = Some(vec![3, 1]);
+        assert_eq!(next(&monday_and_wednesday, "2026-10-12"), "2026-10-14");
+        assert_eq!(next(&monday_and_wednesday, "2026-10-16"), "2026-10-19");
+    }
+
+    #[test]
+    fn monthly_on_the_31st_lands_on_the_last_day_of_february() {
+        let mut monthly = rule(RecurrencePattern::Monthly);
+        monthly.day_of_month = Some(31);
+        assert_eq!(next(&monthly, "2026-01-31"), "2026-02-28");
+        assert_eq!(next(&monthly, "2028-01-31"), "2028-02-29");
+        assert_eq!(next(&monthly, "2028-02-29"), "2028-03-31");
+    }
+
+    #[test]
+    fn yearly_from_a_leap_day_falls_back_to_february_28th() {
+        assert_eq!(next(&rule(RecurrencePattern::Yearly), "2028-02-29"), "2029-02-28");
+    }
+
+    #[test]
+    fn business_days_skip_the_weekend() {
+        let mut business_days = rule(RecurrencePattern::BusinessDays);
+        assert_eq!(next(&business_days, "2026-10-16"), "2026-10-19");
+        business_days.interval = Some(3);
+        assert_eq!(next(&business_days, "2026-10-15"), "2026-10-20");
+    }
+
+    #[test]
+    fn nth_weekday_moves_to_the_next_month() {
+        let mut first_monday = rule(RecurrencePattern::NthWeekday);
+        first_monday.weekdays = Some(vec![1]);
+        first_monday.nth_week = Some(1);
+        assert_eq!(next(&first_monday, "2026-10-14"), "2026-11-02");
+    }
+
+    #[test]
+    fn a_day_past_the_end_of_the_month_shows_on_its_last_business_day() {
+        let mut monthly = rule(RecurrencePattern::Monthly);
+        monthly.day_of_month = Some(31);
+        // February 28th 2026 is a Saturday
+        assert!(applies_to_date(&monthly, date("2026-02-27")));
+        assert!(!applies_to_date(&monthly, date("2026-02-28")));
+        assert!(applies_to_date(&monthly, date("2026-03-31")));
+    }
+
+    #[test]
+    fn nth_weekday_applies_only_to_that_week() {
+        let mut second_tuesday = rule(RecurrencePattern::NthWeekday);
+        second_tuesday.weekdays = Some(vec![2]);
+        second_tuesday.nth_week = Some(2);
+        assert!(applies_to_date(&second_tuesday, date("2026-10-13")));
+        assert!(!applies_to_date(&second_tuesday, date("2026-10-06")));
+        assert!(!applies_to_date(&second_tuesday, date("2026-10-14")));
+    }
+}
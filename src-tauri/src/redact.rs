@@ -0,0 +1,104 @@
+//! Redactions for anything sent off the machine to diagnose a problem, such
+//! as crash reports: the home directory and user name, email addresses, and
+//! credentials in headers and URLs.
+
+/// URL parameters whose values are credentials.
+const SECRET_PARAMS: [&str; 7] = [
+    "token",
+    "access_token",
+    "refresh_token",
+    "key",
+    "api_key",
+    "password",
+    "secret",
+];
+
+/// Shortest user name replaced on its own; shorter ones would hit ordinary
+/// words.
+const MIN_USER_NAME_LEN: usize = 3;
+
+pub fn redact(text: &str) -> String {
+    let mut text = text.to_string();
+
+    if let Some(home) = env_var(if cfg!(windows) { "USERPROFILE" } else { "HOME" }) {
+        text = text.replace(&home, "~");
+    }
+    if let Some(user) = env_var(if cfg!(windows) { "USERNAME" } else { "USER" }) {
+        if user.len() >= MIN_USER_NAME_LEN {
+            text = text.replace(&user, "<user>");
+        }
+    }
+
+    let mut after_bearer = false;
+    text.split_inclusive(char::is_whitespace)
+        .map(|word| {
+            let trimmed = word.trim_end();
+            let rest = &word[trimmed.len()..];
+            let redacted = if after_bearer && !trimmed.is_empty() {
+                "<token>".to_string()
+            } else if trimmed.contains('@') && trimmed.contains('.') && !trimmed.contains("://") {
+                "<email>".to_string()
+            } else {
+                redact_params(trimmed)
+            };
+            if !trimmed.is_empty() {
+                after_bearer = trimmed.eq_ignore_ascii_case("bearer");
+            }
+            format!("{}{}", redacted, rest)
+        })
+        .collect()
+}
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// Blanks the values of `SECRET_PARAMS` in a URL or query string.
+fn redact_params(word: &str) -> String {
+    let Some((head, query)) = word.split_once('?') else {
+        return word.to_string();
+    };
+    let query = query
+        .split('&')
+        .map(|param| match param.split_once('=') {
+            Some((name, _)) if SECRET_PARAMS.contains(&name.to_ascii_lowercase().as_str()) => {
+                format!("{}=<redacted>", name)
+            }
+            _ => param.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    format!("{}?{}", head, query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_emails() {
+        assert_eq!(redact("sent to zoe@example.com today"), "sent to <email> today");
+    }
+
+    #[test]
+    fn redacts_bearer_tokens() {
+        assert_eq!(redact("Authorization: Bearer abc123\nnext"), "Authorization: Bearer <token>\nnext");
+    }
+
+    #[test]
+    fn redacts_credentials_in_urls() {
+        assert_eq!(
+            redact("GET https://user@api.example.com/v1?page=2&access_token=s3cret"),
+            "GET https://user@api.example.com/v1?page=2&access_token=<redacted>"
+        );
+    }
+
+    #[test]
+    fn redacts_the_home_directory() {
+        let Some(home) = env_var(if cfg!(windows) { "USERPROFILE" } else { "HOME" }) else {
+            return;
+        };
+        let redacted = redact(&format!("at {}/projects/afterglow/src/main.rs:10", home));
+        assert!(redacted.starts_with("at ~/projects/afterglow"), "{}", redacted);
+    }
+}
@@ -0,0 +1,483 @@
+//! Reminder notifications.
+//!
+//! Tasks can carry a `reminderAt` timestamp. A background thread fires a native
+//! notification once it passes. Snoozing and completing a reminder are handled
+//! here (from the tray menu or via commands) so acting on a reminder doesn't
+//! require opening the main window.
+//!
+//! Quiet hours (see `settings::QuietHours`) hold reminders back; whatever came
+//! due in the meantime is delivered as one batch when they end. Labels can be
+//! set to silence reminders entirely or to make them critical, which bypasses
+//! quiet hours.
+//!
+//! Labels and priorities can also set an urgency and a sound
+//! (`settings::NotificationStyle`). Desktop notifications only carry a sound,
+//! so that's what urgency maps to there: the system default for low and
+//! normal, louder system sounds for high and critical. Low is silent on
+//! mobile. Critical reminders also bypass quiet hours and go to the
+//! configured channels.
+//!
+//! With `settings::NagSettings` on, critical reminders repeat at shrinking
+//! intervals until the task is completed, snoozed or the reminder is
+//! acknowledged, up to a daily cap per task. Repeats don't resume after a
+//! restart.
+
+use chrono::{DateTime, Duration, Local, NaiveDateTime, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::announcements;
+use crate::calendar_feeds;
+use crate::clock;
+use crate::channels;
+use crate::metrics;
+use crate::notification_history::{self, NotificationHistory, ReminderAction};
+use crate::recurrence::parse_task_date;
+use crate::settings::{self, LabelNotificationMode, NotificationStyle, Urgency};
+use crate::tasks::{self, str_field, task_id};
+use crate::telemetry;
+use crate::tray;
+
+/// Upper bound on how long the scheduler sleeps between checks.
+const MAX_SLEEP: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Hour of day "Snooze until tomorrow" reminders come back at.
+const TOMORROW_HOUR: u32 = 9;
+
+/// How many titles a quiet-hours batch notification lists by name.
+const BATCH_PREVIEW_COUNT: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FiredReminder {
+    pub task_id: String,
+    pub title: String,
+    pub reminder_at: String,
+}
+
+impl FiredReminder {
+    pub fn key(&self) -> String {
+        format!("{}|{}", self.task_id, self.reminder_at)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum SnoozeOption {
+    #[serde(rename = "10m")]
+    TenMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "tomorrow")]
+    Tomorrow,
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    /// Keys (see `FiredReminder::key`) of reminders that already fired this session
+    fired: HashSet<String>,
+    /// The most recent reminder the user hasn't acted on yet
+    active: Option<FiredReminder>,
+    /// Reminders that came due during quiet hours, delivered when they end
+    deferred: Vec<FiredReminder>,
+    /// Critical reminders being repeated, keyed by task id
+    nags: HashMap<String, Nag>,
+    /// Set when tasks change so the scheduler re-reads them right away
+    dirty: bool,
+}
+
+struct Nag {
+    reminder: FiredReminder,
+    /// Time until the next repeat; halves each time
+    interval: Duration,
+    next_at: DateTime<Local>,
+}
+
+#[derive(Default)]
+pub struct ReminderScheduler {
+    state: Mutex<SchedulerState>,
+    wake: Condvar,
+}
+
+impl ReminderScheduler {
+    /// Wakes the scheduler so it picks up changed reminder times.
+    pub fn reschedule(&self) {
+        self.state.lock().unwrap().dirty = true;
+        self.wake.notify_one();
+    }
+
+    pub fn active_reminder(&self) -> Option<FiredReminder> {
+        self.state.lock().unwrap().active.clone()
+    }
+
+    /// Forgets a reminder the user acted on, so it neither stays active nor
+    /// repeats.
+    fn clear(&self, task_id: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.nags.remove(task_id);
+        if state.active.as_ref().is_some_and(|r| r.task_id == task_id) {
+            state.active = None;
+        }
+    }
+}
+
+/// Parses a `reminderAt` value: either an ISO timestamp with offset (what
+/// `Date.toISOString()` produces) or a local `yyyy-MM-ddTHH:mm[:ss]` time.
+pub fn parse_reminder_time(value: &str) -> Option<DateTime<Local>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Local));
+    }
+
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+}
+
+fn snooze_until(option: SnoozeOption, now: DateTime<Local>) -> DateTime<Local> {
+    match option {
+        SnoozeOption::TenMinutes => now + Duration::minutes(10),
+        SnoozeOption::OneHour => now + Duration::hours(1),
+        SnoozeOption::Tomorrow => {
+            let morning = NaiveTime::from_hms_opt(TOMORROW_HOUR, 0, 0).unwrap_or_default();
+            let tomorrow = (now.date_naive() + Duration::days(1)).and_time(morning);
+            Local
+                .from_local_datetime(&tomorrow)
+                .earliest()
+                .unwrap_or(now + Duration::days(1))
+        }
+    }
+}
+
+/// Starts the background thread that fires due reminders.
+pub fn start(app: AppHandle) {
+    // Reminders already shown in an earlier session shouldn't fire again
+    {
+        let fired = app
+            .state::<NotificationHistory>()
+            .records()
+            .iter()
+            .flat_map(|record| record.reminders.iter().map(FiredReminder::key))
+            .collect();
+        app.state::<ReminderScheduler>().state.lock().unwrap().fired = fired;
+    }
+
+    thread::spawn(move || loop {
+        let next_reminder = fire_due_reminders(&app);
+
+        let timeout = next_reminder
+            .and_then(|at| (at - clock::now()).to_std().ok())
+            .map_or(MAX_SLEEP, |until| until.min(MAX_SLEEP));
+
+        let scheduler = app.state::<ReminderScheduler>();
+        let state = scheduler.state.lock().unwrap();
+        let (mut state, _) = scheduler
+            .wake
+            .wait_timeout_while(state, timeout, |state| !state.dirty)
+            .unwrap();
+        state.dirty = false;
+    });
+}
+
+/// Fires notifications for reminders that are due and returns the time of
+/// the next upcoming one. During quiet hours (and out-of-office days from
+/// calendar feeds) due reminders are held back and delivered together once
+/// they end, unless they're critical.
+fn fire_due_reminders(app: &AppHandle) -> Option<DateTime<Local>> {
+    let data = crate::storage::read_task_data(app).ok()?;
+    let now = clock::now();
+    let notification_settings = settings::current(app).notifications;
+    let quiet = notification_settings.quiet_hours.is_quiet(now)
+        || calendar_feeds::is_out_of_office(app, now.date_naive());
+    let mut next_reminder: Option<DateTime<Local>> = None;
+    let mut due = Vec::new();
+    let mut styles: HashMap<String, NotificationStyle> = HashMap::new();
+
+    for task in &data.tasks {
+        if !tasks::is_open(task) {
+            continue;
+        }
+        let (Some(id), Some(reminder_at)) = (task_id(task), str_field(task, "reminderAt")) else {
+            continue;
+        };
+        let Some(at) = parse_reminder_time(reminder_at) else {
+            continue;
+        };
+
+        let mode = notification_settings.mode_for_labels(tasks::labels(task));
+        if mode == LabelNotificationMode::Silent {
+            continue;
+        }
+
+        if at > now {
+            next_reminder = Some(next_reminder.map_or(at, |next| next.min(at)));
+            continue;
+        }
+
+        let style = notification_settings.style_for(tasks::labels(task), str_field(task, "priority"));
+        styles.insert(id.to_string(), style);
+        due.push(FiredReminder {
+            task_id: id.to_string(),
+            title: str_field(task, "title").unwrap_or("Switchback").to_string(),
+            reminder_at: reminder_at.to_string(),
+        });
+    }
+
+    let due_keys: HashSet<_> = due.iter().map(FiredReminder::key).collect();
+    let is_critical = |id: &str| styles.get(id).is_some_and(|style| style.urgency == Urgency::Critical);
+
+    let scheduler = app.state::<ReminderScheduler>();
+    let to_show = {
+        let mut state = scheduler.state.lock().unwrap();
+        let mut new_reminders: Vec<_> = due
+            .into_iter()
+            .filter(|r| state.fired.insert(r.key()))
+            .collect();
+
+        if quiet {
+            let (to_show, mut held_back): (Vec<_>, Vec<_>) = new_reminders
+                .into_iter()
+                .partition(|r| is_critical(&r.task_id));
+            state.deferred.append(&mut held_back);
+            if let Some(latest) = to_show.last() {
+                state.active = Some(latest.clone());
+            }
+            to_show
+        } else {
+            // Drop deferred reminders that were completed or snoozed meanwhile
+            let mut to_show: Vec<_> = std::mem::take(&mut state.deferred)
+                .into_iter()
+                .filter(|r| due_keys.contains(&r.key()))
+                .collect();
+            to_show.append(&mut new_reminders);
+            if let Some(latest) = to_show.last() {
+                state.active = Some(latest.clone());
+            }
+            to_show
+        }
+    };
+
+    let next_nag = fire_nags(app, &due_keys, &styles, now);
+    let next_reminder = match (next_reminder, next_nag) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+
+    if to_show.is_empty() {
+        return next_reminder;
+    }
+
+    let nag = &notification_settings.nag;
+    if nag.enabled {
+        let interval = Duration::minutes(nag.first_interval_minutes.into());
+        let mut state = scheduler.state.lock().unwrap();
+        for reminder in to_show.iter().filter(|r| is_critical(&r.task_id)) {
+            state.nags.insert(
+                reminder.task_id.clone(),
+                Nag {
+                    reminder: reminder.clone(),
+                    interval,
+                    next_at: now + interval,
+                },
+            );
+        }
+    }
+
+    channels::forward_critical_reminders(
+        app,
+        to_show
+            .iter()
+            .filter(|r| is_critical(&r.task_id))
+            .cloned()
+            .collect(),
+    );
+
+    for reminder in &to_show {
+        let due = data
+            .tasks
+            .iter()
+            .find(|task| task_id(task) == Some(reminder.task_id.as_str()))
+            .and_then(|task| str_field(task, "dueDate"))
+            .and_then(parse_task_date);
+        announcements::reminder(app, &reminder.task_id, &reminder.title, due);
+    }
+
+    // A batch sounds like its most urgent reminder
+    let style = to_show
+        .iter()
+        .filter_map(|r| styles.get(&r.task_id))
+        .max_by_key(|style| style.urgency)
+        .cloned()
+        .unwrap_or_default();
+    if to_show.len() == 1 {
+        show_notification(app, &to_show[0], &style);
+    } else {
+        show_batch_notification(app, &to_show, &style);
+    }
+    tray::refresh(app);
+
+    next_reminder
+}
+
+/// Repeats the critical reminders whose next nag is due and returns when the
+/// next one is. `due_keys` are the reminders still due; the rest were
+/// completed or snoozed.
+fn fire_nags(
+    app: &AppHandle,
+    due_keys: &HashSet<String>,
+    styles: &HashMap<String, NotificationStyle>,
+    now: DateTime<Local>,
+) -> Option<DateTime<Local>> {
+    let settings = settings::current(app).notifications.nag;
+    let min_interval = Duration::minutes(settings.min_interval_minutes.into());
+    let scheduler = app.state::<ReminderScheduler>();
+
+    let repeats: Vec<FiredReminder> = {
+        let mut state = scheduler.state.lock().unwrap();
+        if !settings.enabled {
+            state.nags.clear();
+        }
+        state.nags.retain(|_, nag| due_keys.contains(&nag.reminder.key()));
+        state
+            .nags
+            .values_mut()
+            .filter(|nag| nag.next_at <= now)
+            .map(|nag| {
+                nag.interval = (nag.interval / 2).max(min_interval);
+                nag.next_at = now + nag.interval;
+                nag.reminder.clone()
+            })
+            .collect()
+    };
+
+    let midnight = now.date_naive().and_time(NaiveTime::MIN);
+    let today = Local.from_local_datetime(&midnight).earliest().unwrap_or(now);
+    for reminder in repeats {
+        let cap = settings.daily_cap as usize;
+        if notification_history::count_since(app, &reminder.task_id, today) >= cap {
+            scheduler.state.lock().unwrap().nags.remove(&reminder.task_id);
+            continue;
+        }
+        scheduler.state.lock().unwrap().active = Some(reminder.clone());
+        let style = styles.get(&reminder.task_id).cloned().unwrap_or_default();
+        notify(app, "Still waiting", &reminder.title, std::slice::from_ref(&reminder), &style);
+        tray::refresh(app);
+    }
+
+    let state = scheduler.state.lock().unwrap();
+    state.nags.values().map(|nag| nag.next_at).min()
+}
+
+fn show_notification(app: &AppHandle, reminder: &FiredReminder, style: &NotificationStyle) {
+    notify(app, "Afterglow reminder", &reminder.title, std::slice::from_ref(reminder), style);
+}
+
+/// One notification summarizing several reminders, e.g. those held back
+/// during quiet hours.
+fn show_batch_notification(app: &AppHandle, reminders: &[FiredReminder], style: &NotificationStyle) {
+    let mut lines: Vec<_> = reminders
+        .iter()
+        .take(BATCH_PREVIEW_COUNT)
+        .map(|r| r.title.clone())
+        .collect();
+    let more = reminders.len().saturating_sub(BATCH_PREVIEW_COUNT);
+    if more > 0 {
+        lines.push(format!("and {} more", more));
+    }
+
+    let title = format!("{} reminders", reminders.len());
+    notify(app, &title, &lines.join("\n"), reminders, style);
+}
+
+/// The system sound for an urgency; `None` leaves the platform default.
+fn urgency_sound(urgency: Urgency) -> Option<&'static str> {
+    match urgency {
+        Urgency::Low | Urgency::Normal => None,
+        Urgency::High if cfg!(target_os = "macos") => Some("Glass"),
+        Urgency::High if cfg!(windows) => Some("Reminder"),
+        Urgency::High => Some("message-new-instant"),
+        Urgency::Critical if cfg!(target_os = "macos") => Some("Sosumi"),
+        Urgency::Critical if cfg!(windows) => Some("Alarm"),
+        Urgency::Critical => Some("alarm-clock-elapsed"),
+    }
+}
+
+fn notify(app: &AppHandle, title: &str, body: &str, reminders: &[FiredReminder], style: &NotificationStyle) {
+    let mut builder = app.notification().builder().title(title).body(body);
+    if style.urgency == Urgency::Low {
+        builder = builder.silent();
+    } else if let Some(sound) = style.sound.as_deref().or(urgency_sound(style.urgency)) {
+        builder = builder.sound(sound);
+    }
+    if style.urgency == Urgency::Critical {
+        // Can't be swiped away, only tapped (mobile)
+        builder = builder.ongoing().auto_cancel();
+    }
+    builder.show().ok();
+
+    notification_history::record(app, title, body, reminders);
+    metrics::increment("notifications_total");
+}
+
+/// Pushes a task's reminder back and returns the new `reminderAt`.
+pub fn snooze(app: &AppHandle, task_id: &str, option: SnoozeOption) -> Result<String, String> {
+    let reminder_at = snooze_until(option, clock::now()).to_rfc3339();
+
+    tasks::modify_task_data(app, |data| {
+        let task = tasks::find_task_mut(data, task_id)
+            .ok_or_else(|| format!("Task not found: {}", task_id))?;
+        task["reminderAt"] = serde_json::json!(reminder_at);
+        Ok(())
+    })?;
+
+    telemetry::record(app, "reminder.snooze");
+    let action = ReminderAction::Snoozed { until: reminder_at.clone() };
+    after_reminder_action(app, task_id, action);
+    Ok(reminder_at)
+}
+
+/// Completes the task behind a reminder.
+pub fn complete(app: &AppHandle, task_id: &str) -> Result<(), String> {
+    tasks::modify_task_data(app, |data| tasks::complete_task(data, task_id))?;
+    telemetry::record(app, "reminder.complete");
+    after_reminder_action(app, task_id, ReminderAction::Completed);
+    Ok(())
+}
+
+/// Stops a critical reminder from repeating without touching the task.
+pub fn acknowledge(app: &AppHandle, task_id: &str) {
+    telemetry::record(app, "reminder.acknowledge");
+    after_reminder_action(app, task_id, ReminderAction::Acknowledged);
+}
+
+fn after_reminder_action(app: &AppHandle, task_id: &str, action: ReminderAction) {
+    notification_history::record_action(app, task_id, action);
+
+    let scheduler = app.state::<ReminderScheduler>();
+    scheduler.clear(task_id);
+    scheduler.reschedule();
+    tray::refresh(app);
+}
+
+#[tauri::command]
+pub fn snooze_reminder(app: AppHandle, task_id: String, option: SnoozeOption) -> Result<String, String> {
+    snooze(&app, &task_id, option)
+}
+
+#[tauri::command]
+pub fn complete_reminder_task(app: AppHandle, task_id: String) -> Result<(), String> {
+    complete(&app, &task_id)
+}
+
+#[tauri::command]
+pub fn acknowledge_reminder(app: AppHandle, task_id: String) {
+    acknowledge(&app, &task_id)
+}
+
+#[tauri::command]
+pub fn get_active_reminder(app: AppHandle) -> Option<FiredReminder> {
+    app.state::<ReminderScheduler>().active_reminder()
+}
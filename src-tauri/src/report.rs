@@ -0,0 +1,292 @@
+//! Plain-text reports built from the task data, shared by the channels that
+//! deliver them (email, chat channels, scheduled exports).
+
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::clock;
+use crate::goals::GoalProgress;
+use crate::locale::{DateStyle, Locale};
+use crate::periods;
+use crate::recurrence::parse_task_date;
+use crate::settings::WeekNumbering;
+use crate::storage::TaskData;
+use crate::tasks::{self, str_field};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Digest {
+    pub subject: String,
+    pub body: String,
+}
+
+fn priority_weight(task: &Value) -> u8 {
+    match str_field(task, "priority") {
+        Some("p0") => 0,
+        Some("p1") => 1,
+        Some("p3") => 3,
+        Some("p4") => 4,
+        _ => 2,
+    }
+}
+
+/// Highest priority first, then the user's manual order.
+pub fn sort_for_report(tasks: &mut [&Value]) {
+    tasks.sort_by(|a, b| {
+        priority_weight(a).cmp(&priority_weight(b)).then_with(|| {
+            let order = |t: &Value| t.get("sortOrder").and_then(Value::as_f64).unwrap_or(0.0);
+            order(a).total_cmp(&order(b))
+        })
+    });
+}
+
+fn task_line(task: &Value) -> String {
+    let priority = str_field(task, "priority").unwrap_or("p2").to_uppercase();
+    let title = str_field(task, "title").unwrap_or("Untitled");
+    format!("- [{}] {}", priority, title)
+}
+
+fn push_section(body: &mut String, heading: &str, lines: Vec<String>) {
+    if lines.is_empty() {
+        return;
+    }
+    body.push_str(&format!("{} ({})\n", heading, lines.len()));
+    for line in lines {
+        body.push_str(&line);
+        body.push('\n');
+    }
+    body.push('\n');
+}
+
+/// The morning digest: what's overdue, what's due today, and what's in progress.
+/// Dates and durations follow `locale`.
+pub fn morning_digest(data: &TaskData, today: NaiveDate, locale: &Locale) -> Digest {
+    let open: Vec<&Value> = data.tasks.iter().filter(|t| tasks::is_open(t)).collect();
+    let due_date = |task: &Value| str_field(task, "dueDate").and_then(parse_task_date);
+
+    let mut overdue: Vec<_> = open.iter().copied().filter(|t| due_date(t).is_some_and(|d| d < today)).collect();
+    let mut due_today: Vec<_> = open.iter().copied().filter(|t| due_date(t) == Some(today)).collect();
+    let mut in_progress: Vec<_> = open
+        .iter()
+        .copied()
+        .filter(|t| str_field(t, "status") == Some("in-progress"))
+        .filter(|t| due_date(t).is_none_or(|d| d > today))
+        .collect();
+
+    sort_for_report(&mut overdue);
+    sort_for_report(&mut due_today);
+    sort_for_report(&mut in_progress);
+
+    let planned_minutes: i64 = due_today
+        .iter()
+        .filter_map(|t| t.get("estimatedMinutes").and_then(Value::as_i64))
+        .sum();
+
+    let mut body = format!("Your agenda for {}.\n\n", locale.format_date(today, DateStyle::WeekdayDayMonth));
+
+    if overdue.is_empty() && due_today.is_empty() && in_progress.is_empty() {
+        body.push_str("Nothing due today. Enjoy the clear trail!\n");
+    }
+
+    push_section(
+        &mut body,
+        "Overdue",
+        overdue
+            .iter()
+            .map(|t| match due_date(t) {
+                Some(due) => format!("{} (due {})", task_line(t), locale.format_date(due, DateStyle::DayMonth)),
+                None => task_line(t),
+            })
+            .collect(),
+    );
+    push_section(&mut body, "Due today", due_today.iter().map(|t| task_line(t)).collect());
+    push_section(&mut body, "In progress", in_progress.iter().map(|t| task_line(t)).collect());
+
+    if planned_minutes > 0 {
+        body.push_str(&format!("Planned time today: {}\n", locale.format_duration(planned_minutes)));
+    }
+
+    let subject = format!(
+        "Afterglow agenda for {}: {} due, {} overdue",
+        locale.format_date(today, DateStyle::DayMonth),
+        due_today.len(),
+        overdue.len()
+    );
+
+    Digest { subject, body }
+}
+
+/// The weekly digest: what was done in the last seven days, what's due in the
+/// next seven, and how the goals are coming along. The subject names the
+/// week as `weeks` counts it.
+pub fn weekly_digest(
+    data: &TaskData,
+    goals: &[GoalProgress],
+    today: NaiveDate,
+    locale: &Locale,
+    weeks: WeekNumbering,
+) -> Digest {
+    let week_start = today - Duration::days(6);
+    let week_end = today + Duration::days(7);
+    let due_date = |task: &Value| str_field(task, "dueDate").and_then(parse_task_date);
+    let completed_on = |task: &Value| {
+        str_field(task, "completedAt")
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+            .map(|at| at.with_timezone(&Local).date_naive())
+    };
+
+    let mut done: Vec<&Value> = data
+        .tasks
+        .iter()
+        .filter(|t| tasks::is_done(t))
+        .filter(|t| completed_on(t).is_some_and(|d| d >= week_start && d <= today))
+        .collect();
+    let mut coming_up: Vec<&Value> = data
+        .tasks
+        .iter()
+        .filter(|t| tasks::is_open(t))
+        .filter(|t| due_date(t).is_some_and(|d| d > today && d <= week_end))
+        .collect();
+
+    sort_for_report(&mut done);
+    sort_for_report(&mut coming_up);
+
+    let done_minutes: i64 = done
+        .iter()
+        .filter_map(|t| t.get("estimatedMinutes").and_then(Value::as_i64))
+        .sum();
+
+    let mut body = format!(
+        "Your week from {} to {}.\n\n",
+        locale.format_date(week_start, DateStyle::DayMonth),
+        locale.format_date(today, DateStyle::DayMonth)
+    );
+
+    push_section(&mut body, "Done this week", done.iter().map(|t| task_line(t)).collect());
+    if done_minutes > 0 {
+        body.push_str(&format!("Time completed: {}\n\n", locale.format_duration(done_minutes)));
+    }
+    push_section(
+        &mut body,
+        "Coming up",
+        coming_up
+            .iter()
+            .map(|t| match due_date(t) {
+                Some(due) => format!("{} ({})", task_line(t), locale.format_date(due, DateStyle::DayMonth)),
+                None => task_line(t),
+            })
+            .collect(),
+    );
+    push_section(
+        &mut body,
+        "Goals",
+        goals
+            .iter()
+            .map(|goal| {
+                let mut line = format!(
+                    "- {}: {}%",
+                    goal.title,
+                    locale.format_number(goal.progress * 100.0, 0)
+                );
+                if let Some(target) = goal.target_date.as_deref().and_then(parse_task_date) {
+                    line.push_str(&format!(" by {}", locale.format_date(target, DateStyle::Medium)));
+                }
+                match goal.on_track {
+                    Some(true) => line.push_str(", on track"),
+                    Some(false) => line.push_str(", behind"),
+                    None => {}
+                }
+                line
+            })
+            .collect(),
+    );
+
+    let subject = format!(
+        "Afterglow week of {} ({}): {} done, {} coming up",
+        locale.format_date(week_start, DateStyle::DayMonth),
+        periods::format_week(week_start, weeks, false),
+        done.len(),
+        coming_up.len()
+    );
+
+    Digest { subject, body }
+}
+
+/// A Markdown snapshot for scheduled exports: what's overdue, due today, in
+/// progress and coming up this week, and what was done in the last seven days.
+pub fn markdown_report(data: &TaskData, today: NaiveDate, locale: &Locale, weeks: WeekNumbering) -> String {
+    let due_date = |task: &Value| str_field(task, "dueDate").and_then(parse_task_date);
+    let completed_on = |task: &Value| {
+        str_field(task, "completedAt")
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+            .map(|at| at.with_timezone(&Local).date_naive())
+    };
+    let open = || data.tasks.iter().filter(|t| tasks::is_open(t));
+
+    let mut sections: Vec<(&str, Vec<&Value>)> = vec![
+        ("Overdue", open().filter(|t| due_date(t).is_some_and(|d| d < today)).collect()),
+        ("Due today", open().filter(|t| due_date(t) == Some(today)).collect()),
+        (
+            "In progress",
+            open()
+                .filter(|t| str_field(t, "status") == Some("in-progress"))
+                .filter(|t| due_date(t).is_none_or(|d| d > today))
+                .collect(),
+        ),
+        (
+            "Coming up",
+            open()
+                .filter(|t| due_date(t).is_some_and(|d| d > today && d <= today + Duration::days(7)))
+                .collect(),
+        ),
+        (
+            "Done in the last 7 days",
+            data.tasks
+                .iter()
+                .filter(|t| tasks::is_done(t))
+                .filter(|t| completed_on(t).is_some_and(|d| d > today - Duration::days(7) && d <= today))
+                .collect(),
+        ),
+    ];
+
+    let mut report = format!(
+        "# Afterglow report for {}, {}\n\n",
+        locale.format_date(today, DateStyle::WeekdayDayMonth),
+        periods::format_week(today, weeks, false)
+    );
+    for (heading, section) in &mut sections {
+        if section.is_empty() {
+            continue;
+        }
+        sort_for_report(section);
+        report.push_str(&format!("## {} ({})\n\n", heading, section.len()));
+        for task in section.iter() {
+            let line = match due_date(task) {
+                Some(due) => format!("{} (due {})", task_line(task), locale.format_date(due, DateStyle::DayMonth)),
+                None => task_line(task),
+            };
+            report.push_str(&line);
+            report.push('\n');
+        }
+        report.push('\n');
+    }
+    report
+}
+
+#[tauri::command]
+pub fn preview_morning_digest(app: tauri::AppHandle) -> Result<Digest, String> {
+    crate::telemetry::record(&app, "digest.preview");
+    let data = crate::storage::read_task_data(&app)?;
+    let locale = crate::locale::current(&app);
+    Ok(morning_digest(&data, clock::today(), locale))
+}
+
+#[tauri::command]
+pub fn preview_weekly_digest(app: tauri::AppHandle) -> Result<Digest, String> {
+    let data = crate::storage::read_task_data(&app)?;
+    let today = clock::today();
+    let goals = crate::goals::active_progress(&app, &data, today);
+    let weeks = crate::settings::current(&app).regional.week_numbering;
+    Ok(weekly_digest(&data, &goals, today, crate::locale::current(&app), weeks))
+}
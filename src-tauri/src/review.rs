@@ -0,0 +1,157 @@
+//! The review queue: someday tasks, and open tasks flagged `review: true`,
+//! come back for a look at growing intervals so nothing in the backlog is
+//! forgotten for good. Each review that keeps a task as it is doubles the
+//! wait before the next one; asking to see it again soon starts over.
+//! Schedules are kept in review.json, keyed by task id.
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::clock;
+use crate::disk;
+use crate::recurrence::{format_task_date, parse_task_date};
+use crate::storage;
+use crate::tasks::{self, now_iso, str_field, task_id};
+
+const FIRST_INTERVAL_DAYS: i64 = 7;
+const MAX_INTERVAL_DAYS: i64 = 180;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewSchedule {
+    /// Next review, as `yyyy-MM-dd`
+    pub due: String,
+    pub interval_days: i64,
+    pub reviews: u32,
+    pub last_reviewed_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReviewOutcome {
+    /// Still belongs in the backlog; wait longer next time
+    Keep,
+    /// Bring it back after the first interval again
+    Soon,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewItem {
+    pub task: Value,
+    #[serde(flatten)]
+    pub schedule: ReviewSchedule,
+}
+
+#[derive(Default)]
+pub struct ReviewStore(Mutex<BTreeMap<String, ReviewSchedule>>);
+
+fn get_review_path(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("review.json")
+}
+
+pub fn load(app: &AppHandle) -> Result<(), String> {
+    let path = get_review_path(app);
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read review schedules: {}", e))?;
+
+    let schedules: BTreeMap<String, ReviewSchedule> = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse review schedules: {}", e))?;
+
+    *app.state::<ReviewStore>().0.lock().unwrap() = schedules;
+    Ok(())
+}
+
+fn save(app: &AppHandle, schedules: &BTreeMap<String, ReviewSchedule>) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(schedules)
+        .map_err(|e| format!("Failed to serialize review schedules: {}", e))?;
+    disk::write_atomic(&get_review_path(app), content.as_bytes())
+        .map_err(|e| format!("Failed to save review schedules: {}", e))
+}
+
+fn in_review(task: &Value) -> bool {
+    tasks::is_open(task)
+        && (str_field(task, "status") == Some("someday")
+            || task.get("review").and_then(Value::as_bool).unwrap_or(false))
+}
+
+/// A task seen for the first time comes up one interval after it was created.
+fn first_schedule(task: &Value, today: NaiveDate) -> ReviewSchedule {
+    let created = str_field(task, "createdAt").and_then(parse_task_date).unwrap_or(today);
+    ReviewSchedule {
+        due: format_task_date(created + Duration::days(FIRST_INTERVAL_DAYS)),
+        interval_days: FIRST_INTERVAL_DAYS,
+        reviews: 0,
+        last_reviewed_at: None,
+    }
+}
+
+/// Tasks due for review, longest overdue first. Schedules of tasks that left
+/// the queue are dropped.
+#[tauri::command]
+pub fn get_review_queue(app: AppHandle) -> Result<Vec<ReviewItem>, String> {
+    let data = storage::read_task_data(&app)?;
+    let today = clock::today();
+    let store = app.state::<ReviewStore>();
+    let mut schedules = store.0.lock().unwrap();
+
+    let reviewable: Vec<&Value> = data.tasks.iter().filter(|t| in_review(t)).collect();
+    let before = schedules.len();
+    schedules.retain(|id, _| reviewable.iter().any(|t| task_id(t) == Some(id.as_str())));
+    if schedules.len() != before {
+        save(&app, &schedules)?;
+    }
+
+    let mut queue: Vec<ReviewItem> = reviewable
+        .into_iter()
+        .filter_map(|task| {
+            let schedule = task_id(task)
+                .and_then(|id| schedules.get(id).cloned())
+                .unwrap_or_else(|| first_schedule(task, today));
+            let due = parse_task_date(&schedule.due).is_none_or(|due| due <= today);
+            due.then(|| ReviewItem { task: task.clone(), schedule })
+        })
+        .collect();
+    queue.sort_by(|a, b| a.schedule.due.cmp(&b.schedule.due));
+    Ok(queue)
+}
+
+/// Records a review and returns when the task comes back.
+#[tauri::command]
+pub fn review_task(app: AppHandle, task_id: String, outcome: ReviewOutcome) -> Result<ReviewSchedule, String> {
+    let data = storage::read_task_data(&app)?;
+    let task = data
+        .tasks
+        .iter()
+        .find(|t| tasks::task_id(t) == Some(task_id.as_str()))
+        .ok_or_else(|| format!("Task not found: {}", task_id))?;
+    let today = clock::today();
+
+    let store = app.state::<ReviewStore>();
+    let mut schedules = store.0.lock().unwrap();
+    let previous = schedules.get(&task_id).cloned().unwrap_or_else(|| first_schedule(task, today));
+    let interval_days = match outcome {
+        ReviewOutcome::Keep => (previous.interval_days * 2).min(MAX_INTERVAL_DAYS),
+        ReviewOutcome::Soon => FIRST_INTERVAL_DAYS,
+    };
+    let schedule = ReviewSchedule {
+        due: format_task_date(today + Duration::days(interval_days)),
+        interval_days,
+        reviews: previous.reviews + 1,
+        last_reviewed_at: Some(now_iso()),
+    };
+    schedules.insert(task_id, schedule.clone());
+    save(&app, &schedules)?;
+    Ok(schedule)
+}
@@ -0,0 +1,141 @@
+//! Rolling overdue switchbacks forward to today, or the next working day
+//! (per the task's project) when today isn't one. Recurring tasks keep the
+//! dates their rule gives them, and tasks with the exclusion label (`fixed`
+//! by default) stay put. A task never lands before an open task it depends
+//! on is due.
+//!
+//! Runs on request, or each morning when `rollover.auto` is on.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::clock;
+use crate::daily;
+use crate::power;
+use crate::projects;
+use crate::recurrence::{format_task_date, parse_task_date};
+use crate::reminders::ReminderScheduler;
+use crate::scoring::depends_on;
+use crate::settings::{self, Settings};
+use crate::storage::TaskData;
+use crate::tasks::{self, str_field, task_id};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RolledTask {
+    pub id: String,
+    pub title: String,
+    pub from: String,
+    pub to: String,
+}
+
+fn target_day(settings: &Settings, task: &Value, today: NaiveDate) -> NaiveDate {
+    if !settings.rollover.working_days_only {
+        return today;
+    }
+    let working_days = projects::effective(settings, str_field(task, "project")).working_days;
+    projects::working_day_on_or_after(today, &working_days)
+}
+
+fn roll_forward(data: &mut TaskData, settings: &Settings, today: NaiveDate) -> Vec<RolledTask> {
+    let exclude = settings.rollover.exclude_label.trim();
+    let due_date = |task: &Value| str_field(task, "dueDate").and_then(parse_task_date);
+
+    let mut new_dates: HashMap<String, NaiveDate> = data
+        .tasks
+        .iter()
+        .filter(|t| tasks::is_open(t) && str_field(t, "type") != Some("recurring"))
+        .filter(|t| str_field(t, "status") != Some("someday"))
+        .filter(|t| exclude.is_empty() || !tasks::labels(t).any(|label| label == exclude))
+        .filter(|t| due_date(t).is_some_and(|due| due < today))
+        .filter_map(|t| Some((task_id(t)?.to_string(), target_day(settings, t, today))))
+        .collect();
+
+    // Push dependents past their open dependencies; bounded in case of cycles
+    let open_due: HashMap<&str, NaiveDate> = data
+        .tasks
+        .iter()
+        .filter(|t| tasks::is_open(t))
+        .filter_map(|t| Some((task_id(t)?, due_date(t)?)))
+        .collect();
+    for _ in 0..data.tasks.len() {
+        let mut changed = false;
+        for task in &data.tasks {
+            let Some(id) = task_id(task) else { continue };
+            let Some(&current) = new_dates.get(id) else { continue };
+            let latest_dependency = depends_on(task)
+                .filter_map(|dep| new_dates.get(dep).or(open_due.get(dep)).copied())
+                .max();
+            if let Some(latest) = latest_dependency.filter(|latest| *latest > current) {
+                new_dates.insert(id.to_string(), latest);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let mut rolled = Vec::new();
+    for task in data.tasks.iter_mut() {
+        let Some(to) = task_id(task).and_then(|id| new_dates.get(id)).copied() else {
+            continue;
+        };
+        let from = str_field(task, "dueDate").unwrap_or_default().to_string();
+        task["dueDate"] = json!(format_task_date(to));
+        rolled.push(RolledTask {
+            id: task_id(task).unwrap_or_default().to_string(),
+            title: str_field(task, "title").unwrap_or("Untitled").to_string(),
+            from,
+            to: format_task_date(to),
+        });
+    }
+    rolled
+}
+
+fn roll_over(app: &AppHandle) -> Result<Vec<RolledTask>, String> {
+    let settings = settings::current(app);
+    let today = clock::today();
+    let rolled = tasks::modify_task_data(app, |data| Ok(roll_forward(data, &settings, today)))?;
+    app.state::<ReminderScheduler>().reschedule();
+    Ok(rolled)
+}
+
+pub fn start(app: AppHandle) {
+    thread::spawn(move || loop {
+        roll_over_if_due(&app);
+        power::sleep(&app, CHECK_INTERVAL);
+    });
+}
+
+fn roll_over_if_due(app: &AppHandle) {
+    let config = settings::current(app).rollover;
+    if !config.auto || !daily::claim_run(app, "rollover", &config.run_at) {
+        return;
+    }
+
+    let (title, body) = match roll_over(app) {
+        Ok(rolled) if rolled.is_empty() => return,
+        Ok(rolled) => (
+            format!("Moved {} overdue switchback{}", rolled.len(), if rolled.len() == 1 { "" } else { "s" }),
+            rolled.iter().map(|task| task.title.as_str()).take(5).collect::<Vec<_>>().join("\n"),
+        ),
+        Err(e) => ("Couldn't move overdue switchbacks".to_string(), e),
+    };
+    app.notification().builder().title(title).body(body).show().ok();
+}
+
+/// Moves every overdue switchback that isn't fixed forward and returns what
+/// moved.
+#[tauri::command]
+pub fn roll_over_overdue(app: AppHandle) -> Result<Vec<RolledTask>, String> {
+    roll_over(&app)
+}
@@ -0,0 +1,130 @@
+//! Log of every change automation rules made to a task, persisted as
+//! rule_audit.json, so a surprising priority or status can be traced back to
+//! the rule that set it.
+
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::disk;
+use crate::log;
+use crate::recurrence::parse_task_date;
+use crate::rules::RuleChange;
+use crate::storage;
+use crate::tasks::now_iso;
+
+/// Oldest entries are dropped beyond this many.
+const MAX_AUDIT_ENTRIES: usize = 5000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleAuditEntry {
+    pub at: String,
+    pub rule_id: String,
+    pub rule_name: String,
+    pub task_id: String,
+    pub title: String,
+    pub field: String,
+    pub from: Value,
+    pub to: Value,
+}
+
+#[derive(Default)]
+pub struct RuleAudit(Mutex<Vec<RuleAuditEntry>>);
+
+fn get_audit_path(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("rule_audit.json")
+}
+
+pub fn load(app: &AppHandle) -> Result<(), String> {
+    let path = get_audit_path(app);
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read rule audit log: {}", e))?;
+
+    let entries: Vec<RuleAuditEntry> = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse rule audit log: {}", e))?;
+
+    *app.state::<RuleAudit>().0.lock().unwrap() = entries;
+    Ok(())
+}
+
+/// Logs changes rules made; `rule_name` looks up each change's rule name.
+pub fn record(app: &AppHandle, changes: &[RuleChange], rule_name: impl Fn(&str) -> String) {
+    if changes.is_empty() {
+        return;
+    }
+    let audit = app.state::<RuleAudit>();
+    let mut entries = audit.0.lock().unwrap();
+
+    let at = now_iso();
+    entries.extend(changes.iter().map(|change| RuleAuditEntry {
+        at: at.clone(),
+        rule_id: change.rule_id.clone(),
+        rule_name: rule_name(&change.rule_id),
+        task_id: change.task_id.clone(),
+        title: change.title.clone(),
+        field: change.field.clone(),
+        from: change.from.clone(),
+        to: change.to.clone(),
+    }));
+
+    let overflow = entries.len().saturating_sub(MAX_AUDIT_ENTRIES);
+    entries.drain(..overflow);
+
+    let saved = serde_json::to_string_pretty(&*entries)
+        .map_err(|e| e.to_string())
+        .and_then(|content| {
+            disk::write_atomic(&get_audit_path(app), content.as_bytes()).map_err(|e| e.to_string())
+        });
+    if let Err(e) = saved {
+        log::error(app, format!("Failed to save rule audit log: {}", e));
+    }
+}
+
+fn local_date(timestamp: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|at| at.with_timezone(&Local).date_naive())
+}
+
+/// Changes made between `start` and `end` (inclusive local dates, either
+/// side open when missing), newest first, optionally for one rule or task.
+#[tauri::command]
+pub fn get_rule_audit(
+    app: AppHandle,
+    start: Option<String>,
+    end: Option<String>,
+    rule_id: Option<String>,
+    task_id: Option<String>,
+) -> Result<Vec<RuleAuditEntry>, String> {
+    let parse = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(|v| parse_task_date(v).ok_or_else(|| format!("Invalid date \"{}\"", v)))
+            .transpose()
+    };
+    let start = parse(&start)?;
+    let end = parse(&end)?;
+
+    let entries = app.state::<RuleAudit>().0.lock().unwrap().clone();
+    Ok(entries
+        .into_iter()
+        .rev()
+        .filter(|entry| {
+            let day = local_date(&entry.at);
+            start.is_none_or(|start| day.is_some_and(|day| day >= start))
+                && end.is_none_or(|end| day.is_some_and(|day| day <= end))
+        })
+        .filter(|entry| rule_id.as_ref().is_none_or(|id| &entry.rule_id == id))
+        .filter(|entry| task_id.as_ref().is_none_or(|id| &entry.task_id == id))
+        .collect())
+}
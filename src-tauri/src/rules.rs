@@ -0,0 +1,329 @@
+//! Automation rules, persisted in rules.json. A rule matches open
+//! switchbacks by status, priority, labels and due date, and changes them
+//! (bump the priority of anything overdue, label what's due this week). A
+//! background pass applies every enabled rule; `simulate_rule` evaluates a
+//! rule, saved or not, against the current tasks and returns what it would
+//! change without writing anything. Every change the pass makes is logged in
+//! `rule_audit`.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::clock;
+use crate::disk;
+use crate::log;
+use crate::power;
+use crate::recurrence::parse_task_date;
+use crate::rule_audit;
+use crate::storage::{self, TaskData};
+use crate::tasks::{self, now_iso, str_field, task_id};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+const PRIORITIES: [&str; 5] = ["p0", "p1", "p2", "p3", "p4"];
+
+const STATUSES: [&str; 7] = [
+    "not-started",
+    "in-progress",
+    "waiting",
+    "needs-review",
+    "blocked",
+    "someday",
+    "done",
+];
+
+/// Which open tasks a rule applies to; every condition that is set must hold.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RuleConditions {
+    /// Any of these statuses
+    pub statuses: Vec<String>,
+    /// Any of these priorities
+    pub priorities: Vec<String>,
+    /// Any of these labels
+    pub labels: Vec<String>,
+    /// Due at least this many days ago
+    pub overdue_days: Option<i64>,
+    /// Due within this many days from today (overdue counts too)
+    pub due_within_days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RuleAction {
+    SetPriority { priority: String },
+    SetStatus { status: String },
+    AddLabel { label: String },
+    RemoveLabel { label: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub conditions: RuleConditions,
+    pub actions: Vec<RuleAction>,
+    #[serde(default)]
+    pub created_at: String,
+}
+
+/// One field a rule changes (or would change) on one task.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleChange {
+    pub rule_id: String,
+    pub task_id: String,
+    pub title: String,
+    pub field: String,
+    pub from: Value,
+    pub to: Value,
+}
+
+#[derive(Default)]
+pub struct RulesStore(Mutex<Vec<Rule>>);
+
+fn get_rules_path(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("rules.json")
+}
+
+pub fn load(app: &AppHandle) -> Result<(), String> {
+    let path = get_rules_path(app);
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read rules: {}", e))?;
+
+    let rules: Vec<Rule> =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse rules: {}", e))?;
+
+    *app.state::<RulesStore>().0.lock().unwrap() = rules;
+    Ok(())
+}
+
+fn save(app: &AppHandle, rules: &[Rule]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(rules)
+        .map_err(|e| format!("Failed to serialize rules: {}", e))?;
+    disk::write_atomic(&get_rules_path(app), content.as_bytes())
+        .map_err(|e| format!("Failed to save rules: {}", e))
+}
+
+fn matches(conditions: &RuleConditions, task: &Value, today: NaiveDate) -> bool {
+    let any_of = |wanted: &[String], value: Option<&str>| {
+        wanted.is_empty() || value.is_some_and(|v| wanted.iter().any(|w| w == v))
+    };
+    let due_in = str_field(task, "dueDate")
+        .and_then(parse_task_date)
+        .map(|due| (due - today).num_days());
+
+    tasks::is_open(task)
+        && any_of(&conditions.statuses, str_field(task, "status"))
+        && any_of(&conditions.priorities, str_field(task, "priority"))
+        && (conditions.labels.is_empty()
+            || tasks::labels(task).any(|label| conditions.labels.iter().any(|l| l == label)))
+        && conditions
+            .overdue_days
+            .is_none_or(|days| due_in.is_some_and(|due_in| -due_in >= days))
+        && conditions
+            .due_within_days
+            .is_none_or(|days| due_in.is_some_and(|due_in| due_in <= days))
+}
+
+/// The (field, from, to) changes an action makes to a task, if any.
+fn action_change(action: &RuleAction, task: &Value) -> Option<(&'static str, Value, Value)> {
+    let current = |field: &str| task.get(field).cloned().unwrap_or(Value::Null);
+    let labels: Vec<&str> = tasks::labels(task).collect();
+
+    match action {
+        RuleAction::SetPriority { priority } => (str_field(task, "priority") != Some(priority))
+            .then(|| ("priority", current("priority"), json!(priority))),
+        RuleAction::SetStatus { status } => (str_field(task, "status") != Some(status))
+            .then(|| ("status", current("status"), json!(status))),
+        RuleAction::AddLabel { label } => (!labels.contains(&label.as_str())).then(|| {
+            let mut next = labels.clone();
+            next.push(label);
+            ("labels", current("labels"), json!(next))
+        }),
+        RuleAction::RemoveLabel { label } => labels.contains(&label.as_str()).then(|| {
+            let next: Vec<&str> = labels.iter().copied().filter(|l| l != label).collect();
+            ("labels", current("labels"), json!(next))
+        }),
+    }
+}
+
+/// Everything `rule` would change in `data`, applying its actions in order.
+fn evaluate(rule: &Rule, data: &TaskData, today: NaiveDate) -> Vec<RuleChange> {
+    let mut changes = Vec::new();
+    for task in data.tasks.iter().filter(|task| matches(&rule.conditions, task, today)) {
+        let Some(id) = task_id(task) else {
+            continue;
+        };
+        // Later actions see the earlier ones, e.g. two labels added in a row
+        let mut task = task.clone();
+        for action in &rule.actions {
+            if let Some((field, from, to)) = action_change(action, &task) {
+                task[field] = to.clone();
+                changes.push(RuleChange {
+                    rule_id: rule.id.clone(),
+                    task_id: id.to_string(),
+                    title: str_field(&task, "title").unwrap_or_default().to_string(),
+                    field: field.to_string(),
+                    from,
+                    to,
+                });
+            }
+        }
+    }
+    changes
+}
+
+fn apply(data: &mut TaskData, changes: &[RuleChange]) -> Result<(), String> {
+    for change in changes {
+        if change.field == "status" && change.to == "done" {
+            tasks::complete_task(data, &change.task_id)?;
+            continue;
+        }
+        if let Some(task) = tasks::find_task_mut(data, &change.task_id) {
+            task[change.field.as_str()] = change.to.clone();
+        }
+    }
+    Ok(())
+}
+
+/// Applies every enabled rule, returning the changes made.
+fn run_enabled(app: &AppHandle) -> Result<Vec<RuleChange>, String> {
+    let rules: Vec<Rule> = app
+        .state::<RulesStore>()
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|rule| rule.enabled)
+        .cloned()
+        .collect();
+    if rules.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let today = clock::today();
+    let changes = storage::update_task_data(app, |data| {
+        let mut changes = Vec::new();
+        // Each rule sees what the rules before it changed
+        for rule in &rules {
+            let rule_changes = evaluate(rule, data, today);
+            apply(data, &rule_changes)?;
+            changes.extend(rule_changes);
+        }
+        Ok(changes)
+    })?;
+    if !changes.is_empty() {
+        rule_audit::record(app, &changes, |id| {
+            let rule = rules.iter().find(|rule| rule.id == id);
+            rule.map(|rule| rule.name.clone()).unwrap_or_default()
+        });
+        tasks::notify_changed(app, None);
+    }
+    Ok(changes)
+}
+
+pub fn start(app: AppHandle) {
+    thread::spawn(move || loop {
+        if let Err(e) = run_enabled(&app) {
+            log::error(&app, format!("Failed to run automation rules: {}", e));
+        }
+        power::sleep(&app, CHECK_INTERVAL);
+    });
+}
+
+fn validate(rule: &Rule) -> Result<(), String> {
+    if rule.name.trim().is_empty() {
+        return Err("A rule needs a name".to_string());
+    }
+    if rule.actions.is_empty() {
+        return Err("A rule needs at least one action".to_string());
+    }
+    let conditions = &rule.conditions;
+    if let Some(status) = conditions.statuses.iter().find(|s| !STATUSES.contains(&s.as_str())) {
+        return Err(format!("Unknown status \"{}\"", status));
+    }
+    if let Some(priority) = conditions.priorities.iter().find(|p| !PRIORITIES.contains(&p.as_str())) {
+        return Err(format!("Unknown priority \"{}\"", priority));
+    }
+    for action in &rule.actions {
+        match action {
+            RuleAction::SetPriority { priority } if !PRIORITIES.contains(&priority.as_str()) => {
+                return Err(format!("Unknown priority \"{}\"", priority));
+            }
+            RuleAction::SetStatus { status } if !STATUSES.contains(&status.as_str()) => {
+                return Err(format!("Unknown status \"{}\"", status));
+            }
+            RuleAction::AddLabel { label } | RuleAction::RemoveLabel { label } if label.trim().is_empty() => {
+                return Err("Label actions need a label".to_string());
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_rules(app: AppHandle) -> Vec<Rule> {
+    app.state::<RulesStore>().0.lock().unwrap().clone()
+}
+
+/// Creates the rule, or replaces the one with the same id.
+#[tauri::command]
+pub fn save_rule(app: AppHandle, mut rule: Rule) -> Result<Rule, String> {
+    validate(&rule)?;
+    if rule.id.is_empty() {
+        rule.id = uuid::Uuid::new_v4().to_string();
+    }
+    if rule.created_at.is_empty() {
+        rule.created_at = now_iso();
+    }
+
+    let store = app.state::<RulesStore>();
+    let mut rules = store.0.lock().unwrap();
+    match rules.iter_mut().find(|r| r.id == rule.id) {
+        Some(existing) => *existing = rule.clone(),
+        None => rules.push(rule.clone()),
+    }
+    save(&app, &rules)?;
+
+    Ok(rule)
+}
+
+#[tauri::command]
+pub fn delete_rule(app: AppHandle, id: String) -> Result<(), String> {
+    let store = app.state::<RulesStore>();
+    let mut rules = store.0.lock().unwrap();
+    let before = rules.len();
+    rules.retain(|r| r.id != id);
+    if rules.len() == before {
+        return Err(format!("Rule not found: {}", id));
+    }
+    save(&app, &rules)
+}
+
+/// What the rule would change right now, whether or not it's enabled or
+/// saved. Nothing is written.
+#[tauri::command]
+pub fn simulate_rule(app: AppHandle, rule: Rule) -> Result<Vec<RuleChange>, String> {
+    validate(&rule)?;
+    let data = storage::read_task_data(&app)?;
+    Ok(evaluate(&rule, &data, clock::today()))
+}
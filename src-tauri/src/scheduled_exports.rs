@@ -0,0 +1,141 @@
+//! Exports configured in settings (a nightly Markdown report, a weekly CSV)
+//! that run on their own. A background thread checks the schedules each
+//! minute and queues due exports on the job system; a failed export shows a
+//! notification.
+
+use chrono::Datelike;
+use serde_json::Value;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::clock;
+use crate::daily;
+use crate::disk;
+use crate::jobs::{self, JobContext};
+use crate::locale;
+use crate::power;
+use crate::report;
+use crate::settings::{self, ExportFormat, ExportFrequency, ScheduledExport};
+use crate::storage::{self, TaskData};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+const CSV_COLUMNS: [&str; 11] = [
+    "id",
+    "title",
+    "type",
+    "status",
+    "priority",
+    "dueDate",
+    "labels",
+    "stakeholders",
+    "estimatedMinutes",
+    "createdAt",
+    "completedAt",
+];
+
+pub fn start(app: AppHandle) {
+    thread::spawn(move || loop {
+        run_due_exports(&app);
+        power::sleep(&app, CHECK_INTERVAL);
+    });
+}
+
+fn run_due_exports(app: &AppHandle) {
+    let today = clock::now().weekday().num_days_from_sunday();
+    for export in settings::current(app).exports {
+        let scheduled_today = export.frequency == ExportFrequency::Daily || export.weekday == today;
+        let job = format!("export-{}", export.name);
+        if export.enabled && scheduled_today && daily::claim_run(app, &job, &export.at) {
+            queue(app, export);
+        }
+    }
+}
+
+fn queue(app: &AppHandle, export: ScheduledExport) -> String {
+    jobs::spawn(app, "scheduled-export", move |job: &JobContext| {
+        let result = write_export(&job.app, &export);
+        if let Err(e) = &result {
+            job.app
+                .notification()
+                .builder()
+                .title(format!("Couldn't run the \"{}\" export", export.name))
+                .body(e)
+                .show()
+                .ok();
+        }
+        result
+    })
+}
+
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn to_csv(data: &TaskData) -> String {
+    let mut csv = CSV_COLUMNS.join(",");
+    csv.push('\n');
+    for task in &data.tasks {
+        let row: Vec<String> = CSV_COLUMNS
+            .iter()
+            .map(|column| match task.get(*column) {
+                Some(Value::String(value)) => csv_field(value),
+                Some(Value::Array(values)) => {
+                    let joined: Vec<&str> = values.iter().filter_map(Value::as_str).collect();
+                    csv_field(&joined.join("; "))
+                }
+                Some(Value::Number(value)) => value.to_string(),
+                _ => String::new(),
+            })
+            .collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Writes the export into its folder and returns the file's path.
+fn write_export(app: &AppHandle, export: &ScheduledExport) -> Result<String, String> {
+    let data = storage::read_task_data(app)?;
+    let today = clock::today();
+
+    let (content, extension) = match export.format {
+        ExportFormat::Json => (
+            serde_json::to_string_pretty(&data).map_err(|e| format!("Failed to serialize tasks: {}", e))?,
+            "json",
+        ),
+        ExportFormat::Csv => (to_csv(&data), "csv"),
+        ExportFormat::Markdown => {
+            let weeks = settings::current(app).regional.week_numbering;
+            (report::markdown_report(&data, today, locale::current(app), weeks), "md")
+        }
+    };
+
+    let folder = Path::new(&export.folder);
+    if !folder.is_dir() {
+        return Err(format!("Export folder not found: {}", export.folder));
+    }
+    let path = folder.join(format!("{}_{}.{}", export.name.trim(), today.format("%Y%m%d"), extension));
+    disk::write_atomic(&path, content.as_bytes())
+        .map_err(|e| disk::describe_io_error("Failed to write export", &e))?;
+
+    Ok(path.display().to_string())
+}
+
+/// Runs a scheduled export right away and returns the job id.
+#[tauri::command]
+pub fn run_scheduled_export(app: AppHandle, name: String) -> Result<String, String> {
+    let export = settings::current(&app)
+        .exports
+        .into_iter()
+        .find(|export| export.name == name)
+        .ok_or_else(|| format!("Scheduled export not found: {}", name))?;
+    Ok(queue(&app, export))
+}
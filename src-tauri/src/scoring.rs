@@ -0,0 +1,161 @@
+//! "What should I do next?": ranks open switchbacks by a weighted score of
+//! due proximity, priority, stakeholder weight, age and how many other tasks
+//! are waiting on them. Weights come from `settings.scoring`.
+//!
+//! Dependencies are read from an optional `dependsOn` list of task ids.
+//! Tasks that are blocked, or still wait on an open dependency, rank after
+//! everything that can be started now.
+
+use chrono::{DateTime, Local, NaiveDate};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+use crate::clock;
+use crate::recurrence::parse_task_date;
+use crate::settings::{self, ScoringSettings};
+use crate::storage;
+use crate::tasks::{self, str_field, task_id};
+
+/// Age stops adding to the score after this many days.
+const MAX_AGE_DAYS: f64 = 30.0;
+
+/// Unblocking this many tasks earns the full bonus.
+const FULL_UNBLOCKS: f64 = 3.0;
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreBreakdown {
+    pub due: f64,
+    pub priority: f64,
+    pub stakeholder: f64,
+    pub age: f64,
+    pub unblocks: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoredTask {
+    pub id: String,
+    pub title: String,
+    pub score: f64,
+    /// Each factor before weighting, 0-1
+    pub breakdown: ScoreBreakdown,
+    /// Blocked, or waiting on an open dependency
+    pub blocked: bool,
+}
+
+pub fn depends_on(task: &Value) -> impl Iterator<Item = &str> {
+    task.get("dependsOn")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+}
+
+fn priority_factor(task: &Value) -> f64 {
+    match str_field(task, "priority") {
+        Some("p0") => 1.0,
+        Some("p1") => 0.75,
+        Some("p3") => 0.25,
+        Some("p4") => 0.0,
+        _ => 0.5,
+    }
+}
+
+fn due_factor(task: &Value, today: NaiveDate, horizon_days: u32) -> f64 {
+    let Some(due) = str_field(task, "dueDate").and_then(parse_task_date) else {
+        return 0.0;
+    };
+    let days = (due - today).num_days();
+    if days <= 0 {
+        1.0
+    } else {
+        (1.0 - days as f64 / horizon_days as f64).max(0.0)
+    }
+}
+
+fn age_factor(task: &Value, today: NaiveDate) -> f64 {
+    let created = str_field(task, "createdAt")
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        .map(|at| at.with_timezone(&Local).date_naive());
+    created.map_or(0.0, |created| {
+        ((today - created).num_days().max(0) as f64 / MAX_AGE_DAYS).min(1.0)
+    })
+}
+
+fn stakeholder_factor(task: &Value, config: &ScoringSettings) -> f64 {
+    task.get("stakeholders")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .filter_map(|name| config.stakeholders.get(name).copied())
+        .fold(0.0, f64::max)
+}
+
+pub fn score(tasks: &[Value], config: &ScoringSettings, today: NaiveDate) -> Vec<ScoredTask> {
+    let open: HashMap<&str, &Value> = tasks
+        .iter()
+        .filter(|task| tasks::is_open(task))
+        .filter_map(|task| Some((task_id(task)?, task)))
+        .collect();
+
+    // How many open tasks wait on each task
+    let mut waiting_on: HashMap<&str, usize> = HashMap::new();
+    for task in open.values() {
+        for dependency in depends_on(task) {
+            *waiting_on.entry(dependency).or_default() += 1;
+        }
+    }
+
+    let mut scored: Vec<ScoredTask> = open
+        .iter()
+        .filter(|(_, task)| str_field(task, "status") != Some("someday"))
+        .map(|(id, task)| {
+            let breakdown = ScoreBreakdown {
+                due: due_factor(task, today, config.due_horizon_days),
+                priority: priority_factor(task),
+                stakeholder: stakeholder_factor(task, config),
+                age: age_factor(task, today),
+                unblocks: (*waiting_on.get(id).unwrap_or(&0) as f64 / FULL_UNBLOCKS).min(1.0),
+            };
+            let score = config.due * breakdown.due
+                + config.priority * breakdown.priority
+                + config.stakeholder * breakdown.stakeholder
+                + config.age * breakdown.age
+                + config.unblocks * breakdown.unblocks;
+            let blocked = str_field(task, "status") == Some("blocked")
+                || depends_on(task).any(|dependency| open.contains_key(dependency));
+
+            ScoredTask {
+                id: id.to_string(),
+                title: str_field(task, "title").unwrap_or("Untitled").to_string(),
+                score,
+                breakdown,
+                blocked,
+            }
+        })
+        .collect();
+
+    scored.sort_by(|a, b| {
+        a.blocked
+            .cmp(&b.blocked)
+            .then_with(|| b.score.total_cmp(&a.score))
+            .then_with(|| a.title.cmp(&b.title))
+    });
+    scored
+}
+
+/// Open switchbacks, best next task first.
+#[tauri::command]
+pub fn score_tasks(app: AppHandle, limit: Option<usize>) -> Result<Vec<ScoredTask>, String> {
+    let data = storage::read_task_data(&app)?;
+    let config = settings::current(&app).scoring;
+    let mut scored = score(&data.tasks, &config, clock::today());
+    if let Some(limit) = limit {
+        scored.truncate(limit);
+    }
+    Ok(scored)
+}
@@ -0,0 +1,31 @@
+//! Secrets (SMTP passwords, tokens) live in the OS keychain rather than in
+//! settings.json.
+
+const SERVICE: &str = "com.dailycommandboard.desktop";
+
+fn entry(name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(SERVICE, name).map_err(|e| format!("Failed to open keychain: {}", e))
+}
+
+pub fn get_secret(name: &str) -> Result<Option<String>, String> {
+    match entry(name)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read from keychain: {}", e)),
+    }
+}
+
+/// Stores a secret, or removes it when `value` is `None`.
+pub fn set_secret(name: &str, value: Option<&str>) -> Result<(), String> {
+    let entry = entry(name)?;
+
+    match value {
+        Some(value) => entry
+            .set_password(value)
+            .map_err(|e| format!("Failed to write to keychain: {}", e)),
+        None => match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Failed to remove from keychain: {}", e)),
+        },
+    }
+}
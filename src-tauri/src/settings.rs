@@ -0,0 +1,1077 @@
+//! App settings, persisted as settings.json next to tasks.json.
+//!
+//! Every section uses `#[serde(default)]` so older settings files keep loading
+//! when new options are added.
+
+use chrono::{DateTime, Datelike, Local, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::backups;
+use crate::crash;
+use crate::disk;
+use crate::http;
+use crate::locale;
+use crate::log;
+use crate::reminders::ReminderScheduler;
+use crate::storage;
+use crate::telemetry;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub notifications: NotificationSettings,
+    pub agenda_email: AgendaEmailSettings,
+    pub channels: ChannelSettings,
+    pub backups: BackupSettings,
+    pub telemetry: TelemetrySettings,
+    pub crash_reports: CrashReportSettings,
+    pub regional: RegionalSettings,
+    pub scoring: ScoringSettings,
+    pub sync: SyncSettings,
+    pub network: NetworkSettings,
+    pub task_defaults: TaskDefaults,
+    /// Per-project overrides of `task_defaults`, keyed by project name
+    pub projects: BTreeMap<String, ProjectSettings>,
+    pub exports: Vec<ScheduledExport>,
+    pub api: ApiSettings,
+    pub voice: VoiceSettings,
+    pub ocr: OcrSettings,
+    pub rollover: RolloverSettings,
+    pub power: PowerSettings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationSettings {
+    pub quiet_hours: QuietHours,
+    /// Per-label overrides; labels not listed behave normally
+    pub labels: BTreeMap<String, LabelNotificationMode>,
+    /// Urgency and sound for reminders of tasks with a label
+    pub label_styles: BTreeMap<String, NotificationStyle>,
+    /// Urgency and sound by priority, keyed `p0`-`p4`
+    pub priority_styles: BTreeMap<String, NotificationStyle>,
+    pub nag: NagSettings,
+}
+
+/// Repeats critical reminders until they're completed, snoozed or
+/// acknowledged. Each repeat comes sooner than the last, down to
+/// `min_interval_minutes`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NagSettings {
+    pub enabled: bool,
+    pub first_interval_minutes: u32,
+    pub min_interval_minutes: u32,
+    /// Most notifications for one task in a day, the first one included
+    pub daily_cap: u32,
+}
+
+impl Default for NagSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            first_interval_minutes: 30,
+            min_interval_minutes: 5,
+            daily_cap: 10,
+        }
+    }
+}
+
+impl NagSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_interval_minutes == 0 || self.first_interval_minutes < self.min_interval_minutes {
+            return Err("Repeat reminders need a shortest interval of at least a minute, no longer than the first".to_string());
+        }
+        if self.daily_cap < 2 {
+            return Err("Repeat reminders need a daily cap of at least 2".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum LabelNotificationMode {
+    #[default]
+    Normal,
+    /// Never remind about tasks with this label
+    Silent,
+    /// Remind even during quiet hours
+    Critical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum Urgency {
+    /// No sound
+    Low,
+    #[default]
+    Normal,
+    /// A more insistent sound
+    High,
+    /// An alarm sound, delivered during quiet hours and to channels
+    Critical,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationStyle {
+    pub urgency: Urgency,
+    /// Sound to play instead of the urgency's own: a system sound name
+    /// (macOS "Glass", Windows "Mail", a freedesktop name on Linux) or a
+    /// sound file on Linux
+    pub sound: Option<String>,
+}
+
+impl NotificationSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        self.quiet_hours.validate()?;
+        self.nag.validate()?;
+        for priority in self.priority_styles.keys() {
+            if !["p0", "p1", "p2", "p3", "p4"].contains(&priority.as_str()) {
+                return Err(format!("Unknown priority \"{}\" in notification styles", priority));
+            }
+        }
+        let mut styles = self.label_styles.values().chain(self.priority_styles.values());
+        if styles.any(|style| style.sound.as_ref().is_some_and(|s| s.trim().is_empty())) {
+            return Err("Notification sounds need a name".to_string());
+        }
+        Ok(())
+    }
+
+    /// Resolves a task's style from its priority and labels: the highest
+    /// urgency wins, and the sound comes from the most urgent style that
+    /// names one. A critical label mode counts as critical urgency.
+    pub fn style_for<'a>(&self, labels: impl Iterator<Item = &'a str>, priority: Option<&str>) -> NotificationStyle {
+        let mut styles: Vec<NotificationStyle> = Vec::new();
+        styles.extend(priority.and_then(|p| self.priority_styles.get(p)).cloned());
+        for label in labels {
+            styles.extend(self.label_styles.get(label).cloned());
+            if self.labels.get(label) == Some(&LabelNotificationMode::Critical) {
+                styles.push(NotificationStyle {
+                    urgency: Urgency::Critical,
+                    sound: None,
+                });
+            }
+        }
+        styles.sort_by_key(|style| std::cmp::Reverse(style.urgency));
+        NotificationStyle {
+            urgency: styles.first().map_or(Urgency::Normal, |style| style.urgency),
+            sound: styles.iter().find_map(|style| style.sound.clone()),
+        }
+    }
+
+    /// Resolves the mode for a task from its labels. Critical wins over
+    /// silent so a task tagged both is never missed.
+    pub fn mode_for_labels<'a>(&self, labels: impl Iterator<Item = &'a str>) -> LabelNotificationMode {
+        let mut mode = LabelNotificationMode::Normal;
+        for label in labels {
+            match self.labels.get(label) {
+                Some(LabelNotificationMode::Critical) => return LabelNotificationMode::Critical,
+                Some(LabelNotificationMode::Silent) => mode = LabelNotificationMode::Silent,
+                _ => {}
+            }
+        }
+        mode
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QuietHours {
+    pub enabled: bool,
+    /// Local time quiet hours begin, as `HH:MM`
+    pub start: String,
+    /// Local time quiet hours end, as `HH:MM`. May be earlier than `start`
+    /// to span midnight.
+    pub end: String,
+    /// Treat all of Saturday and Sunday as quiet
+    pub weekends: bool,
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: "22:00".to_string(),
+            end: "08:00".to_string(),
+            weekends: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with STARTTLS (usually port 587)
+    #[default]
+    Starttls,
+    /// TLS from the start (usually port 465)
+    Tls,
+}
+
+/// Morning agenda email. The SMTP password is stored in the keychain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AgendaEmailSettings {
+    pub enabled: bool,
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub security: SmtpSecurity,
+    pub username: String,
+    pub from: String,
+    pub to: String,
+    /// Local time to send the agenda, as `HH:MM`
+    pub send_at: String,
+}
+
+impl Default for AgendaEmailSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            smtp_host: String::new(),
+            smtp_port: 587,
+            security: SmtpSecurity::default(),
+            username: String::new(),
+            from: String::new(),
+            to: String::new(),
+            send_at: "07:30".to_string(),
+        }
+    }
+}
+
+impl AgendaEmailSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        parse_clock_time(&self.send_at)?;
+
+        if self.enabled && (self.smtp_host.is_empty() || self.from.is_empty() || self.to.is_empty()) {
+            return Err("Agenda email needs an SMTP host, a from and a to address".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TelegramSettings {
+    pub enabled: bool,
+    pub chat_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MatrixSettings {
+    pub enabled: bool,
+    /// e.g. `https://matrix.org`
+    pub homeserver_url: String,
+    /// e.g. `!abcdef:matrix.org`
+    pub room_id: String,
+}
+
+/// Chat channels for digests and critical reminders. Tokens are stored in the
+/// keychain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ChannelSettings {
+    pub telegram: TelegramSettings,
+    pub matrix: MatrixSettings,
+    /// Send the morning digest to the enabled channels
+    pub send_digest: bool,
+    /// Local time to send the digest, as `HH:MM`
+    pub digest_at: String,
+    /// Also send the weekly digest on Mondays, at `digest_at`
+    pub send_weekly_digest: bool,
+    /// Forward reminders for labels marked critical
+    pub critical_reminders: bool,
+}
+
+impl Default for ChannelSettings {
+    fn default() -> Self {
+        Self {
+            telegram: TelegramSettings::default(),
+            matrix: MatrixSettings::default(),
+            send_digest: false,
+            digest_at: "07:30".to_string(),
+            send_weekly_digest: false,
+            critical_reminders: true,
+        }
+    }
+}
+
+impl ChannelSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        parse_clock_time(&self.digest_at)?;
+
+        if self.telegram.enabled && self.telegram.chat_id.is_empty() {
+            return Err("Telegram needs a chat id".to_string());
+        }
+        if self.matrix.enabled && (self.matrix.homeserver_url.is_empty() || self.matrix.room_id.is_empty()) {
+            return Err("Matrix needs a homeserver URL and a room id".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// How backup files are named. Tokens: `{workspace}`, `{date}` and `{time}`,
+/// the latter two rendered with the strftime formats below.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BackupSettings {
+    pub filename_pattern: String,
+    pub date_format: String,
+    pub time_format: String,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            filename_pattern: "{workspace}_backup_{date}_{time}".to_string(),
+            date_format: "%Y%m%d".to_string(),
+            time_format: "%H%M%S".to_string(),
+        }
+    }
+}
+
+impl BackupSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        backups::render_file_name(self, Local::now()).map(|_| ())
+    }
+}
+
+/// Anonymous feature-usage counts. Off unless the user opts in, and nothing
+/// is sent until they ask for it.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TelemetrySettings {
+    pub enabled: bool,
+    /// Where `send_telemetry` posts the report
+    pub endpoint: String,
+}
+
+impl TelemetrySettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.endpoint.is_empty() && !self.endpoint.starts_with("https://") {
+            return Err("Telemetry endpoint must be an https:// URL".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Opt-in local crash reports; see `crash.rs`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CrashReportSettings {
+    pub enabled: bool,
+    /// Where `send_crash_report` uploads a report
+    pub endpoint: String,
+}
+
+impl CrashReportSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.endpoint.is_empty() && !self.endpoint.starts_with("https://") {
+            return Err("Crash report endpoint must be an https:// URL".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// How dates, durations and numbers are formatted in backend-generated text.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RegionalSettings {
+    /// A locale tag such as `en-US` or `de-DE`
+    pub locale: String,
+    /// How reports, digests and the calendar count weeks
+    pub week_numbering: WeekNumbering,
+    /// Month the fiscal year starts in, 1-12; quarters count from it
+    pub fiscal_year_start_month: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum WeekNumbering {
+    /// Weeks start on Sunday; week 1 contains January 1st
+    #[default]
+    Us,
+    /// ISO 8601: weeks start on Monday; week 1 contains the first Thursday
+    Iso,
+}
+
+impl Default for RegionalSettings {
+    fn default() -> Self {
+        Self {
+            locale: "en-US".to_string(),
+            week_numbering: WeekNumbering::default(),
+            fiscal_year_start_month: 1,
+        }
+    }
+}
+
+impl RegionalSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !locale::is_supported(&self.locale) {
+            return Err(format!("Unsupported locale \"{}\"", self.locale));
+        }
+        if !(1..=12).contains(&self.fiscal_year_start_month) {
+            return Err("The fiscal year has to start in month 1-12".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Weights for `score_tasks`. Each factor is scaled to 0-1 before weighting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScoringSettings {
+    pub due: f64,
+    pub priority: f64,
+    pub stakeholder: f64,
+    pub age: f64,
+    /// Bonus for tasks other open tasks are waiting on
+    pub unblocks: f64,
+    /// How much each stakeholder matters, 0-1; unlisted ones count as 0
+    pub stakeholders: BTreeMap<String, f64>,
+    /// Due dates further out than this add nothing
+    pub due_horizon_days: u32,
+}
+
+impl Default for ScoringSettings {
+    fn default() -> Self {
+        Self {
+            due: 3.0,
+            priority: 2.0,
+            stakeholder: 1.0,
+            age: 0.5,
+            unblocks: 1.0,
+            stakeholders: BTreeMap::new(),
+            due_horizon_days: 14,
+        }
+    }
+}
+
+impl ScoringSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        let weights = [self.due, self.priority, self.stakeholder, self.age, self.unblocks];
+        if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+            return Err("Scoring weights must be zero or more".to_string());
+        }
+        if self.stakeholders.values().any(|w| !(0.0..=1.0).contains(w)) {
+            return Err("Stakeholder weights must be between 0 and 1".to_string());
+        }
+        if self.due_horizon_days == 0 {
+            return Err("The due date horizon must be at least a day".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Applies to all outbound HTTP (sync, channels, telemetry, crash reports).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NetworkSettings {
+    /// e.g. `http://proxy.corp:8080`. Empty uses `HTTPS_PROXY`/`ALL_PROXY`
+    /// (and `NO_PROXY`) from the environment.
+    pub proxy_url: String,
+    /// PEM file of CA certificates to trust as well as the built-in roots,
+    /// for networks that inspect TLS
+    pub ca_bundle_path: String,
+}
+
+/// The local REST API (see `api.rs`). Its token lives in the keychain.
+/// Changes take effect the next time the app starts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ApiSettings {
+    pub enabled: bool,
+    /// Port on 127.0.0.1
+    pub port: u16,
+    /// Also serve the MCP endpoint for AI assistants at `/mcp`
+    pub mcp: bool,
+    /// What each kind of caller may do; applies right away
+    pub permissions: CallerPermissions,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 7425,
+            mcp: false,
+            permissions: CallerPermissions::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Permission {
+    Allow,
+    /// Ask with a dialog each time
+    Prompt,
+    Deny,
+}
+
+/// Permissions for one caller, by operation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationPermissions {
+    /// Listing, searching and reading tasks
+    pub read: Permission,
+    /// Adding tasks, including pages the browser extension captures
+    pub create: Permission,
+    pub complete: Permission,
+}
+
+/// Enforced by `access.rs` for everything that reaches the app through the
+/// local API. The defaults keep the behavior from before they existed:
+/// scripts may do anything, assistants ask before changing things.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CallerPermissions {
+    /// Scripts and tools using the REST API token
+    pub api: OperationPermissions,
+    /// AI assistants using the MCP endpoint
+    pub mcp: OperationPermissions,
+}
+
+impl Default for CallerPermissions {
+    fn default() -> Self {
+        Self {
+            api: OperationPermissions {
+                read: Permission::Allow,
+                create: Permission::Allow,
+                complete: Permission::Allow,
+            },
+            mcp: OperationPermissions {
+                read: Permission::Allow,
+                create: Permission::Prompt,
+                complete: Permission::Prompt,
+            },
+        }
+    }
+}
+
+impl ApiSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.port < 1024 {
+            return Err("The API port must be 1024 or higher".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Offline transcription with a local whisper.cpp install. Voice capture is
+/// unavailable until both paths are set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VoiceSettings {
+    /// The whisper.cpp command line program (`whisper-cli`, or `main` in
+    /// older releases)
+    pub whisper_path: String,
+    /// A ggml model file, e.g. `ggml-base.en.bin`
+    pub model_path: String,
+    /// Spoken language code, or `auto` to detect it
+    pub language: String,
+}
+
+impl Default for VoiceSettings {
+    fn default() -> Self {
+        Self {
+            whisper_path: String::new(),
+            model_path: String::new(),
+            language: "auto".to_string(),
+        }
+    }
+}
+
+impl VoiceSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        for path in [&self.whisper_path, &self.model_path] {
+            if !path.is_empty() && !std::path::Path::new(path).is_absolute() {
+                return Err("The whisper.cpp program and model must be full paths".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reading task lists from photos with a local Tesseract install.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OcrSettings {
+    pub enabled: bool,
+    /// The `tesseract` program. Empty looks it up on `PATH`.
+    pub tesseract_path: String,
+    /// Tesseract language codes, e.g. `eng` or `eng+deu`
+    pub language: String,
+}
+
+impl OcrSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.tesseract_path.is_empty() && !std::path::Path::new(&self.tesseract_path).is_absolute() {
+            return Err("The tesseract program must be a full path".to_string());
+        }
+        // Passed to tesseract after `-l`, so it mustn't look like an option;
+        // each `+`-joined part names one language
+        let valid_part =
+            |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !self.language.split('+').all(valid_part) {
+            return Err(format!("Invalid OCR language \"{}\", expected e.g. eng or eng+deu", self.language));
+        }
+        Ok(())
+    }
+}
+
+impl Default for OcrSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tesseract_path: String::new(),
+            language: "eng".to_string(),
+        }
+    }
+}
+
+/// Systems `start_sync` pulls issues from. Tokens live in the keychain.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SyncSettings {
+    pub github: GithubSyncSettings,
+    pub google: OAuthClientSettings,
+    pub jira: OAuthClientSettings,
+    pub jira_project: JiraSyncSettings,
+    pub google_tasks: GoogleTasksSyncSettings,
+}
+
+/// An OAuth app registered by the user with the provider.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OAuthClientSettings {
+    pub enabled: bool,
+    pub client_id: String,
+    /// Only for providers that require one even for desktop apps (Google)
+    pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GithubSyncSettings {
+    pub enabled: bool,
+    /// `owner/repo`
+    pub repository: String,
+}
+
+/// What `start_sync` pulls from Jira.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct JiraSyncSettings {
+    /// e.g. `acme.atlassian.net`. Empty uses the first site the connected
+    /// account can see.
+    pub site: String,
+    /// Project key, e.g. `OPS`
+    pub project: String,
+}
+
+/// What `start_sync` pulls from Google Tasks.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GoogleTasksSyncSettings {
+    /// Task list id. Empty is the account's default list.
+    pub task_list: String,
+}
+
+impl SyncSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        let repository = self.github.repository.trim();
+        let valid = repository.is_empty()
+            || matches!(repository.split_once('/'), Some((owner, repo))
+                if !owner.is_empty() && !repo.is_empty() && !repo.contains('/'));
+        if !valid {
+            return Err(format!("Invalid GitHub repository \"{}\", expected owner/repo", repository));
+        }
+        // The key goes into a JQL query, so nothing that could end the string
+        let project = self.jira_project.project.trim();
+        if !project.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("Invalid Jira project key \"{}\"", project));
+        }
+        Ok(())
+    }
+}
+
+/// Defaults for new switchbacks. Projects can override each of them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TaskDefaults {
+    /// Added to every new task
+    pub labels: Vec<String>,
+    /// Reminder set on new tasks that have a due date
+    pub reminder: ReminderDefault,
+    /// Working weekdays, 0 = Sunday. Default reminders that would land on
+    /// another day move back to the last working day.
+    pub working_days: Vec<u32>,
+}
+
+impl Default for TaskDefaults {
+    fn default() -> Self {
+        Self {
+            labels: Vec::new(),
+            reminder: ReminderDefault::default(),
+            working_days: vec![1, 2, 3, 4, 5],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReminderDefault {
+    pub enabled: bool,
+    pub days_before: u32,
+    /// Local time, as `HH:MM`
+    pub at: String,
+}
+
+impl Default for ReminderDefault {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            days_before: 1,
+            at: "09:00".to_string(),
+        }
+    }
+}
+
+/// Overrides for one project; `None` inherits from the parent project, or
+/// from `task_defaults` at the top of the chain.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProjectSettings {
+    pub parent: Option<String>,
+    pub labels: Option<Vec<String>>,
+    pub reminder: Option<ReminderDefault>,
+    pub working_days: Option<Vec<u32>>,
+}
+
+fn validate_working_days(days: &[u32]) -> Result<(), String> {
+    if days.is_empty() || days.iter().any(|day| *day > 6) {
+        return Err("Working days must be weekdays 0-6, at least one".to_string());
+    }
+    Ok(())
+}
+
+impl TaskDefaults {
+    pub fn validate(&self) -> Result<(), String> {
+        parse_clock_time(&self.reminder.at)?;
+        validate_working_days(&self.working_days)
+    }
+}
+
+fn validate_projects(projects: &BTreeMap<String, ProjectSettings>) -> Result<(), String> {
+    for (name, project) in projects {
+        if name.trim().is_empty() {
+            return Err("Projects need a name".to_string());
+        }
+        if let Some(reminder) = &project.reminder {
+            parse_clock_time(&reminder.at)?;
+        }
+        if let Some(days) = &project.working_days {
+            validate_working_days(days)?;
+        }
+
+        // Walk up the chain to catch unknown parents and cycles
+        let mut seen = vec![name.as_str()];
+        let mut parent = project.parent.as_deref();
+        while let Some(next) = parent {
+            if seen.contains(&next) {
+                return Err(format!("Project \"{}\" inherits from itself", name));
+            }
+            let Some(next_project) = projects.get(next) else {
+                return Err(format!("Project \"{}\" inherits from unknown project \"{}\"", name, next));
+            };
+            seen.push(next);
+            parent = next_project.parent.as_deref();
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    /// The same file `export_tasks` writes
+    #[default]
+    Json,
+    Csv,
+    /// A readable report of what's due and what got done
+    Markdown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFrequency {
+    #[default]
+    Daily,
+    Weekly,
+}
+
+/// Moving overdue switchbacks forward, by hand or each morning.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RolloverSettings {
+    /// Run every morning at `run_at`
+    pub auto: bool,
+    /// Local time, as `HH:MM`
+    pub run_at: String,
+    /// Tasks with this label keep their date
+    pub exclude_label: String,
+    /// Move to the next working day when today isn't one
+    pub working_days_only: bool,
+}
+
+impl Default for RolloverSettings {
+    fn default() -> Self {
+        Self {
+            auto: false,
+            run_at: "06:00".to_string(),
+            exclude_label: "fixed".to_string(),
+            working_days_only: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum PowerMode {
+    /// Low power while on battery
+    #[default]
+    Auto,
+    /// Always low power
+    On,
+    /// Never low power
+    Off,
+}
+
+/// Low-power mode (see `power.rs`): background work runs less often.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PowerSettings {
+    pub low_power: PowerMode,
+    /// How many times longer background intervals get
+    pub stretch_factor: u32,
+}
+
+impl Default for PowerSettings {
+    fn default() -> Self {
+        Self {
+            low_power: PowerMode::Auto,
+            stretch_factor: 4,
+        }
+    }
+}
+
+impl PowerSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=20).contains(&self.stretch_factor) {
+            return Err("Low-power intervals can be stretched 1 to 20 times".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// An export the background job system writes to a folder on a schedule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScheduledExport {
+    pub enabled: bool,
+    /// Unique; also the start of the exported file names
+    pub name: String,
+    pub format: ExportFormat,
+    pub folder: String,
+    pub frequency: ExportFrequency,
+    /// Weekday for weekly exports, 0 = Sunday
+    pub weekday: u32,
+    /// Local time to export, as `HH:MM`
+    pub at: String,
+}
+
+impl Default for ScheduledExport {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            name: String::new(),
+            format: ExportFormat::default(),
+            folder: String::new(),
+            frequency: ExportFrequency::default(),
+            weekday: 1,
+            at: "23:00".to_string(),
+        }
+    }
+}
+
+fn validate_exports(exports: &[ScheduledExport]) -> Result<(), String> {
+    for (i, export) in exports.iter().enumerate() {
+        let name = export.name.trim();
+        if name.is_empty() || name.contains(['/', '\\']) {
+            return Err("Every scheduled export needs a name without slashes".to_string());
+        }
+        if exports[..i].iter().any(|other| other.name.trim() == name) {
+            return Err(format!("There are two scheduled exports named \"{}\"", name));
+        }
+        if !std::path::Path::new(&export.folder).is_absolute() {
+            return Err(format!("The folder for \"{}\" must be a full path", name));
+        }
+        if export.weekday > 6 {
+            return Err("Export weekdays must be 0-6".to_string());
+        }
+        parse_clock_time(&export.at)?;
+    }
+    Ok(())
+}
+
+pub fn parse_clock_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .map_err(|_| format!("Invalid time \"{}\", expected HH:MM", value))
+}
+
+impl QuietHours {
+    pub fn validate(&self) -> Result<(), String> {
+        parse_clock_time(&self.start)?;
+        parse_clock_time(&self.end)?;
+        Ok(())
+    }
+
+    pub fn is_quiet(&self, now: DateTime<Local>) -> bool {
+        if !self.enabled {
+            return false;
+        }
+
+        if self.weekends && matches!(now.weekday(), Weekday::Sat | Weekday::Sun) {
+            return true;
+        }
+
+        let (Ok(start), Ok(end)) = (parse_clock_time(&self.start), parse_clock_time(&self.end)) else {
+            return false;
+        };
+        let time = now.time();
+
+        if start <= end {
+            time >= start && time < end
+        } else {
+            // Spans midnight, e.g. 22:00-08:00
+            time >= start || time < end
+        }
+    }
+}
+
+impl Settings {
+    pub fn validate(&self) -> Result<(), String> {
+        self.notifications.validate()?;
+        self.agenda_email.validate()?;
+        self.channels.validate()?;
+        self.backups.validate()?;
+        self.telemetry.validate()?;
+        self.crash_reports.validate()?;
+        self.regional.validate()?;
+        self.scoring.validate()?;
+        self.sync.validate()?;
+        self.task_defaults.validate()?;
+        validate_projects(&self.projects)?;
+        validate_exports(&self.exports)?;
+        self.api.validate()?;
+        parse_clock_time(&self.rollover.run_at)?;
+        self.power.validate()?;
+        self.voice.validate()?;
+        self.ocr.validate()
+    }
+}
+
+/// Managed state holding the current settings.
+#[derive(Default)]
+pub struct SettingsStore(Mutex<Settings>);
+
+impl SettingsStore {
+    pub fn get(&self) -> Settings {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+fn get_settings_path(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("settings.json")
+}
+
+/// What settings.json holds, without applying it. `None` when nothing was
+/// saved yet.
+pub fn read_saved(app: &AppHandle) -> Result<Option<Settings>, String> {
+    let path = get_settings_path(app);
+
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read settings file: {}", e))?;
+
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse settings: {}", e))
+}
+
+/// Loads settings.json into the managed store, falling back to defaults.
+pub fn load(app: &AppHandle) -> Result<(), String> {
+    let Some(settings) = read_saved(app)? else {
+        return Ok(());
+    };
+
+    if let Err(e) = http::configure(&settings.network) {
+        log::error(app, e);
+    }
+    *app.state::<SettingsStore>().0.lock().unwrap() = settings;
+    Ok(())
+}
+
+pub fn current(app: &AppHandle) -> Settings {
+    app.state::<SettingsStore>().get()
+}
+
+#[tauri::command]
+pub fn get_settings(app: AppHandle) -> Settings {
+    current(&app)
+}
+
+#[tauri::command]
+pub fn save_settings(app: AppHandle, settings: Settings) -> Result<(), String> {
+    save(&app, settings)
+}
+
+/// Validates and writes settings.json and applies the new settings.
+pub fn save(app: &AppHandle, settings: Settings) -> Result<(), String> {
+    settings.validate()?;
+    // Also checks the proxy and CA bundle before anything is saved
+    http::configure(&settings.network)?;
+
+    let content = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    let path = get_settings_path(app);
+    disk::retry_io(|| disk::write_atomic(&path, content.as_bytes()))
+        .map_err(|e| disk::describe_io_error("Failed to write settings file", &e))?;
+
+    // Opting out also throws away what was collected
+    if !settings.telemetry.enabled {
+        telemetry::clear(app);
+    }
+    crash::set_enabled(settings.crash_reports.enabled);
+
+    *app.state::<SettingsStore>().0.lock().unwrap() = settings;
+
+    // Quiet hours and other reminder options may have changed
+    app.state::<ReminderScheduler>().reschedule();
+
+    Ok(())
+}
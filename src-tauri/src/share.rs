@@ -0,0 +1,254 @@
+//! Things shared with Afterglow from other apps: files sent with Windows'
+//! Send To menu or "Open With" on Linux, and text passed as
+//! `--share-text "..."` (for scripts and shortcuts). Emails become tasks as
+//! in `email_import.rs`, other files become tasks with the file attached,
+//! and text goes through the quick-add parser.
+//!
+//! Sharing launches the app with the items as arguments. When a copy is
+//! already running, the new one writes them to share_inbox/ in the data
+//! directory and quits; the running copy picks them up. A launch with
+//! nothing to share brings the running copy to the front instead.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::attachments;
+use crate::disk;
+use crate::email_import;
+use crate::log;
+use crate::paste;
+use crate::power;
+use crate::storage;
+use crate::tasks::{self, str_field, QuickAddResult};
+use crate::tray;
+
+const INBOX_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum SharedItem {
+    Text { text: String },
+    File { path: PathBuf },
+}
+
+/// Held by the first running copy for as long as it runs.
+pub struct InstanceLock {
+    _file: File,
+}
+
+fn inbox_dir(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("share_inbox")
+}
+
+fn items_from_args(args: impl Iterator<Item = String>) -> Vec<SharedItem> {
+    let mut items = Vec::new();
+    let mut args = args;
+    while let Some(arg) = args.next() {
+        if arg == "--share-text" {
+            if let Some(text) = args.next().filter(|text| !text.trim().is_empty()) {
+                items.push(SharedItem::Text { text });
+            }
+        } else if !arg.starts_with('-') {
+            let path = PathBuf::from(&arg);
+            if path.is_absolute() && path.is_file() {
+                items.push(SharedItem::File { path });
+            }
+        }
+    }
+    items
+}
+
+fn try_lock_instance(app: &AppHandle) -> Option<File> {
+    let path = storage::get_app_data_dir(app).join("instance.lock");
+    let file = OpenOptions::new().create(true).truncate(false).write(true).open(path).ok()?;
+    file.try_lock().ok()?;
+    Some(file)
+}
+
+/// Hands launch arguments to a copy that's already running.
+fn queue(app: &AppHandle, items: &[SharedItem]) -> Result<(), String> {
+    let dir = inbox_dir(app);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create the share inbox: {}", e))?;
+    let content =
+        serde_json::to_string(items).map_err(|e| format!("Failed to serialize shared items: {}", e))?;
+    disk::write_atomic(&dir.join(format!("{}.json", uuid::Uuid::new_v4())), content.as_bytes())
+        .map_err(|e| disk::describe_io_error("Failed to queue shared items", &e))
+}
+
+fn add_file(app: &AppHandle, path: &Path) -> Result<QuickAddResult, String> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+    if extension.eq_ignore_ascii_case("eml") {
+        return email_import::import_file(app, path, None);
+    }
+
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("Shared file");
+    let result = tasks::add_task(app, json!({ "title": name }), None)?;
+    let id = tasks::task_id(&result.task).unwrap_or_default();
+    attachments::copy_into(app, id, path)
+        .map_err(|e| format!("The task was created, but the file couldn't be attached: {}", e))?;
+    Ok(result)
+}
+
+/// The first line is the title; the rest goes in the notes.
+fn add_text(app: &AppHandle, text: &str) -> Result<QuickAddResult, String> {
+    let text = text.trim();
+    let (first, rest) = text.split_once('\n').unwrap_or((text, ""));
+    let mut task = paste::parse_line(first).ok_or_else(|| "There was no text to add".to_string())?;
+    if !rest.trim().is_empty() {
+        task["notes"] = json!(rest.trim());
+    }
+    tasks::add_task(app, task, None)
+}
+
+fn add_items(app: &AppHandle, items: Vec<SharedItem>) {
+    for item in items {
+        let result = match &item {
+            SharedItem::Text { text } => add_text(app, text),
+            SharedItem::File { path } => add_file(app, path),
+        };
+        let (title, body) = match &result {
+            Ok(added) => ("Added to Afterglow", str_field(&added.task, "title").unwrap_or_default().to_string()),
+            Err(e) => ("Couldn't add what was shared", e.clone()),
+        };
+        app.notification().builder().title(title).body(body).show().ok();
+    }
+}
+
+fn drain_inbox(app: &AppHandle) {
+    let Ok(entries) = fs::read_dir(inbox_dir(app)) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let items = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str::<Vec<SharedItem>>(&content).map_err(|e| e.to_string()));
+        fs::remove_file(&path).ok();
+        match items {
+            Ok(items) if items.is_empty() => tray::show_main_window(app),
+            Ok(items) => add_items(app, items),
+            Err(e) => log::error(app, format!("Failed to read shared items: {}", e)),
+        }
+    }
+}
+
+/// Whether this is the only copy running. When it isn't, the running copy
+/// is handed what this one was launched with, or asked to come to the front,
+/// and this one should quit before it loads or starts anything.
+pub fn claim_instance(app: &AppHandle) -> bool {
+    let Some(lock) = try_lock_instance(app) else {
+        if let Err(e) = queue(app, &items_from_args(env::args().skip(1))) {
+            log::error(app, e);
+        }
+        return false;
+    };
+    app.manage(InstanceLock { _file: lock });
+    true
+}
+
+/// Adds what this copy was launched with and starts watching the inbox.
+/// Only for the copy that claimed the instance.
+pub fn start(app: AppHandle) {
+    let items = items_from_args(env::args().skip(1));
+    thread::spawn(move || {
+        add_items(&app, items);
+        loop {
+            drain_inbox(&app);
+            power::sleep(&app, INBOX_POLL_INTERVAL);
+        }
+    });
+}
+
+/// The file that adds Afterglow to the system's share menu.
+#[cfg(target_os = "windows")]
+fn registration_path() -> Result<Option<PathBuf>, String> {
+    let app_data = env::var("APPDATA").map_err(|_| "Couldn't find the Send To folder".to_string())?;
+    Ok(Some(PathBuf::from(app_data).join("Microsoft\\Windows\\SendTo\\Afterglow.lnk")))
+}
+
+#[cfg(target_os = "linux")]
+fn registration_path() -> Result<Option<PathBuf>, String> {
+    let data_home = env::var("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|_| env::var("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .map_err(|_| "Couldn't find the applications folder".to_string())?;
+    Ok(Some(data_home.join("applications/afterglow-share.desktop")))
+}
+
+/// Nothing to register elsewhere. On macOS the share menu is Services, which
+/// takes an `NSServices` entry in Info.plist plus a provider object
+/// registered with AppKit at launch; Tauri has no hook for the latter.
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn registration_path() -> Result<Option<PathBuf>, String> {
+    Ok(None)
+}
+
+/// Writes a shortcut in Send To. Shortcuts are binary, so PowerShell makes it.
+#[cfg(target_os = "windows")]
+fn register(exe: &Path, path: &Path) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+
+    let quote = |path: &Path| path.display().to_string().replace('\'', "''");
+    let script = format!(
+        "$s = (New-Object -ComObject WScript.Shell).CreateShortcut('{}'); $s.TargetPath = '{}'; $s.Save()",
+        quote(path),
+        quote(exe)
+    );
+    let mut command = std::process::Command::new("powershell");
+    command.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+    // CREATE_NO_WINDOW, so no console flashes up
+    command.creation_flags(0x0800_0000);
+    let status = command
+        .status()
+        .map_err(|e| format!("Failed to create the Send To shortcut: {}", e))?;
+    if !status.success() {
+        return Err("Failed to create the Send To shortcut".to_string());
+    }
+    Ok(())
+}
+
+/// A hidden desktop entry, so Afterglow shows up under "Open With".
+#[cfg(not(target_os = "windows"))]
+fn register(exe: &Path, path: &Path) -> Result<(), String> {
+    let entry = format!(
+        "[Desktop Entry]\nType=Application\nName=Afterglow\nComment=Add to Afterglow\nExec=\"{}\" %F\nNoDisplay=true\nMimeType=message/rfc822;text/plain;application/pdf;image/png;image/jpeg;\n",
+        exe.display()
+    );
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create the applications folder: {}", e))?;
+    }
+    fs::write(path, entry).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Adds Afterglow to Send To (Windows) or Open With (Linux) and returns the
+/// file that does it. macOS isn't supported (see `registration_path`); text
+/// can still be shared there with `--share-text`.
+#[tauri::command]
+pub fn register_share_target() -> Result<String, String> {
+    let path = registration_path()?
+        .ok_or_else(|| "Sharing to Afterglow isn't available on this system yet".to_string())?;
+    let exe = env::current_exe().map_err(|e| format!("Failed to find the app: {}", e))?;
+    register(&exe, &path)?;
+    Ok(path.display().to_string())
+}
+
+#[tauri::command]
+pub fn unregister_share_target() -> Result<(), String> {
+    let Some(path) = registration_path()? else {
+        return Ok(());
+    };
+    match fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove {}: {}", path.display(), e)),
+    }
+}
@@ -0,0 +1,400 @@
+//! Reading and writing tasks.json.
+//!
+//! Every window and backend module shares one in-memory copy of the task
+//! data, loaded on first use and kept in step with each save, so windows
+//! never work from separate copies of the file. Each save bumps a revision
+//! number so a window saving a snapshot it loaded before someone else's
+//! save is turned away instead of overwriting that save.
+//!
+//! Where the data is kept is up to a `Storage` backend. `FileStorage` is
+//! the default; `MemoryStorage` keeps everything in memory, for trying the
+//! app (`AFTERGLOW_STORAGE=memory`) or exercising migrations and importers
+//! without touching real data. Other backends only need `load` and `save`.
+//!
+//! IO is retried with backoff so a data directory on a network share rides
+//! out brief drops. When the share is unreachable a save goes to a local
+//! write-behind file instead and is flushed once the share comes back.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::backups;
+use crate::disk;
+use crate::log;
+use crate::metrics;
+use crate::power;
+
+/// Tells the frontend whether a save is waiting for the data directory.
+pub const STORAGE_STATUS_EVENT: &str = "storage-status";
+
+const PENDING_FILE: &str = "pending_tasks.json";
+
+const FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Serializes saves with the background flush.
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TaskData {
+    pub tasks: Vec<serde_json::Value>,
+    pub labels: Vec<String>,
+    pub stakeholders: Vec<String>,
+}
+
+/// Where the task data is kept.
+pub trait Storage: Send + Sync {
+    /// The saved data, or empty data when nothing was saved yet.
+    fn load(&self) -> Result<TaskData, String>;
+
+    fn save(&self, data: &TaskData) -> Result<(), String>;
+
+    /// Whether anything was saved yet.
+    fn has_data(&self) -> bool;
+
+    /// Size of the saved data, when the backend knows it.
+    fn size_bytes(&self) -> Option<u64> {
+        None
+    }
+
+    /// Whether a save is held back until the store can be reached.
+    fn pending_write(&self) -> bool {
+        false
+    }
+
+    /// Retries a held-back save; called regularly in the background.
+    fn flush_pending(&self) {}
+}
+
+/// tasks.json in the app data directory, with backups and write-behind.
+pub struct FileStorage {
+    app: AppHandle,
+}
+
+impl Storage for FileStorage {
+    fn load(&self) -> Result<TaskData, String> {
+        read_from_disk(&self.app)
+    }
+
+    fn save(&self, data: &TaskData) -> Result<(), String> {
+        persist(&self.app, data)
+    }
+
+    fn has_data(&self) -> bool {
+        get_data_path(&self.app).exists() || get_pending_path(&self.app).exists()
+    }
+
+    fn size_bytes(&self) -> Option<u64> {
+        fs::metadata(get_data_path(&self.app)).ok().map(|metadata| metadata.len())
+    }
+
+    fn pending_write(&self) -> bool {
+        get_pending_path(&self.app).exists()
+    }
+
+    fn flush_pending(&self) {
+        flush_pending(&self.app);
+    }
+}
+
+/// Keeps the data in memory only; nothing survives a restart and nothing
+/// touches the data directory.
+#[derive(Default)]
+pub struct MemoryStorage(Mutex<Option<TaskData>>);
+
+impl Storage for MemoryStorage {
+    fn load(&self) -> Result<TaskData, String> {
+        Ok(self.0.lock().unwrap().clone().unwrap_or_default())
+    }
+
+    fn save(&self, data: &TaskData) -> Result<(), String> {
+        *self.0.lock().unwrap() = Some(data.clone());
+        Ok(())
+    }
+
+    fn has_data(&self) -> bool {
+        self.0.lock().unwrap().is_some()
+    }
+
+    fn size_bytes(&self) -> Option<u64> {
+        let data = self.0.lock().unwrap();
+        data.as_ref().and_then(|data| serde_json::to_vec(data).ok()).map(|bytes| bytes.len() as u64)
+    }
+}
+
+/// The shared copy of the task data (`None` until first read) and the
+/// backend behind it, `FileStorage` unless another was set first.
+#[derive(Default)]
+pub struct SharedTaskData {
+    cached: Mutex<Option<TaskData>>,
+    /// Bumped by every save, only while `cached` is locked.
+    revision: AtomicU64,
+    backend: OnceLock<Box<dyn Storage>>,
+}
+
+impl SharedTaskData {
+    fn backend(&self, app: &AppHandle) -> &dyn Storage {
+        self.backend
+            .get_or_init(|| Box::new(FileStorage { app: app.clone() }))
+            .as_ref()
+    }
+}
+
+/// Whether the backend has saved task data yet.
+pub fn has_saved_data(app: &AppHandle) -> bool {
+    app.state::<SharedTaskData>().backend(app).has_data()
+}
+
+/// Size of the saved task data, when the backend knows it.
+pub fn saved_size(app: &AppHandle) -> Option<u64> {
+    app.state::<SharedTaskData>().backend(app).size_bytes()
+}
+
+/// Uses `backend` for the task data. Only possible before it's first read
+/// or written.
+pub fn set_backend(app: &AppHandle, backend: Box<dyn Storage>) -> Result<(), String> {
+    app.state::<SharedTaskData>()
+        .backend
+        .set(backend)
+        .map_err(|_| "The task storage is already in use".to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageStatus {
+    /// A save is held locally until the data directory is reachable.
+    pub pending_write: bool,
+}
+
+pub fn get_app_data_dir(app: &AppHandle) -> PathBuf {
+    let app_data = app.path().app_data_dir().expect("Failed to get app data dir");
+    let app_data = disk::long_path(&app_data);
+    fs::create_dir_all(&app_data).ok();
+    app_data
+}
+
+pub fn get_data_path(app: &AppHandle) -> PathBuf {
+    get_app_data_dir(app).join("tasks.json")
+}
+
+/// The write-behind file. It lives in the local (non-roaming) data dir so it
+/// stays writable when the data directory is on a share that's gone away.
+fn get_pending_path(app: &AppHandle) -> PathBuf {
+    let local = app
+        .path()
+        .app_local_data_dir()
+        .expect("Failed to get app local data dir");
+    let local = disk::long_path(&local);
+    fs::create_dir_all(&local).ok();
+    local.join(PENDING_FILE)
+}
+
+fn is_reachable(dir: &Path) -> bool {
+    disk::retry_io(|| fs::metadata(dir)).is_ok()
+}
+
+/// The current task data, loaded from disk on first use.
+pub fn read_task_data(app: &AppHandle) -> Result<TaskData, String> {
+    let shared = app.state::<SharedTaskData>();
+    let mut cached = shared.cached.lock().unwrap();
+    load_cached(shared.backend(app), &mut cached).cloned()
+}
+
+/// The shared data along with its revision, for a window that will later
+/// save it back with `replace_task_data`.
+pub fn read_task_data_at_revision(app: &AppHandle) -> Result<(TaskData, u64), String> {
+    let shared = app.state::<SharedTaskData>();
+    let mut cached = shared.cached.lock().unwrap();
+    let data = load_cached(shared.backend(app), &mut cached)?.clone();
+    Ok((data, shared.revision.load(Ordering::SeqCst)))
+}
+
+/// Saves `data` and makes it the shared copy.
+pub fn write_task_data(app: &AppHandle, data: &TaskData) -> Result<(), String> {
+    let shared = app.state::<SharedTaskData>();
+    let mut cached = shared.cached.lock().unwrap();
+    shared.backend(app).save(data)?;
+    *cached = Some(data.clone());
+    shared.revision.fetch_add(1, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Saves `data` in place of the shared copy, but only if nothing was saved
+/// since `revision` was read. Returns the new revision.
+pub fn replace_task_data(app: &AppHandle, data: &TaskData, revision: u64) -> Result<u64, String> {
+    let shared = app.state::<SharedTaskData>();
+    let mut cached = shared.cached.lock().unwrap();
+    if shared.revision.load(Ordering::SeqCst) != revision {
+        return Err("Switchbacks were changed elsewhere since this window loaded them".to_string());
+    }
+    shared.backend(app).save(data)?;
+    *cached = Some(data.clone());
+    Ok(shared.revision.fetch_add(1, Ordering::SeqCst) + 1)
+}
+
+/// Applies `update` to the shared data and saves it, holding the lock
+/// throughout so no other save can slip in between the read and the write.
+pub fn update_task_data<T>(
+    app: &AppHandle,
+    update: impl FnOnce(&mut TaskData) -> Result<T, String>,
+) -> Result<T, String> {
+    let shared = app.state::<SharedTaskData>();
+    let mut cached = shared.cached.lock().unwrap();
+    let result = update_cached(shared.backend(app), &mut cached, update)?;
+    shared.revision.fetch_add(1, Ordering::SeqCst);
+    Ok(result)
+}
+
+/// Applies `update` to a copy of `cached` and saves it to `backend`. When
+/// `update` fails nothing is saved and `cached` stays as it was.
+pub fn update_cached<T>(
+    backend: &dyn Storage,
+    cached: &mut Option<TaskData>,
+    update: impl FnOnce(&mut TaskData) -> Result<T, String>,
+) -> Result<T, String> {
+    let mut data = load_cached(backend, cached)?.clone();
+    let result = update(&mut data)?;
+    backend.save(&data)?;
+    *cached = Some(data);
+    Ok(result)
+}
+
+fn load_cached<'a>(backend: &dyn Storage, cached: &'a mut Option<TaskData>) -> Result<&'a TaskData, String> {
+    if cached.is_none() {
+        *cached = Some(backend.load()?);
+    }
+    Ok(cached.as_ref().unwrap())
+}
+
+/// Reads tasks.json, returning empty data when the file doesn't exist yet.
+/// A save still waiting in the write-behind file is newer, so it wins.
+fn read_from_disk(app: &AppHandle) -> Result<TaskData, String> {
+    let pending_path = get_pending_path(app);
+    let path = if pending_path.exists() {
+        pending_path
+    } else {
+        get_data_path(app)
+    };
+
+    if !path.exists() {
+        return Ok(TaskData::default());
+    }
+
+    let content = disk::retry_io(|| fs::read_to_string(&path))
+        .map_err(|e| disk::describe_io_error("Failed to read tasks file", &e))?;
+
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse tasks: {}", e))
+}
+
+/// Backs up the current tasks.json and replaces it with `data`.
+///
+/// The new file is written next to tasks.json and renamed into place, so a
+/// full disk never leaves a truncated tasks.json behind. If the data
+/// directory can't be reached the save is kept in the write-behind file.
+fn persist(app: &AppHandle, data: &TaskData) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(data)
+        .map_err(|e| format!("Failed to serialize tasks: {}", e))?;
+
+    let _guard = WRITE_LOCK.lock().unwrap();
+    let data_dir = get_app_data_dir(app);
+
+    let result = metrics::time("save", || {
+        if is_reachable(&data_dir) {
+            write_to_data_dir(app, &content)
+        } else {
+            Err("Data directory is unreachable".to_string())
+        }
+    });
+
+    match result {
+        Ok(()) => {
+            metrics::increment("saves_total");
+            clear_pending(app);
+            Ok(())
+        }
+        // Only an unreachable share is worth deferring; a full disk or a
+        // permissions problem should reach the user
+        Err(e) if !is_reachable(&data_dir) => {
+            metrics::increment("saves_deferred_total");
+            write_pending(app, &content).map_err(|_| e)
+        }
+        Err(e) => {
+            metrics::increment("save_failures_total");
+            Err(e)
+        }
+    }
+}
+
+fn write_to_data_dir(app: &AppHandle, content: &str) -> Result<(), String> {
+    if !disk::has_space_for(&get_app_data_dir(app), content.len() as u64, disk::SAVE_HEADROOM) {
+        let message = "Not enough disk space to save tasks";
+        disk::warn_low_space(app, message);
+        return Err(message.to_string());
+    }
+
+    let path = get_data_path(app);
+
+    // Back up the current file before the new one replaces it
+    backups::create_backup(app, &path)?;
+
+    disk::retry_io(|| disk::write_atomic(&path, content.as_bytes()))
+        .map_err(|e| disk::describe_io_error("Failed to write tasks file", &e))
+}
+
+fn write_pending(app: &AppHandle, content: &str) -> Result<(), String> {
+    disk::write_atomic(&get_pending_path(app), content.as_bytes())
+        .map_err(|e| format!("Failed to write local copy of tasks: {}", e))?;
+    emit_status(app, true);
+    Ok(())
+}
+
+fn clear_pending(app: &AppHandle) {
+    let pending_path = get_pending_path(app);
+    if pending_path.exists() && fs::remove_file(&pending_path).is_ok() {
+        emit_status(app, false);
+    }
+}
+
+fn emit_status(app: &AppHandle, pending_write: bool) {
+    app.emit(STORAGE_STATUS_EVENT, StorageStatus { pending_write }).ok();
+}
+
+/// Starts the thread that writes a deferred save to the data directory once
+/// it's reachable again. Backends without write-behind ignore it.
+pub fn start_write_behind_flusher(app: AppHandle) {
+    thread::spawn(move || loop {
+        power::sleep(&app, FLUSH_INTERVAL);
+        app.state::<SharedTaskData>().backend(&app).flush_pending();
+    });
+}
+
+fn flush_pending(app: &AppHandle) {
+    let _guard = WRITE_LOCK.lock().unwrap();
+
+    let pending_path = get_pending_path(app);
+    if !pending_path.exists() || !is_reachable(&get_app_data_dir(app)) {
+        return;
+    }
+
+    let Ok(content) = fs::read_to_string(&pending_path) else {
+        return;
+    };
+
+    match write_to_data_dir(app, &content) {
+        Ok(()) => clear_pending(app),
+        Err(e) => log::error(app, format!("Failed to flush pending tasks: {}", e)),
+    }
+}
+
+#[tauri::command]
+pub fn get_storage_status(app: AppHandle) -> StorageStatus {
+    StorageStatus {
+        pending_write: app.state::<SharedTaskData>().backend(&app).pending_write(),
+    }
+}
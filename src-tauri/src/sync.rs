@@ -0,0 +1,729 @@
+//! Pulling issues from other systems (GitHub issues, a Jira project, a
+//! Google Tasks list) into switchbacks, as a background job.
+//!
+//! A sync walks the source's pages oldest change first and saves its cursor
+//! (the newest change seen, plus the next page) to sync_state.json after
+//! every page, so a run that fails halfway through a large project picks up
+//! where it stopped instead of starting over. Later runs only ask for what
+//! changed since the cursor. Jira and Google use the accounts connected
+//! through `oauth`.
+//!
+//! Rate limits are waited out when the wait is short, and server errors are
+//! retried with exponential backoff. Tasks are matched to issues through the
+//! external id store.
+
+use chrono::{DateTime, Duration as ChronoDuration, Local, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use ureq::http::Response;
+use ureq::Body;
+
+use crate::announcements;
+use crate::disk;
+use crate::external_ids;
+use crate::http;
+use crate::integrations::{self, Integration};
+use crate::jobs::{self, JobContext};
+use crate::log;
+use crate::metrics;
+use crate::oauth::{self, OAuthProvider};
+use crate::secrets;
+use crate::settings;
+use crate::storage;
+use crate::tasks::{self, now_iso};
+
+const MAX_ATTEMPTS: u32 = 6;
+
+/// Rate-limit waits longer than this end the run; the next run resumes.
+const MAX_WAIT: Duration = Duration::from_secs(15 * 60);
+
+const GITHUB_TOKEN_SECRET: &str = "github-token";
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncSourceKind {
+    Github,
+    Jira,
+    Google,
+}
+
+impl SyncSourceKind {
+    /// Cursor keys for this kind start with this
+    fn key_prefix(self) -> &'static str {
+        match self {
+            SyncSourceKind::Github => "github:",
+            SyncSourceKind::Jira => "jira:",
+            SyncSourceKind::Google => "google:",
+        }
+    }
+}
+
+/// Where a source's sync got to.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SyncCursor {
+    /// Update time of the newest change applied
+    pub since: Option<String>,
+    /// The page to fetch next when a run stopped partway
+    pub next_page: Option<String>,
+    /// Newest change seen by an unfinished run of a source that doesn't
+    /// sort by update time; becomes `since` once the run completes
+    pub pending_since: Option<String>,
+    pub last_run_at: Option<String>,
+    pub last_completed_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Default)]
+pub struct SyncState(Mutex<BTreeMap<String, SyncCursor>>);
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncSummary {
+    pub pages: usize,
+    pub added: usize,
+    pub updated: usize,
+}
+
+struct RemoteItem {
+    external_id: String,
+    title: String,
+    url: String,
+    closed: bool,
+    updated_at: String,
+}
+
+struct Page {
+    items: Vec<RemoteItem>,
+    next_page: Option<String>,
+}
+
+trait SyncSource {
+    /// Name used for external id links
+    fn system(&self) -> &'static str;
+    fn integration(&self) -> Integration;
+    /// Key for the cursor; changes when the source is pointed elsewhere
+    fn cursor_key(&self) -> String;
+    fn first_page(&self, since: Option<&str>) -> String;
+    fn fetch(&self, url: &str) -> Result<Response<Body>, String>;
+    /// Reads the page fetched from `url`
+    fn parse(&self, url: &str, response: Response<Body>) -> Result<Page, String>;
+    /// Whether pages come oldest change first
+    fn sorted_by_update(&self) -> bool {
+        true
+    }
+}
+
+struct GithubIssues {
+    repository: String,
+    token: String,
+}
+
+impl SyncSource for GithubIssues {
+    fn system(&self) -> &'static str {
+        "github"
+    }
+
+    fn integration(&self) -> Integration {
+        Integration::Github
+    }
+
+    fn cursor_key(&self) -> String {
+        format!("{}{}", SyncSourceKind::Github.key_prefix(), self.repository)
+    }
+
+    fn first_page(&self, since: Option<&str>) -> String {
+        let mut url = format!(
+            "https://api.github.com/repos/{}/issues?state=all&sort=updated&direction=asc&per_page=100",
+            self.repository
+        );
+        if let Some(since) = since {
+            url.push_str(&format!("&since={}", http::encode_path_segment(since)));
+        }
+        url
+    }
+
+    fn fetch(&self, url: &str) -> Result<Response<Body>, String> {
+        http::agent()
+            .get(url)
+            .config()
+            .http_status_as_error(false)
+            .build()
+            .header("Accept", "application/vnd.github+json")
+            .header("Authorization", &format!("Bearer {}", self.token))
+            .header("User-Agent", "Afterglow")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .call()
+            .map_err(|e| e.to_string())
+    }
+
+    fn parse(&self, _url: &str, mut response: Response<Body>) -> Result<Page, String> {
+        let next_page = header(&response, "link").and_then(next_link);
+        let issues: Vec<Value> = response
+            .body_mut()
+            .read_json()
+            .map_err(|e| format!("Failed to parse GitHub issues: {}", e))?;
+
+        let items = issues
+            .iter()
+            // The issues list includes pull requests
+            .filter(|issue| issue.get("pull_request").is_none())
+            .filter_map(|issue| {
+                Some(RemoteItem {
+                    external_id: format!("{}#{}", self.repository, issue.get("number")?.as_u64()?),
+                    title: issue.get("title")?.as_str()?.to_string(),
+                    url: issue.get("html_url")?.as_str()?.to_string(),
+                    closed: issue.get("state")?.as_str()? == "closed",
+                    updated_at: issue.get("updated_at")?.as_str()?.to_string(),
+                })
+            })
+            .collect();
+
+        Ok(Page { items, next_page })
+    }
+}
+
+/// A Jira project, through the Atlassian API gateway. Page URLs are kept
+/// relative to the site so a saved cursor doesn't depend on the cloud id.
+struct JiraIssues {
+    app: AppHandle,
+    site: String,
+    project: String,
+    /// Cloud id and site URL, looked up on the first request
+    resource: Mutex<Option<(String, String)>>,
+}
+
+impl JiraIssues {
+    fn resource(&self, token: &str) -> Result<(String, String), String> {
+        let mut resource = self.resource.lock().unwrap();
+        if let Some(found) = resource.as_ref() {
+            return Ok(found.clone());
+        }
+
+        let sites: Vec<Value> = http::agent()
+            .get("https://api.atlassian.com/oauth/token/accessible-resources")
+            .header("Authorization", &format!("Bearer {}", token))
+            .header("Accept", "application/json")
+            .call()
+            .map_err(|e| format!("Failed to look up Jira sites: {}", e))?
+            .body_mut()
+            .read_json()
+            .map_err(|e| format!("Failed to parse Jira sites: {}", e))?;
+
+        let found = sites
+            .iter()
+            .filter_map(|site| {
+                Some((site.get("id")?.as_str()?.to_string(), site.get("url")?.as_str()?.to_string()))
+            })
+            .find(|(_, url)| self.site.is_empty() || url.trim_start_matches("https://") == self.site)
+            .ok_or_else(|| match self.site.as_str() {
+                "" => "The connected Jira account can't see any sites".to_string(),
+                site => format!("The connected Jira account can't see {}", site),
+            })?;
+        *resource = Some(found.clone());
+        Ok(found)
+    }
+}
+
+impl SyncSource for JiraIssues {
+    fn system(&self) -> &'static str {
+        "jira"
+    }
+
+    fn integration(&self) -> Integration {
+        Integration::Jira
+    }
+
+    fn cursor_key(&self) -> String {
+        format!("{}{}/{}", SyncSourceKind::Jira.key_prefix(), self.site, self.project)
+    }
+
+    fn first_page(&self, since: Option<&str>) -> String {
+        let mut jql = format!("project = \"{}\"", self.project);
+        // JQL only takes dates in the user's time zone, so go back a day
+        // and let already-applied issues come through again
+        if let Some(since) = since.and_then(|since| DateTime::parse_from_rfc3339(since).ok()) {
+            let day = (since.with_timezone(&Utc) - ChronoDuration::days(1)).format("%Y-%m-%d");
+            jql.push_str(&format!(" AND updated >= \"{}\"", day));
+        }
+        jql.push_str(" ORDER BY updated ASC");
+        format!(
+            "/rest/api/3/search/jql?jql={}&fields=summary,status,updated&maxResults=100",
+            http::encode_path_segment(&jql)
+        )
+    }
+
+    fn fetch(&self, url: &str) -> Result<Response<Body>, String> {
+        let token = oauth::access_token(&self.app, OAuthProvider::Jira)?;
+        let (cloud_id, _) = self.resource(&token)?;
+        http::agent()
+            .get(&format!("https://api.atlassian.com/ex/jira/{}{}", cloud_id, url))
+            .config()
+            .http_status_as_error(false)
+            .build()
+            .header("Authorization", &format!("Bearer {}", token))
+            .header("Accept", "application/json")
+            .call()
+            .map_err(|e| e.to_string())
+    }
+
+    fn parse(&self, url: &str, mut response: Response<Body>) -> Result<Page, String> {
+        let body: Value = response
+            .body_mut()
+            .read_json()
+            .map_err(|e| format!("Failed to parse Jira issues: {}", e))?;
+        let site_url = self.resource.lock().unwrap().clone().map(|(_, url)| url).unwrap_or_default();
+
+        let items = body
+            .get("issues")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|issue| {
+                let key = issue.get("key")?.as_str()?;
+                let fields = issue.get("fields")?;
+                let category = fields.pointer("/status/statusCategory/key")?.as_str()?;
+                let updated = fields.get("updated")?.as_str()?;
+                let updated = DateTime::parse_from_str(updated, "%Y-%m-%dT%H:%M:%S%.f%z").ok()?;
+                Some(RemoteItem {
+                    external_id: key.to_string(),
+                    title: fields.get("summary")?.as_str()?.to_string(),
+                    url: format!("{}/browse/{}", site_url, key),
+                    closed: category == "done",
+                    updated_at: updated.with_timezone(&Utc).to_rfc3339_opts(SecondsFormat::Millis, true),
+                })
+            })
+            .collect();
+
+        let last = body.get("isLast").and_then(Value::as_bool).unwrap_or(true);
+        let next_page = body
+            .get("nextPageToken")
+            .and_then(Value::as_str)
+            .filter(|_| !last)
+            .map(|token| with_page_token(url, "nextPageToken", token));
+        Ok(Page { items, next_page })
+    }
+}
+
+/// A Google Tasks list. The API doesn't sort by update time, so the cursor
+/// only moves past a change once a whole run has seen it.
+struct GoogleTasks {
+    app: AppHandle,
+    task_list: String,
+}
+
+impl SyncSource for GoogleTasks {
+    fn system(&self) -> &'static str {
+        "google"
+    }
+
+    fn integration(&self) -> Integration {
+        Integration::Google
+    }
+
+    fn cursor_key(&self) -> String {
+        format!("{}{}", SyncSourceKind::Google.key_prefix(), self.task_list)
+    }
+
+    fn sorted_by_update(&self) -> bool {
+        false
+    }
+
+    fn first_page(&self, since: Option<&str>) -> String {
+        let mut url = format!(
+            "https://tasks.googleapis.com/tasks/v1/lists/{}/tasks?showCompleted=true&showHidden=true&maxResults=100",
+            http::encode_path_segment(&self.task_list)
+        );
+        if let Some(since) = since {
+            url.push_str(&format!("&updatedMin={}", http::encode_path_segment(since)));
+        }
+        url
+    }
+
+    fn fetch(&self, url: &str) -> Result<Response<Body>, String> {
+        let token = oauth::access_token(&self.app, OAuthProvider::Google)?;
+        http::agent()
+            .get(url)
+            .config()
+            .http_status_as_error(false)
+            .build()
+            .header("Authorization", &format!("Bearer {}", token))
+            .call()
+            .map_err(|e| e.to_string())
+    }
+
+    fn parse(&self, url: &str, mut response: Response<Body>) -> Result<Page, String> {
+        let body: Value = response
+            .body_mut()
+            .read_json()
+            .map_err(|e| format!("Failed to parse Google tasks: {}", e))?;
+
+        let items = body
+            .get("items")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|task| {
+                let title = task.get("title")?.as_str()?.trim();
+                if title.is_empty() {
+                    return None;
+                }
+                Some(RemoteItem {
+                    external_id: format!("{}/{}", self.task_list, task.get("id")?.as_str()?),
+                    title: title.to_string(),
+                    url: task.get("webViewLink").and_then(Value::as_str).unwrap_or_default().to_string(),
+                    closed: task.get("status")?.as_str()? == "completed",
+                    updated_at: task.get("updated")?.as_str()?.to_string(),
+                })
+            })
+            .collect();
+
+        let next_page = body
+            .get("nextPageToken")
+            .and_then(Value::as_str)
+            .map(|token| with_page_token(url, "pageToken", token));
+        Ok(Page { items, next_page })
+    }
+}
+
+/// `url` with its `name` parameter set to `token`. The parameter is always
+/// added last, so an earlier one is cut off the end.
+fn with_page_token(url: &str, name: &str, token: &str) -> String {
+    let base = url.split(&format!("&{}=", name)).next().unwrap_or(url);
+    format!("{}&{}={}", base, name, http::encode_path_segment(token))
+}
+
+fn header<'a>(response: &'a Response<Body>, name: &str) -> Option<&'a str> {
+    response.headers().get(name).and_then(|value| value.to_str().ok())
+}
+
+/// The `rel="next"` URL of a `Link` header.
+fn next_link(link: &str) -> Option<String> {
+    link.split(',').find_map(|part| {
+        let (url, params) = part.split_once(';')?;
+        params
+            .split(';')
+            .any(|param| param.trim() == "rel=\"next\"")
+            .then(|| url.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+    })
+}
+
+/// How long a rate-limited response asks us to wait.
+fn rate_limit_wait(response: &Response<Body>) -> Option<Duration> {
+    if let Some(seconds) = header(response, "retry-after").and_then(|v| v.parse::<u64>().ok()) {
+        return Some(Duration::from_secs(seconds));
+    }
+    if header(response, "x-ratelimit-remaining") != Some("0") {
+        return None;
+    }
+    let reset = header(response, "x-ratelimit-reset").and_then(|v| v.parse::<i64>().ok())?;
+    Some(Duration::from_secs((reset - Utc::now().timestamp()).max(1) as u64))
+}
+
+/// Sleeps in short steps so cancelling doesn't wait out the whole delay.
+fn wait(job: &JobContext, delay: Duration) -> Result<(), String> {
+    let step = Duration::from_secs(1);
+    let mut remaining = delay;
+    while !remaining.is_zero() {
+        job.cancel.check()?;
+        let nap = remaining.min(step);
+        thread::sleep(nap);
+        remaining -= nap;
+    }
+    Ok(())
+}
+
+fn wait_for_limit(job: &JobContext, delay: Duration) -> Result<(), String> {
+    if delay > MAX_WAIT {
+        let resume_at = Utc::now() + chrono::Duration::seconds(delay.as_secs() as i64);
+        return Err(format!(
+            "Rate limited until {}; run the sync again then to resume",
+            resume_at.with_timezone(&Local).format("%H:%M")
+        ));
+    }
+    wait(job, delay)
+}
+
+/// Fetches one page, waiting out rate limits and retrying server errors.
+fn fetch_page(job: &JobContext, source: &dyn SyncSource, url: &str) -> Result<(Page, Option<Duration>), String> {
+    let mut attempt = 0;
+    loop {
+        job.cancel.check()?;
+        attempt += 1;
+        let backoff = Duration::from_secs(1 << (attempt - 1).min(5));
+
+        let response = match metrics::time("sync_request", || source.fetch(url)) {
+            Ok(response) => response,
+            Err(e) if attempt < MAX_ATTEMPTS => {
+                log::error(&job.app, format!("Sync request failed, retrying: {}", e));
+                wait(job, backoff)?;
+                continue;
+            }
+            Err(e) => return Err(format!("Sync request failed: {}", e)),
+        };
+
+        let status = response.status().as_u16();
+        let rate_limited = status == 429 || (status == 403 && rate_limit_wait(&response).is_some());
+        if rate_limited && attempt < MAX_ATTEMPTS {
+            metrics::increment("sync_rate_limited_total");
+            wait_for_limit(job, rate_limit_wait(&response).unwrap_or(backoff))?;
+            continue;
+        }
+        if status >= 500 && attempt < MAX_ATTEMPTS {
+            wait(job, backoff)?;
+            continue;
+        }
+        if !(200..300).contains(&status) {
+            return Err(format!("Sync request failed with status {}", status));
+        }
+
+        // Out of requests: wait before the next page rather than be refused
+        let pause = rate_limit_wait(&response);
+        return Ok((source.parse(url, response)?, pause));
+    }
+}
+
+fn get_state_path(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("sync_state.json")
+}
+
+pub fn load(app: &AppHandle) -> Result<(), String> {
+    let path = get_state_path(app);
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read sync state: {}", e))?;
+
+    let cursors: BTreeMap<String, SyncCursor> = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse sync state: {}", e))?;
+
+    *app.state::<SyncState>().0.lock().unwrap() = cursors;
+    Ok(())
+}
+
+fn update_cursor(app: &AppHandle, key: &str, update: impl FnOnce(&mut SyncCursor)) -> Result<SyncCursor, String> {
+    let state = app.state::<SyncState>();
+    let mut cursors = state.0.lock().unwrap();
+    let cursor = cursors.entry(key.to_string()).or_default();
+    update(cursor);
+    let updated = cursor.clone();
+
+    let content = serde_json::to_string_pretty(&*cursors)
+        .map_err(|e| format!("Failed to serialize sync state: {}", e))?;
+    disk::write_atomic(&get_state_path(app), content.as_bytes())
+        .map_err(|e| format!("Failed to save sync state: {}", e))?;
+    Ok(updated)
+}
+
+/// Creates or updates the linked task for each item. Closed items that were
+/// never linked are left alone.
+fn apply_page(app: &AppHandle, system: &str, items: &[RemoteItem]) -> Result<(usize, usize), String> {
+    let linked = external_ids::links_for(app, system);
+
+    let (added, updated, new_links) = storage::update_task_data(app, |data| {
+        let (mut added, mut updated, mut new_links) = (0, 0, Vec::new());
+        for item in items {
+            let task = linked
+                .get(&item.external_id)
+                .and_then(|id| tasks::find_task_mut(data, id));
+
+            match task {
+                Some(task) => {
+                    let mut changed = false;
+                    if task.get("title").and_then(Value::as_str) != Some(item.title.as_str()) {
+                        task["title"] = json!(item.title);
+                        changed = true;
+                    }
+                    if item.closed && !tasks::is_done(task) {
+                        task["status"] = json!("done");
+                        task["completedAt"] = json!(now_iso());
+                        changed = true;
+                    } else if !item.closed && tasks::is_done(task) {
+                        task["status"] = json!("not-started");
+                        if let Some(fields) = task.as_object_mut() {
+                            fields.remove("completedAt");
+                        }
+                        changed = true;
+                    }
+                    updated += usize::from(changed);
+                }
+                None if !item.closed => {
+                    let id = uuid::Uuid::new_v4().to_string();
+                    data.tasks.push(json!({
+                        "id": id,
+                        "title": item.title,
+                        "type": "one-off",
+                        "priority": "p2",
+                        "status": "not-started",
+                        "createdAt": now_iso(),
+                        "sortOrder": tasks::max_sort_order(data) + 1,
+                        "notes": item.url,
+                        "labels": [system],
+                        "externalId": item.external_id,
+                    }));
+                    new_links.push((id, item.external_id.clone()));
+                    added += 1;
+                }
+                None => {}
+            }
+        }
+        Ok((added, updated, new_links))
+    })?;
+
+    external_ids::add_links(app, system, new_links)?;
+    if added + updated > 0 {
+        tasks::notify_changed(app, None);
+    }
+    Ok((added, updated))
+}
+
+fn run(job: &JobContext, source: &dyn SyncSource) -> Result<SyncSummary, String> {
+    let app = &job.app;
+    let key = source.cursor_key();
+    let cursor = update_cursor(app, &key, |cursor| cursor.last_run_at = Some(now_iso()))?;
+
+    let mut summary = SyncSummary::default();
+    let mut url = cursor
+        .next_page
+        .clone()
+        .unwrap_or_else(|| source.first_page(cursor.since.as_deref()));
+
+    loop {
+        let (page, pause) = fetch_page(job, source, &url)?;
+        let (added, updated) = apply_page(app, source.system(), &page.items)?;
+        metrics::increment("sync_pages_total");
+        summary.pages += 1;
+        summary.added += added;
+        summary.updated += updated;
+
+        // UTC timestamps, so the newest sorts last
+        let newest = page.items.iter().map(|item| item.updated_at.clone()).max();
+        let next_page = page.next_page.clone();
+        update_cursor(app, &key, |cursor| {
+            if source.sorted_by_update() {
+                cursor.since = newest.max(cursor.since.take());
+            } else {
+                cursor.pending_since = newest.max(cursor.pending_since.take());
+            }
+            cursor.next_page = next_page;
+        })?;
+
+        match page.next_page {
+            Some(next) => url = next,
+            None => break,
+        }
+        if let Some(pause) = pause {
+            wait_for_limit(job, pause)?;
+        }
+    }
+
+    update_cursor(app, &key, |cursor| {
+        cursor.since = cursor.pending_since.take().max(cursor.since.take());
+        cursor.last_completed_at = Some(now_iso());
+        cursor.last_error = None;
+    })?;
+    Ok(summary)
+}
+
+fn source_for(app: &AppHandle, kind: SyncSourceKind) -> Result<Box<dyn SyncSource + Send>, String> {
+    match kind {
+        SyncSourceKind::Github => {
+            let config = settings::current(app).sync.github;
+            if !config.enabled {
+                return Err("The GitHub integration is turned off".to_string());
+            }
+            let repository = config.repository.trim().to_string();
+            if repository.is_empty() {
+                return Err("Set a GitHub repository in settings first".to_string());
+            }
+            let token = secrets::get_secret(GITHUB_TOKEN_SECRET)?
+                .ok_or_else(|| "Add a GitHub token first".to_string())?;
+            Ok(Box::new(GithubIssues { repository, token }))
+        }
+        SyncSourceKind::Jira => {
+            let sync = settings::current(app).sync;
+            ensure_connected(OAuthProvider::Jira, sync.jira.enabled, "Jira")?;
+            let config = sync.jira_project;
+            let project = config.project.trim().to_string();
+            if project.is_empty() {
+                return Err("Set a Jira project in settings first".to_string());
+            }
+            Ok(Box::new(JiraIssues {
+                app: app.clone(),
+                site: config.site.trim().trim_start_matches("https://").trim_end_matches('/').to_string(),
+                project,
+                resource: Mutex::new(None),
+            }))
+        }
+        SyncSourceKind::Google => {
+            let sync = settings::current(app).sync;
+            ensure_connected(OAuthProvider::Google, sync.google.enabled, "Google")?;
+            let config = sync.google_tasks;
+            let task_list = match config.task_list.trim() {
+                "" => "@default".to_string(),
+                list => list.to_string(),
+            };
+            Ok(Box::new(GoogleTasks { app: app.clone(), task_list }))
+        }
+    }
+}
+
+fn ensure_connected(provider: OAuthProvider, enabled: bool, name: &str) -> Result<(), String> {
+    if !enabled {
+        return Err(format!("The {} integration is turned off", name));
+    }
+    if !oauth::is_connected(provider) {
+        return Err(format!("Connect your {} account first", name));
+    }
+    Ok(())
+}
+
+/// Starts a sync job for `source` and returns the job id.
+#[tauri::command]
+pub fn start_sync(app: AppHandle, source: SyncSourceKind) -> Result<String, String> {
+    let source = source_for(&app, source)?;
+    Ok(jobs::spawn(&app, "sync", move |job| {
+        let result = metrics::time("sync", || run(job, source.as_ref()));
+        metrics::increment(if result.is_ok() { "syncs_total" } else { "syncs_failed_total" });
+        integrations::record(&job.app, source.integration(), &result);
+        if let Err(e) = &result {
+            announcements::sync_failed(&job.app, source.integration().name(), e);
+            update_cursor(&job.app, &source.cursor_key(), |cursor| cursor.last_error = Some(e.clone())).ok();
+        }
+        result
+    }))
+}
+
+#[tauri::command]
+pub fn get_sync_state(app: AppHandle) -> BTreeMap<String, SyncCursor> {
+    app.state::<SyncState>().0.lock().unwrap().clone()
+}
+
+/// Forgets where a source got to, so the next run fetches everything again.
+#[tauri::command]
+pub fn reset_sync(app: AppHandle, source: SyncSourceKind) -> Result<(), String> {
+    let state = app.state::<SyncState>();
+    let mut cursors = state.0.lock().unwrap();
+    cursors.retain(|key, _| !key.starts_with(source.key_prefix()));
+
+    let content = serde_json::to_string_pretty(&*cursors)
+        .map_err(|e| format!("Failed to serialize sync state: {}", e))?;
+    disk::write_atomic(&get_state_path(&app), content.as_bytes())
+        .map_err(|e| format!("Failed to save sync state: {}", e))
+}
+
+/// Stores the GitHub token in the keychain, or removes it when `None`.
+#[tauri::command]
+pub fn set_github_token(token: Option<String>) -> Result<(), String> {
+    secrets::set_secret(GITHUB_TOKEN_SECRET, token.as_deref())
+}
@@ -0,0 +1,244 @@
+//! Helpers for working with tasks on the backend.
+//!
+//! Tasks are kept as raw JSON so fields the backend doesn't know about survive
+//! a round trip untouched. These helpers mirror the task store's actions in
+//! `src/stores/taskStore.ts`.
+
+use chrono::SecondsFormat;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, Manager, Webview};
+
+use crate::clock;
+use crate::duplicates::{self, DuplicateCandidate};
+use crate::events;
+use crate::habits;
+use crate::projects;
+use crate::recurrence::{self, RecurrenceRule};
+use crate::reminders::ReminderScheduler;
+use crate::settings;
+use crate::storage::{self, TaskData};
+
+/// Emitted whenever tasks change so every window reloads them.
+pub const TASKS_CHANGED_EVENT: &str = "tasks-changed";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TasksChanged {
+    /// Label of the window that made the change, which already has it.
+    /// `None` for changes made by the backend.
+    pub source: Option<String>,
+}
+
+/// Tells every window (and API subscribers) that tasks changed, and logs any
+/// habit completions.
+pub fn notify_changed(app: &AppHandle, source: Option<&str>) {
+    habits::record(app);
+    events::publish(app);
+    let payload = TasksChanged {
+        source: source.map(str::to_string),
+    };
+    app.emit(TASKS_CHANGED_EVENT, payload).ok();
+}
+
+pub fn str_field<'a>(task: &'a Value, field: &str) -> Option<&'a str> {
+    task.get(field).and_then(Value::as_str)
+}
+
+pub fn task_id(task: &Value) -> Option<&str> {
+    str_field(task, "id")
+}
+
+pub fn labels(task: &Value) -> impl Iterator<Item = &str> {
+    task.get("labels")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+}
+
+pub fn is_done(task: &Value) -> bool {
+    str_field(task, "status") == Some("done")
+}
+
+/// Whether a recurring task was ended (it stops appearing after `endedAt`).
+pub fn is_ended(task: &Value) -> bool {
+    task.get("endedAt").is_some_and(|v| !v.is_null())
+}
+
+/// Not done and not ended.
+pub fn is_open(task: &Value) -> bool {
+    !is_done(task) && !is_ended(task)
+}
+
+/// Timestamp in the same shape as JavaScript's `Date.toISOString()`.
+pub fn now_iso() -> String {
+    clock::now_utc().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+pub fn find_task_mut<'a>(data: &'a mut TaskData, id: &str) -> Option<&'a mut Value> {
+    data.tasks.iter_mut().find(|task| task_id(task) == Some(id))
+}
+
+pub fn max_sort_order(data: &TaskData) -> i64 {
+    data.tasks
+        .iter()
+        .filter_map(|task| task.get("sortOrder").and_then(Value::as_i64))
+        .fold(0, i64::max)
+}
+
+/// Marks a task as done, creating the next instance for recurring tasks.
+/// Mirrors `completeTask` in the task store.
+pub fn complete_task(data: &mut TaskData, id: &str) -> Result<(), String> {
+    let next_sort_order = max_sort_order(data) + 1;
+    let task = find_task_mut(data, id).ok_or_else(|| format!("Task not found: {}", id))?;
+
+    let completed_at = now_iso();
+    task["status"] = json!("done");
+    task["completedAt"] = json!(completed_at);
+
+    let is_recurring = str_field(task, "type") == Some("recurring");
+    let rule = task
+        .get("recurrence")
+        .and_then(|r| serde_json::from_value::<RecurrenceRule>(r.clone()).ok());
+
+    // If it's a recurring task, create the next instance
+    if let (true, Some(rule)) = (is_recurring, rule) {
+        let today = clock::today();
+        if let Some(next_due_date) =
+            recurrence::next_recurrence_date(&rule, str_field(task, "dueDate"), today)
+        {
+            let mut next_task = json!({
+                "id": uuid::Uuid::new_v4().to_string(),
+                "title": task["title"],
+                "type": "recurring",
+                "priority": task["priority"],
+                "status": "not-started",
+                "createdAt": completed_at,
+                "dueDate": next_due_date,
+                "recurrence": task["recurrence"],
+                "parentRecurringId": str_field(task, "parentRecurringId").unwrap_or(id),
+                "sortOrder": next_sort_order,
+            });
+            for field in ["notes", "stakeholders", "labels", "estimatedMinutes", "habit"] {
+                if let Some(value) = task.get(field) {
+                    next_task[field] = value.clone();
+                }
+            }
+            data.tasks.push(next_task);
+        }
+    }
+
+    Ok(())
+}
+
+/// Applies a backend-initiated change to tasks.json and notifies the frontend.
+pub fn modify_task_data<T>(
+    app: &AppHandle,
+    update: impl FnOnce(&mut TaskData) -> Result<T, String>,
+) -> Result<T, String> {
+    let result = storage::update_task_data(app, update)?;
+    notify_changed(app, None);
+    Ok(result)
+}
+
+/// Fills in the fields `addTask` in the task store defaults for a new task.
+pub fn fill_defaults(data: &TaskData, task: &mut Value) {
+    let defaults = [
+        ("id", json!(uuid::Uuid::new_v4().to_string())),
+        ("type", json!("one-off")),
+        ("priority", json!("p2")),
+        ("status", json!("not-started")),
+        ("createdAt", json!(now_iso())),
+        ("sortOrder", json!(max_sort_order(data) + 1)),
+    ];
+    for (field, value) in defaults {
+        if task.get(field).is_none_or(Value::is_null) {
+            task[field] = value;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickAddResult {
+    pub task: Value,
+    /// Open tasks with a similar title, so the UI can ask "might this already
+    /// exist?"
+    pub possible_duplicates: Vec<DuplicateCandidate>,
+}
+
+/// Adds a task with the same defaults as `addTask` in the task store plus
+/// its project's defaults, and returns any open tasks that look like near-duplicates of it.
+#[tauri::command]
+pub fn quick_add_task(app: AppHandle, webview: Webview, task: Value) -> Result<QuickAddResult, String> {
+    add_task(&app, task, Some(webview.label()))
+}
+
+/// `quick_add_task` for callers outside the windows; `source` is the window
+/// label, if any.
+pub fn add_task(app: &AppHandle, task: Value, source: Option<&str>) -> Result<QuickAddResult, String> {
+    let mut results = add_tasks(app, vec![task], source)?;
+    Ok(results.remove(0))
+}
+
+/// Adds several tasks in one write, each as `add_task` would.
+pub fn add_tasks(app: &AppHandle, new_tasks: Vec<Value>, source: Option<&str>) -> Result<Vec<QuickAddResult>, String> {
+    if new_tasks.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut prepared = Vec::with_capacity(new_tasks.len());
+    for task in new_tasks {
+        if !task.is_object() {
+            return Err("A task must be an object".to_string());
+        }
+        let title = str_field(&task, "title").map(str::trim).unwrap_or("");
+        if title.is_empty() {
+            return Err("A task needs a title".to_string());
+        }
+        let title = title.to_string();
+        prepared.push((task, title));
+    }
+
+    let settings = settings::current(app);
+    let results = storage::update_task_data(app, |data| {
+        let mut results = Vec::with_capacity(prepared.len());
+        for (mut task, title) in prepared {
+            let possible_duplicates = duplicates::find_duplicates(&data.tasks, &title);
+
+            fill_defaults(data, &mut task);
+            projects::apply_defaults(&settings, &mut task);
+            task["title"] = json!(title);
+            remember_names(data, &task);
+
+            data.tasks.push(task.clone());
+            results.push(QuickAddResult { task, possible_duplicates });
+        }
+        Ok(results)
+    })?;
+
+    notify_changed(app, source);
+    app.state::<ReminderScheduler>().reschedule();
+    Ok(results)
+}
+
+/// Adds the task's labels and stakeholders to the lists the pickers offer.
+fn remember_names(data: &mut TaskData, task: &Value) {
+    for label in labels(task) {
+        if !data.labels.iter().any(|known| known == label) {
+            data.labels.push(label.to_string());
+        }
+    }
+    let stakeholders = task.get("stakeholders").and_then(Value::as_array).into_iter().flatten();
+    for stakeholder in stakeholders.filter_map(Value::as_str) {
+        if !data.stakeholders.iter().any(|known| known == stakeholder) {
+            data.stakeholders.push(stakeholder.to_string());
+        }
+    }
+}
+
+/// Adds a batch of tasks, e.g. the ones kept from a paste or scan preview.
+#[tauri::command]
+pub fn quick_add_tasks(app: AppHandle, webview: Webview, tasks: Vec<Value>) -> Result<Vec<QuickAddResult>, String> {
+    add_tasks(&app, tasks, Some(webview.label()))
+}
@@ -0,0 +1,185 @@
+//! Opt-in, anonymous feature-usage telemetry.
+//!
+//! Usage is only counted while `settings.telemetry.enabled` is on, and the
+//! counts stay in telemetry.json until the user sends them with
+//! `send_telemetry`. `preview_telemetry` returns the exact payload that would
+//! be sent. Setting `AFTERGLOW_DISABLE_TELEMETRY` turns all of it off
+//! regardless of settings.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::disk;
+use crate::http;
+use crate::settings;
+use crate::storage;
+use crate::tasks::now_iso;
+
+/// Kill switch for managed installs: when set, nothing is counted or sent.
+const KILL_SWITCH_ENV: &str = "AFTERGLOW_DISABLE_TELEMETRY";
+
+const MAX_FEATURE_NAME_LEN: usize = 64;
+
+/// Aggregated counts since the last send. No task content, names or paths.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TelemetryReport {
+    /// Random id generated on opt-in, not tied to the user or machine
+    pub install_id: String,
+    pub app_version: String,
+    pub os: String,
+    pub since: String,
+    pub features: BTreeMap<String, u64>,
+}
+
+#[derive(Default)]
+pub struct TelemetryStore(Mutex<TelemetryReport>);
+
+fn get_telemetry_path(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("telemetry.json")
+}
+
+fn is_killed() -> bool {
+    std::env::var_os(KILL_SWITCH_ENV).is_some()
+}
+
+fn is_enabled(app: &AppHandle) -> bool {
+    !is_killed() && settings::current(app).telemetry.enabled
+}
+
+pub fn load(app: &AppHandle) -> Result<(), String> {
+    let path = get_telemetry_path(app);
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read telemetry file: {}", e))?;
+
+    let report: TelemetryReport = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse telemetry file: {}", e))?;
+
+    *app.state::<TelemetryStore>().0.lock().unwrap() = report;
+    Ok(())
+}
+
+fn save(app: &AppHandle, report: &TelemetryReport) {
+    if let Ok(content) = serde_json::to_string_pretty(report) {
+        let path = get_telemetry_path(app);
+        disk::retry_io(|| disk::write_atomic(&path, content.as_bytes())).ok();
+    }
+}
+
+/// Counts one use of `feature`, if the user opted in.
+pub fn record(app: &AppHandle, feature: &str) {
+    if !is_enabled(app) {
+        return;
+    }
+
+    let store = app.state::<TelemetryStore>();
+    let mut report = store.0.lock().unwrap();
+
+    if report.install_id.is_empty() {
+        report.install_id = uuid::Uuid::new_v4().to_string();
+    }
+    if report.since.is_empty() {
+        report.since = now_iso();
+    }
+    *report.features.entry(feature.to_string()).or_default() += 1;
+
+    save(app, &report);
+}
+
+/// Forgets everything collected, including the install id.
+pub fn clear(app: &AppHandle) {
+    *app.state::<TelemetryStore>().0.lock().unwrap() = TelemetryReport::default();
+    fs::remove_file(get_telemetry_path(app)).ok();
+}
+
+fn payload(app: &AppHandle) -> TelemetryReport {
+    TelemetryReport {
+        app_version: app.package_info().version.to_string(),
+        os: std::env::consts::OS.to_string(),
+        ..app.state::<TelemetryStore>().0.lock().unwrap().clone()
+    }
+}
+
+/// Lets the frontend count its own features, e.g. `"canopy.filter"`.
+#[tauri::command]
+pub fn record_feature_usage(app: AppHandle, feature: String) -> Result<(), String> {
+    let valid = !feature.is_empty()
+        && feature.len() <= MAX_FEATURE_NAME_LEN
+        && feature.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+    if !valid {
+        return Err(format!("Invalid feature name \"{}\"", feature));
+    }
+
+    record(&app, &feature);
+    Ok(())
+}
+
+/// Exactly what `send_telemetry` would send right now.
+#[tauri::command]
+pub fn preview_telemetry(app: AppHandle) -> TelemetryReport {
+    payload(&app)
+}
+
+/// Sends the collected counts and starts counting afresh. Only ever runs
+/// when the user triggers it, on a blocking thread since the upload waits
+/// for the server.
+#[tauri::command]
+pub async fn send_telemetry(app: AppHandle) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || upload(&app))
+        .await
+        .map_err(|e| format!("Failed to send telemetry: {}", e))?
+}
+
+fn upload(app: &AppHandle) -> Result<(), String> {
+    if is_killed() {
+        return Err("Telemetry is disabled on this machine".to_string());
+    }
+
+    let config = settings::current(app).telemetry;
+    if !config.enabled {
+        return Err("Telemetry is turned off".to_string());
+    }
+    if config.endpoint.is_empty() {
+        return Err("No telemetry endpoint is configured".to_string());
+    }
+
+    let report = payload(app);
+    if report.features.is_empty() {
+        return Ok(());
+    }
+
+    http::agent()
+        .post(&config.endpoint)
+        .send_json(&report)
+        .map_err(|e| format!("Failed to send telemetry: {}", e))?;
+
+    // Keep the install id; subtract what was sent so uses counted
+    // meanwhile aren't lost
+    let store = app.state::<TelemetryStore>();
+    let mut stored = store.0.lock().unwrap();
+    for (feature, count) in report.features {
+        if let Some(remaining) = stored.features.get_mut(&feature) {
+            *remaining = remaining.saturating_sub(count);
+        }
+    }
+    stored.features.retain(|_, count| *count > 0);
+    stored.since = now_iso();
+    save(app, &stored);
+
+    Ok(())
+}
+
+/// Deletes collected telemetry without changing the opt-in setting.
+#[tauri::command]
+pub fn clear_telemetry(app: AppHandle) {
+    clear(&app);
+}
@@ -0,0 +1,416 @@
+//! Task templates, persisted in templates.json. A template is a named set of
+//! switchbacks to create together (a client kickoff, a conference trip).
+//! Titles, notes and due dates can use `{{variables}}`; instantiating a
+//! template without every value returns the variables to prompt for instead
+//! of creating anything.
+//!
+//! Due dates are `+N` / `-N` days from today, a `YYYY-MM-DD` date, or either
+//! relative to a date variable, e.g. `{{kickoff_date}}+3`.
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Webview};
+
+use crate::clock;
+use crate::disk;
+use crate::projects;
+use crate::recurrence::{format_task_date, parse_task_date};
+use crate::reminders::ReminderScheduler;
+use crate::settings;
+use crate::storage;
+use crate::tasks::{self, now_iso};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum VariableKind {
+    #[default]
+    Text,
+    Date,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateVariable {
+    pub name: String,
+    #[serde(default)]
+    pub label: String,
+    #[serde(default)]
+    pub kind: VariableKind,
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateTask {
+    pub title: String,
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// See the module docs for the format
+    #[serde(default)]
+    pub due: Option<String>,
+    /// Any other task fields (priority, labels, estimatedMinutes, ...),
+    /// copied as they are
+    #[serde(flatten)]
+    pub fields: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskTemplate {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// Labels and defaults for variables; ones only used in the text are
+    /// picked up too
+    #[serde(default)]
+    pub variables: Vec<TemplateVariable>,
+    pub tasks: Vec<TemplateTask>,
+    #[serde(default)]
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum InstantiateResult {
+    /// Nothing was created; ask for these and call again
+    NeedsValues { variables: Vec<TemplateVariable> },
+    Created { tasks: Vec<Value> },
+}
+
+#[derive(Default)]
+pub struct TemplatesStore(Mutex<Vec<TaskTemplate>>);
+
+fn get_templates_path(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("templates.json")
+}
+
+pub fn load(app: &AppHandle) -> Result<(), String> {
+    let path = get_templates_path(app);
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read templates: {}", e))?;
+
+    let templates: Vec<TaskTemplate> = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse templates: {}", e))?;
+
+    *app.state::<TemplatesStore>().0.lock().unwrap() = templates;
+    Ok(())
+}
+
+fn save(app: &AppHandle, templates: &[TaskTemplate]) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(templates)
+        .map_err(|e| format!("Failed to serialize templates: {}", e))?;
+    disk::write_atomic(&get_templates_path(app), content.as_bytes())
+        .map_err(|e| format!("Failed to save templates: {}", e))
+}
+
+/// Names of the `{{variables}}` in `text`, in order of appearance.
+fn placeholders(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + end].trim();
+        if !name.is_empty() {
+            names.push(name.to_string());
+        }
+        rest = &rest[start + end + 2..];
+    }
+    names
+}
+
+fn substitute(text: &str, values: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let name = rest[start + 2..start + end].trim();
+        match values.get(name) {
+            Some(value) => out.push_str(value),
+            None => out.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Declared variables followed by any others the template uses. Undeclared
+/// ones ending in `_date` are dates.
+fn variables(template: &TaskTemplate) -> Vec<TemplateVariable> {
+    let mut variables: Vec<TemplateVariable> = template
+        .variables
+        .iter()
+        .cloned()
+        .map(|mut variable| {
+            if variable.label.is_empty() {
+                variable.label = variable.name.clone();
+            }
+            variable
+        })
+        .collect();
+
+    let used = template.tasks.iter().flat_map(|task| {
+        [Some(&task.title), task.notes.as_ref(), task.due.as_ref()]
+            .into_iter()
+            .flatten()
+            .flat_map(|text| placeholders(text))
+    });
+    for name in used {
+        if variables.iter().any(|v| v.name == name) {
+            continue;
+        }
+        let kind = if name.ends_with("_date") {
+            VariableKind::Date
+        } else {
+            VariableKind::Text
+        };
+        variables.push(TemplateVariable {
+            label: name.clone(),
+            name,
+            kind,
+            default: None,
+        });
+    }
+    variables
+}
+
+/// Evaluates a due expression once its variables are substituted.
+fn resolve_due(expression: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let invalid = || format!("Invalid due date \"{}\"", expression);
+    let expression = expression.trim();
+
+    // A leading date is 10 characters; the rest is an optional offset
+    let (base, offset) = match expression.get(..10).and_then(parse_task_date) {
+        Some(date) => (date, expression[10..].trim()),
+        None => (today, expression),
+    };
+    if offset.is_empty() {
+        return Ok(base);
+    }
+    // "- 2" as well as "-2"
+    let (sign, amount) = match offset.strip_prefix('-') {
+        Some(amount) => (-1, amount),
+        None => (1, offset.strip_prefix('+').unwrap_or(offset)),
+    };
+    let days: i64 = amount.trim().parse().map_err(|_| invalid())?;
+    Ok(base + Duration::days(sign * days))
+}
+
+fn build_tasks(
+    template: &TaskTemplate,
+    values: &HashMap<String, String>,
+    today: NaiveDate,
+) -> Result<Vec<Value>, String> {
+    template
+        .tasks
+        .iter()
+        .map(|item| {
+            let mut task = Value::Object(item.fields.clone());
+            task["title"] = json!(substitute(&item.title, values).trim());
+            if let Some(notes) = &item.notes {
+                task["notes"] = json!(substitute(notes, values));
+            }
+            if let Some(due) = item.due.as_deref().filter(|due| !due.trim().is_empty()) {
+                let due = resolve_due(&substitute(due, values), today)?;
+                task["dueDate"] = json!(format_task_date(due));
+            }
+            Ok(task)
+        })
+        .collect()
+}
+
+fn validate(template: &TaskTemplate) -> Result<(), String> {
+    if template.name.trim().is_empty() {
+        return Err("A template needs a name".to_string());
+    }
+    if template.tasks.is_empty() {
+        return Err("A template needs at least one task".to_string());
+    }
+    if template.tasks.iter().any(|task| task.title.trim().is_empty()) {
+        return Err("Every template task needs a title".to_string());
+    }
+    if template.variables.iter().any(|v| v.name.trim().is_empty()) {
+        return Err("Every variable needs a name".to_string());
+    }
+    Ok(())
+}
+
+fn find_template(app: &AppHandle, id: &str) -> Result<TaskTemplate, String> {
+    app.state::<TemplatesStore>()
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|t| t.id == id)
+        .cloned()
+        .ok_or_else(|| format!("Template not found: {}", id))
+}
+
+#[tauri::command]
+pub fn list_templates(app: AppHandle) -> Vec<TaskTemplate> {
+    app.state::<TemplatesStore>().0.lock().unwrap().clone()
+}
+
+/// Creates the template, or replaces the one with the same id.
+#[tauri::command]
+pub fn save_template(app: AppHandle, mut template: TaskTemplate) -> Result<TaskTemplate, String> {
+    validate(&template)?;
+    if template.id.is_empty() {
+        template.id = uuid::Uuid::new_v4().to_string();
+    }
+    if template.created_at.is_empty() {
+        template.created_at = now_iso();
+    }
+
+    let store = app.state::<TemplatesStore>();
+    let mut templates = store.0.lock().unwrap();
+    match templates.iter_mut().find(|t| t.id == template.id) {
+        Some(existing) => *existing = template.clone(),
+        None => templates.push(template.clone()),
+    }
+    save(&app, &templates)?;
+
+    Ok(template)
+}
+
+#[tauri::command]
+pub fn delete_template(app: AppHandle, id: String) -> Result<(), String> {
+    let store = app.state::<TemplatesStore>();
+    let mut templates = store.0.lock().unwrap();
+    let before = templates.len();
+    templates.retain(|t| t.id != id);
+    if templates.len() == before {
+        return Err(format!("Template not found: {}", id));
+    }
+    save(&app, &templates)
+}
+
+/// The variables to prompt for before instantiating the template.
+#[tauri::command]
+pub fn get_template_variables(app: AppHandle, id: String) -> Result<Vec<TemplateVariable>, String> {
+    Ok(variables(&find_template(&app, &id)?))
+}
+
+/// Creates the template's tasks with `values` substituted. Variables missing
+/// a value (and without a default) come back as `needsValues` instead.
+#[tauri::command]
+pub fn instantiate_template(
+    app: AppHandle,
+    webview: Webview,
+    id: String,
+    values: HashMap<String, String>,
+) -> Result<InstantiateResult, String> {
+    let template = find_template(&app, &id)?;
+    let schema = variables(&template);
+
+    let mut resolved = HashMap::new();
+    let mut missing = Vec::new();
+    for variable in schema {
+        let value = values
+            .get(&variable.name)
+            .or(variable.default.as_ref())
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+        match value {
+            Some(value) => {
+                if variable.kind == VariableKind::Date && parse_task_date(&value).is_none() {
+                    return Err(format!("{} must be a date (YYYY-MM-DD)", variable.label));
+                }
+                resolved.insert(variable.name, value);
+            }
+            None => missing.push(variable),
+        }
+    }
+    if !missing.is_empty() {
+        return Ok(InstantiateResult::NeedsValues { variables: missing });
+    }
+
+    let mut new_tasks = build_tasks(&template, &resolved, clock::today())?;
+    let settings = settings::current(&app);
+    storage::update_task_data(&app, |data| {
+        for task in &mut new_tasks {
+            tasks::fill_defaults(data, task);
+            projects::apply_defaults(&settings, task);
+            data.tasks.push(task.clone());
+        }
+        Ok(())
+    })?;
+
+    tasks::notify_changed(&app, Some(webview.label()));
+    app.state::<ReminderScheduler>().reschedule();
+    Ok(InstantiateResult::Created { tasks: new_tasks })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        parse_task_date(value).unwrap()
+    }
+
+    #[test]
+    fn substitute_fills_known_variables_and_keeps_the_rest() {
+        let values = values(&[("client", "Contoso"), ("week", "42")]);
+        assert_eq!(
+            substitute("Invoice {{ client }} for week {{week}} ({{missing}})", &values),
+            "Invoice Contoso for week 42 ({{missing}})"
+        );
+    }
+
+    #[test]
+    fn substitute_leaves_unclosed_and_empty_text_alone() {
+        let values = values(&[("client", "Contoso")]);
+        assert_eq!(substitute("", &values), "");
+        assert_eq!(substitute("Call {{client", &values), "Call {{client");
+        assert_eq!(substitute("{{client}}{{client}}", &values), "ContosoContoso");
+    }
+
+    #[test]
+    fn placeholders_lists_names_in_order() {
+        assert_eq!(placeholders("{{ b }} then {{a}}, {{}} and {{b"), vec!["b", "a"]);
+    }
+
+    #[test]
+    fn resolve_due_takes_a_date_an_offset_or_both() {
+        let today = date("2026-10-14");
+        assert_eq!(resolve_due("2026-11-01", today), Ok(date("2026-11-01")));
+        assert_eq!(resolve_due("+3", today), Ok(date("2026-10-17")));
+        assert_eq!(resolve_due(" -14 ", today), Ok(date("2026-09-30")));
+        assert_eq!(resolve_due("2026-11-01 - 2", today), Ok(date("2026-10-30")));
+    }
+
+    #[test]
+    fn resolve_due_crosses_a_leap_day() {
+        assert_eq!(resolve_due("2028-02-28 +1", date("2026-10-14")), Ok(date("2028-02-29")));
+        assert_eq!(resolve_due("2028-02-28+2", date("2026-10-14")), Ok(date("2028-03-01")));
+    }
+
+    #[test]
+    fn resolve_due_rejects_anything_else() {
+        let today = date("2026-10-14");
+        assert!(resolve_due("next friday", today).is_err());
+        assert!(resolve_due("2026-10-14 tomorrow", today).is_err());
+        assert_eq!(resolve_due("", today), Ok(today));
+    }
+}
@@ -0,0 +1,83 @@
+//! Time tracked against tasks with the focus timer, persisted in
+//! tracked_time.json as totals per task.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::disk;
+use crate::log;
+use crate::storage;
+use crate::tasks::now_iso;
+
+/// Timer runs shorter than this are treated as misclicks.
+const MIN_SESSION_SECONDS: i64 = 30;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackedTime {
+    pub seconds: i64,
+    pub sessions: u32,
+    pub last_tracked_at: String,
+}
+
+#[derive(Default)]
+pub struct TimeLog(Mutex<BTreeMap<String, TrackedTime>>);
+
+impl TimeLog {
+    pub fn totals(&self) -> BTreeMap<String, TrackedTime> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+fn get_log_path(app: &AppHandle) -> PathBuf {
+    storage::get_app_data_dir(app).join("tracked_time.json")
+}
+
+pub fn load(app: &AppHandle) -> Result<(), String> {
+    let path = get_log_path(app);
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read tracked time: {}", e))?;
+
+    let totals: BTreeMap<String, TrackedTime> = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse tracked time: {}", e))?;
+
+    *app.state::<TimeLog>().0.lock().unwrap() = totals;
+    Ok(())
+}
+
+/// Adds a finished timer run to the task's total.
+pub fn record(app: &AppHandle, task_id: &str, seconds: i64) {
+    if seconds < MIN_SESSION_SECONDS {
+        return;
+    }
+
+    let log = app.state::<TimeLog>();
+    let mut totals = log.0.lock().unwrap();
+    let entry = totals.entry(task_id.to_string()).or_default();
+    entry.seconds += seconds;
+    entry.sessions += 1;
+    entry.last_tracked_at = now_iso();
+
+    let saved = serde_json::to_string_pretty(&*totals)
+        .map_err(|e| e.to_string())
+        .and_then(|content| {
+            disk::write_atomic(&get_log_path(app), content.as_bytes()).map_err(|e| e.to_string())
+        });
+    if let Err(e) = saved {
+        log::error(app, format!("Failed to save tracked time: {}", e));
+    }
+}
+
+#[tauri::command]
+pub fn get_tracked_time(app: AppHandle, task_id: String) -> Option<TrackedTime> {
+    app.state::<TimeLog>().0.lock().unwrap().get(&task_id).cloned()
+}
@@ -0,0 +1,552 @@
+//! Exporting tasks to a file and importing them back, with progress reported
+//! to the frontend so large files don't look like a hang.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::disk;
+use crate::duplicates::normalize_title;
+use crate::files::{self, FilePurpose};
+use crate::jobs::{self, JobContext};
+use crate::reminders::ReminderScheduler;
+use crate::storage::{self, TaskData};
+use crate::tasks::{self, str_field};
+use crate::telemetry;
+
+/// Emitted while an export or import job runs.
+pub const TRANSFER_PROGRESS_EVENT: &str = "transfer-progress";
+
+const CHUNK_SIZE: usize = 256 * 1024;
+
+/// Progress events are throttled to about this often.
+const EMIT_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransferOperation {
+    Export,
+    Import,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransferPhase {
+    Reading,
+    Merging,
+    Writing,
+    Done,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferProgress {
+    pub job_id: String,
+    pub operation: TransferOperation,
+    pub phase: TransferPhase,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub tasks_done: usize,
+    pub tasks_total: usize,
+    /// Estimated seconds left, once enough has been done to guess
+    pub eta_seconds: Option<u64>,
+}
+
+/// How an imported task is matched to an existing one, so re-running an
+/// import updates tasks instead of copying them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DedupKey {
+    /// The Afterglow task id
+    Id,
+    /// The id in the system the task came from (`externalId`)
+    ExternalId,
+    /// Same title, ignoring case and punctuation, and same due date
+    TitleDue,
+}
+
+impl DedupKey {
+    fn value(self, task: &Value) -> Option<String> {
+        match self {
+            DedupKey::Id => tasks::task_id(task).map(str::to_string),
+            DedupKey::ExternalId => str_field(task, "externalId")
+                .filter(|id| !id.is_empty())
+                .map(str::to_string),
+            DedupKey::TitleDue => {
+                let title = normalize_title(str_field(task, "title")?);
+                let due = str_field(task, "dueDate").unwrap_or("");
+                (!title.is_empty()).then(|| format!("{}|{}", title, due))
+            }
+        }
+    }
+}
+
+const DEFAULT_DEDUP_KEYS: [DedupKey; 2] = [DedupKey::Id, DedupKey::ExternalId];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    pub added: usize,
+    pub updated: usize,
+    /// Tasks matching one earlier in the same file, which were left out
+    pub duplicates: usize,
+}
+
+struct ProgressReporter<'a> {
+    job: &'a JobContext,
+    operation: TransferOperation,
+    started: Instant,
+    last_emit: Option<Instant>,
+}
+
+impl<'a> ProgressReporter<'a> {
+    fn new(job: &'a JobContext, operation: TransferOperation) -> Self {
+        Self {
+            job,
+            operation,
+            started: Instant::now(),
+            last_emit: None,
+        }
+    }
+
+    /// Emits progress unless one went out very recently. Phase changes and
+    /// completion always go out.
+    fn report(
+        &mut self,
+        phase: TransferPhase,
+        (bytes_done, bytes_total): (u64, u64),
+        (tasks_done, tasks_total): (usize, usize),
+        force: bool,
+    ) {
+        let now = Instant::now();
+        if !force && self.last_emit.is_some_and(|last| now - last < EMIT_INTERVAL) {
+            return;
+        }
+        self.last_emit = Some(now);
+
+        // Bytes are the better measure while reading or writing; tasks while merging
+        let fraction = match phase {
+            TransferPhase::Merging if tasks_total > 0 => tasks_done as f64 / tasks_total as f64,
+            _ if bytes_total > 0 => bytes_done as f64 / bytes_total as f64,
+            _ => 0.0,
+        };
+        let eta_seconds = (fraction > 0.0 && fraction < 1.0).then(|| {
+            let elapsed = self.started.elapsed().as_secs_f64();
+            (elapsed / fraction - elapsed).round() as u64
+        });
+
+        self.job.set_progress(fraction);
+        self.job
+            .app
+            .emit(
+                TRANSFER_PROGRESS_EVENT,
+                TransferProgress {
+                    job_id: self.job.id.clone(),
+                    operation: self.operation,
+                    phase,
+                    bytes_done,
+                    bytes_total,
+                    tasks_done,
+                    tasks_total,
+                    eta_seconds,
+                },
+            )
+            .ok();
+    }
+}
+
+/// Starts writing the current tasks to `export_path`, which must have been
+/// chosen with `choose_file`, and returns the job id.
+#[tauri::command]
+pub fn export_tasks(app: AppHandle, export_path: String) -> Result<String, String> {
+    if !storage::has_saved_data(&app) {
+        return Err("No saved tasks to export".to_string());
+    }
+    let export_path = files::take_grant(&app, &export_path, FilePurpose::Export)?;
+    telemetry::record(&app, "export");
+
+    Ok(jobs::spawn(&app, "export", move |job| export(job, &export_path)))
+}
+
+fn export(job: &JobContext, export_path: &Path) -> Result<(), String> {
+    let app = &job.app;
+    let mut progress = ProgressReporter::new(job, TransferOperation::Export);
+
+    // Goes through read_task_data so a save still held locally is included
+    let data = storage::read_task_data(app)?;
+    let content = serde_json::to_string_pretty(&data)
+        .map_err(|e| format!("Failed to serialize tasks: {}", e))?;
+    let tasks = (data.tasks.len(), data.tasks.len());
+    let total = content.len() as u64;
+
+    // Written next to the destination and renamed into place, so a failed
+    // or cancelled export leaves an existing file as it was
+    let temp_path = disk::temp_path_for(export_path);
+    let result = File::create(&temp_path)
+        .map_err(|e| disk::describe_io_error("Failed to export tasks", &e))
+        .and_then(|mut file| {
+            let mut written = 0;
+            for chunk in content.as_bytes().chunks(CHUNK_SIZE) {
+                job.cancel.check()?;
+                file.write_all(chunk)
+                    .map_err(|e| disk::describe_io_error("Failed to export tasks", &e))?;
+                written += chunk.len() as u64;
+                progress.report(TransferPhase::Writing, (written, total), tasks, false);
+            }
+            file.sync_all()
+                .map_err(|e| disk::describe_io_error("Failed to export tasks", &e))
+        })
+        .and_then(|_| {
+            disk::retry_io(|| fs::rename(&temp_path, export_path))
+                .map_err(|e| disk::describe_io_error("Failed to export tasks", &e))
+        });
+
+    if let Err(e) = result {
+        fs::remove_file(&temp_path).ok();
+        return Err(e);
+    }
+
+    progress.report(TransferPhase::Done, (total, total), tasks, true);
+    Ok(())
+}
+
+/// Starts merging an exported file into the current tasks and returns the
+/// job id. An imported task replaces the first existing one that matches on
+/// `dedup_keys`, tried in order (by default id, then external id); everything
+/// else is added. A task matching one earlier in the same file is left out and
+/// counted as a duplicate. Cancelling before the merge is saved leaves tasks
+/// as they were.
+#[tauri::command]
+pub fn import_tasks(
+    app: AppHandle,
+    import_path: String,
+    dedup_keys: Option<Vec<DedupKey>>,
+) -> Result<String, String> {
+    let import_path = files::take_grant(&app, &import_path, FilePurpose::Import)?;
+    let dedup_keys = dedup_keys.unwrap_or_else(|| DEFAULT_DEDUP_KEYS.to_vec());
+    telemetry::record(&app, "import");
+    Ok(jobs::spawn(&app, "import", move |job| import(job, &import_path, &dedup_keys)))
+}
+
+fn import(job: &JobContext, import_path: &Path, dedup_keys: &[DedupKey]) -> Result<ImportSummary, String> {
+    let app = &job.app;
+    let mut progress = ProgressReporter::new(job, TransferOperation::Import);
+
+    let content = read_with_progress(import_path, &mut progress)?;
+    let total = content.len() as u64;
+
+    let imported: TaskData = serde_json::from_slice(&content)
+        .map_err(|e| format!("Failed to parse import file: {}", e))?;
+
+    let (summary, tasks) = storage::update_task_data(app, |data| {
+        let summary = merge(data, imported, dedup_keys, |done, tasks_total| {
+            progress.report(TransferPhase::Merging, (total, total), (done, tasks_total), false);
+            job.cancel.check()
+        })?;
+
+        // Past this point the save goes ahead
+        job.cancel.check()?;
+        let tasks = (data.tasks.len(), data.tasks.len());
+        progress.report(TransferPhase::Writing, (total, total), tasks, true);
+        Ok((summary, tasks))
+    })?;
+
+    tasks::notify_changed(app, None);
+    app.state::<ReminderScheduler>().reschedule();
+
+    progress.report(TransferPhase::Done, (total, total), tasks, true);
+    Ok(summary)
+}
+
+fn read_with_progress(path: &Path, progress: &mut ProgressReporter) -> Result<Vec<u8>, String> {
+    let read_error = |e: std::io::Error| disk::describe_io_error("Failed to read import file", &e);
+
+    let mut file = disk::retry_io(|| File::open(path)).map_err(read_error)?;
+    let total = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+    let mut content = Vec::with_capacity(total as usize);
+    let mut buffer = vec![0; CHUNK_SIZE];
+    loop {
+        progress.job.cancel.check()?;
+        let read = disk::retry_io(|| file.read(&mut buffer)).map_err(read_error)?;
+        if read == 0 {
+            break;
+        }
+        content.extend_from_slice(&buffer[..read]);
+        progress.report(TransferPhase::Reading, (content.len() as u64, total), (0, 0), false);
+    }
+
+    Ok(content)
+}
+
+fn merge(
+    data: &mut TaskData,
+    imported: TaskData,
+    dedup_keys: &[DedupKey],
+    mut on_progress: impl FnMut(usize, usize) -> Result<(), String>,
+) -> Result<ImportSummary, String> {
+    let mut summary = ImportSummary { added: 0, updated: 0, duplicates: 0 };
+    let mut index: HashMap<(DedupKey, String), usize> = HashMap::new();
+    for (i, task) in data.tasks.iter().enumerate() {
+        index_task(&mut index, dedup_keys, task, i);
+    }
+    // Positions this import already wrote, so a second task in the file
+    // with the same key doesn't overwrite the first
+    let mut claimed = HashSet::new();
+
+    let total = imported.tasks.len();
+    for (done, mut task) in imported.tasks.into_iter().enumerate() {
+        let existing = dedup_keys
+            .iter()
+            .find_map(|&key| index.get(&(key, key.value(&task)?)).copied());
+        match existing {
+            Some(i) if claimed.contains(&i) => summary.duplicates += 1,
+            Some(i) => {
+                // Matched on another key: keep the id other data refers to
+                if let Some(id) = tasks::task_id(&data.tasks[i]) {
+                    task["id"] = Value::String(id.to_string());
+                }
+                index_task(&mut index, dedup_keys, &task, i);
+                data.tasks[i] = task;
+                claimed.insert(i);
+                summary.updated += 1;
+            }
+            None => {
+                index_task(&mut index, dedup_keys, &task, data.tasks.len());
+                claimed.insert(data.tasks.len());
+                data.tasks.push(task);
+                summary.added += 1;
+            }
+        }
+        on_progress(done + 1, total)?;
+    }
+
+    merge_names(&mut data.labels, imported.labels);
+    merge_names(&mut data.stakeholders, imported.stakeholders);
+
+    Ok(summary)
+}
+
+/// Points each of the task's keys at position `i`, keeping earlier entries.
+fn index_task(index: &mut HashMap<(DedupKey, String), usize>, keys: &[DedupKey], task: &Value, i: usize) {
+    for &key in keys {
+        if let Some(value) = key.value(task) {
+            index.entry((key, value)).or_insert(i);
+        }
+    }
+}
+
+fn merge_names(existing: &mut Vec<String>, imported: Vec<String>) {
+    for name in imported {
+        if !existing.contains(&name) {
+            existing.push(name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{MemoryStorage, Storage};
+    use serde_json::json;
+
+    fn task(id: &str, title: &str, external_id: Option<&str>) -> Value {
+        let mut task = json!({ "id": id, "title": title, "status": "not-started" });
+        if let Some(external_id) = external_id {
+            task["externalId"] = json!(external_id);
+        }
+        task
+    }
+
+    fn data(tasks: Vec<Value>, labels: &[&str]) -> TaskData {
+        TaskData {
+            tasks,
+            labels: labels.iter().map(|l| l.to_string()).collect(),
+            stakeholders: Vec::new(),
+        }
+    }
+
+    /// Loads from `store`, merges `imported` and saves, as an import does.
+    fn import_into(store: &dyn Storage, imported: TaskData, keys: &[DedupKey]) -> ImportSummary {
+        let mut current = store.load().unwrap();
+        let summary = merge(&mut current, imported, keys, |_, _| Ok(())).unwrap();
+        store.save(&current).unwrap();
+        summary
+    }
+
+    #[test]
+    fn import_into_empty_store_adds_everything() {
+        let store = MemoryStorage::default();
+        let imported = data(vec![task("a", "One", None), task("b", "Two", None)], &["home"]);
+        let summary = import_into(&store, imported, &DEFAULT_DEDUP_KEYS);
+
+        assert_eq!((summary.added, summary.updated), (2, 0));
+        let saved = store.load().unwrap();
+        assert_eq!(saved.tasks.len(), 2);
+        assert_eq!(saved.labels, vec!["home"]);
+    }
+
+    #[test]
+    fn match_on_external_id_keeps_the_existing_id() {
+        let store = MemoryStorage::default();
+        store.save(&data(vec![task("a", "Old title", Some("gh-1"))], &[])).unwrap();
+
+        let imported = data(vec![task("z", "New title", Some("gh-1"))], &[]);
+        let summary = import_into(&store, imported, &DEFAULT_DEDUP_KEYS);
+
+        assert_eq!((summary.added, summary.updated), (0, 1));
+        let saved = store.load().unwrap();
+        assert_eq!(saved.tasks.len(), 1);
+        assert_eq!(str_field(&saved.tasks[0], "id"), Some("a"));
+        assert_eq!(str_field(&saved.tasks[0], "title"), Some("New title"));
+    }
+
+    #[test]
+    fn title_and_due_ignores_case_and_punctuation() {
+        let store = MemoryStorage::default();
+        let mut existing = task("a", "Call the bank!", None);
+        existing["dueDate"] = json!("2026-10-14");
+        store.save(&data(vec![existing], &[])).unwrap();
+
+        let mut imported = task("b", "call the bank", None);
+        imported["dueDate"] = json!("2026-10-14");
+        let summary = import_into(&store, data(vec![imported], &[]), &[DedupKey::TitleDue]);
+
+        assert_eq!((summary.added, summary.updated), (0, 1));
+    }
+
+    #[test]
+    fn a_repeated_task_in_one_file_is_left_out() {
+        let store = MemoryStorage::default();
+        store.save(&data(vec![task("a", "Old title", Some("gh-1"))], &[])).unwrap();
+
+        let imported = data(
+            vec![
+                task("x", "First", Some("gh-1")),
+                task("y", "Second", Some("gh-1")),
+                task("n", "New", Some("gh-2")),
+                task("m", "New again", Some("gh-2")),
+            ],
+            &[],
+        );
+        let summary = import_into(&store, imported, &DEFAULT_DEDUP_KEYS);
+
+        assert_eq!((summary.added, summary.updated, summary.duplicates), (1, 1, 2));
+        let saved = store.load().unwrap();
+        assert_eq!(saved.tasks.len(), 2);
+        assert_eq!(str_field(&saved.tasks[0], "title"), Some("First"));
+        assert_eq!(str_field(&saved.tasks[1], "title"), Some("New"));
+    }
+
+    #[test]
+    fn cancelling_leaves_the_store_untouched() {
+        let store = MemoryStorage::default();
+        store.save(&data(vec![task("a", "One", None)], &[])).unwrap();
+
+        // The same save path as `import`, with the cancel on the first task
+        let mut cached = None;
+        let imported = data(vec![task("b", "Two", None), task("c", "Three", None)], &["new"]);
+        let result = storage::update_cached(&store, &mut cached, |current| {
+            merge(current, imported, &DEFAULT_DEDUP_KEYS, |_, _| Err("Cancelled".to_string()))
+        });
+
+        assert_eq!(result.unwrap_err(), "Cancelled");
+        let saved = store.load().unwrap();
+        assert_eq!(saved.tasks.len(), 1);
+        assert!(saved.labels.is_empty());
+        assert_eq!(cached.unwrap().tasks.len(), 1);
+    }
+
+    /// xorshift, so the generated cases are the same on every run.
+    struct Cases(u64);
+
+    impl Cases {
+        fn next(&mut self, n: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % n
+        }
+
+        /// Ids and external ids are unique within one file, as in an export,
+        /// but overlap between files.
+        fn task_data(&mut self) -> TaskData {
+            let mut ids: Vec<u64> = (0..12).filter(|_| self.next(2) == 0).collect();
+            let mut external_ids: Vec<u64> = (0..12).collect();
+            let tasks = ids
+                .drain(..)
+                .map(|id| {
+                    let external_id = match self.next(2) {
+                        0 if !external_ids.is_empty() => {
+                            let i = self.next(external_ids.len() as u64) as usize;
+                            Some(format!("x{}", external_ids.swap_remove(i)))
+                        }
+                        _ => None,
+                    };
+                    task(&format!("t{}", id), &format!("Task {}", self.next(5)), external_id.as_deref())
+                })
+                .collect();
+            let labels: Vec<String> = (0..self.next(4)).map(|i| format!("label{}", i)).collect();
+            TaskData { tasks, labels, stakeholders: Vec::new() }
+        }
+    }
+
+    #[test]
+    fn importing_an_export_of_the_store_changes_nothing() {
+        let mut cases = Cases(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..500 {
+            let store = MemoryStorage::default();
+            store.save(&cases.task_data()).unwrap();
+            let export = store.load().unwrap();
+
+            let summary = import_into(&store, export.clone(), &DEFAULT_DEDUP_KEYS);
+
+            assert_eq!((summary.added, summary.updated), (0, export.tasks.len()));
+            assert_eq!(store.load().unwrap().tasks, export.tasks);
+        }
+    }
+
+    #[test]
+    fn importing_twice_by_id_changes_nothing_the_second_time() {
+        let mut cases = Cases(0x5851_f42d_4c95_7f2d);
+        for _ in 0..500 {
+            let store = MemoryStorage::default();
+            store.save(&cases.task_data()).unwrap();
+            let imported = cases.task_data();
+
+            let first = import_into(&store, imported.clone(), &[DedupKey::Id]);
+            let after_first = store.load().unwrap();
+            let second = import_into(&store, imported.clone(), &[DedupKey::Id]);
+            let after_second = store.load().unwrap();
+
+            assert_eq!(first.added + first.updated, imported.tasks.len());
+            assert_eq!((second.added, second.updated), (0, imported.tasks.len()));
+            assert_eq!(after_first.tasks, after_second.tasks);
+            assert_eq!(after_first.labels, after_second.labels);
+        }
+    }
+
+    #[test]
+    fn merge_never_loses_existing_tasks_or_names() {
+        let mut cases = Cases(0x2545_f491_4f6c_dd1d);
+        for _ in 0..500 {
+            let store = MemoryStorage::default();
+            let existing = cases.task_data();
+            store.save(&existing).unwrap();
+            let imported = cases.task_data();
+
+            let summary = import_into(&store, imported.clone(), &DEFAULT_DEDUP_KEYS);
+            let saved = store.load().unwrap();
+
+            assert_eq!(saved.tasks.len(), existing.tasks.len() + summary.added);
+            for label in existing.labels.iter().chain(&imported.labels) {
+                assert!(saved.labels.contains(label));
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! System tray icon. Besides showing/quitting the app, the tray menu carries
+//! the actions for the latest reminder so it can be handled without the main
+//! window.
+
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::reminders::{self, FiredReminder, ReminderScheduler, SnoozeOption};
+use crate::windows;
+
+const TRAY_ID: &str = "main";
+
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let menu = build_menu(app, None)?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("Afterglow")
+        .menu(&menu)
+        .on_menu_event(handle_menu_event);
+
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+
+    builder.build(app)?;
+    Ok(())
+}
+
+/// Rebuilds the tray menu so it reflects the current active reminder.
+pub fn refresh(app: &AppHandle) {
+    let active = app.state::<ReminderScheduler>().active_reminder();
+
+    if let (Some(tray), Ok(menu)) = (app.tray_by_id(TRAY_ID), build_menu(app, active.as_ref())) {
+        tray.set_menu(Some(menu)).ok();
+    }
+}
+
+fn build_menu(app: &AppHandle, reminder: Option<&FiredReminder>) -> tauri::Result<Menu<Wry>> {
+    let menu = Menu::new(app)?;
+
+    if let Some(reminder) = reminder {
+        menu.append_items(&[
+            &MenuItem::with_id(app, "reminder-title", &reminder.title, false, None::<&str>)?,
+            &MenuItem::with_id(app, "snooze-10m", "Snooze 10 minutes", true, None::<&str>)?,
+            &MenuItem::with_id(app, "snooze-1h", "Snooze 1 hour", true, None::<&str>)?,
+            &MenuItem::with_id(app, "snooze-tomorrow", "Snooze until tomorrow", true, None::<&str>)?,
+            &MenuItem::with_id(app, "reminder-done", "Mark done", true, None::<&str>)?,
+            &MenuItem::with_id(app, "reminder-ack", "Stop reminding", true, None::<&str>)?,
+            &PredefinedMenuItem::separator(app)?,
+        ])?;
+    }
+
+    menu.append_items(&[
+        &MenuItem::with_id(app, "show", "Show Afterglow", true, None::<&str>)?,
+        &MenuItem::with_id(app, "widget", "Toggle mini widget", true, None::<&str>)?,
+        &MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?,
+    ])?;
+
+    Ok(menu)
+}
+
+enum ReminderMenuAction {
+    Snooze(SnoozeOption),
+    Done,
+    Acknowledge,
+}
+
+fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    let action = match event.id().as_ref() {
+        "snooze-10m" => ReminderMenuAction::Snooze(SnoozeOption::TenMinutes),
+        "snooze-1h" => ReminderMenuAction::Snooze(SnoozeOption::OneHour),
+        "snooze-tomorrow" => ReminderMenuAction::Snooze(SnoozeOption::Tomorrow),
+        "reminder-done" => ReminderMenuAction::Done,
+        "reminder-ack" => ReminderMenuAction::Acknowledge,
+        "show" => {
+            show_main_window(app);
+            return;
+        }
+        "widget" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                windows::toggle_widget(&app).await.ok();
+            });
+            return;
+        }
+        "quit" => {
+            app.exit(0);
+            return;
+        }
+        _ => return,
+    };
+
+    let Some(reminder) = app.state::<ReminderScheduler>().active_reminder() else {
+        return;
+    };
+
+    match action {
+        ReminderMenuAction::Snooze(option) => reminders::snooze(app, &reminder.task_id, option).map(|_| ()),
+        ReminderMenuAction::Done => reminders::complete(app, &reminder.task_id),
+        ReminderMenuAction::Acknowledge => {
+            reminders::acknowledge(app, &reminder.task_id);
+            Ok(())
+        }
+    }
+    .ok();
+}
+
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        window.show().ok();
+        window.unminimize().ok();
+        window.set_focus().ok();
+    }
+}
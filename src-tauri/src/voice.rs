@@ -0,0 +1,108 @@
+//! Voice capture: a recording is transcribed by a local whisper.cpp install
+//! and becomes a quick-add task, so nothing leaves the computer. The program
+//! and model are set in `settings.voice`; until then the feature is off.
+//!
+//! Recordings are files picked with `choose_file` for `FilePurpose::Audio`.
+//!
+//! All of this is behind the `voice` feature (on by default). Without it,
+//! `transcribe_to_task` only says so.
+
+#[cfg(feature = "voice")]
+use serde_json::json;
+#[cfg(feature = "voice")]
+use std::path::Path;
+#[cfg(feature = "voice")]
+use std::process::Command;
+use tauri::AppHandle;
+
+#[cfg(feature = "voice")]
+use crate::files::{self, FilePurpose};
+#[cfg(feature = "voice")]
+use crate::jobs::{self, JobContext};
+#[cfg(feature = "voice")]
+use crate::settings::{self, VoiceSettings};
+#[cfg(feature = "voice")]
+use crate::tasks::{self, QuickAddResult};
+
+#[cfg(feature = "voice")]
+const MAX_TITLE_CHARS: usize = 120;
+
+/// Runs whisper.cpp on `audio` and returns the transcript as one line.
+#[cfg(feature = "voice")]
+fn transcribe(voice: &VoiceSettings, audio: &Path) -> Result<String, String> {
+    let mut command = Command::new(&voice.whisper_path);
+    command
+        .arg("--model")
+        .arg(&voice.model_path)
+        .arg("--file")
+        .arg(audio)
+        .args(["--language", voice.language.as_str(), "--no-timestamps", "--no-prints"]);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW, so no console flashes up
+        command.creation_flags(0x0800_0000);
+    }
+
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to run whisper.cpp: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or("no output");
+        return Err(format!("whisper.cpp failed: {}", reason.trim()));
+    }
+
+    // Silence and noise come out as markers like [BLANK_AUDIO] or (music)
+    let transcript = String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("[BLANK_AUDIO]", "");
+    let transcript = transcript.trim();
+    if transcript.is_empty() {
+        return Err("No speech was found in the recording".to_string());
+    }
+    Ok(transcript.to_string())
+}
+
+/// The first sentence becomes the title; a longer transcript also goes in
+/// the notes.
+#[cfg(feature = "voice")]
+fn to_task(transcript: &str) -> serde_json::Value {
+    let first_sentence = transcript
+        .find(['.', '!', '?'])
+        .map(|end| &transcript[..end])
+        .unwrap_or(transcript)
+        .trim();
+    let title: String = first_sentence.chars().take(MAX_TITLE_CHARS).collect();
+
+    let mut task = json!({ "title": title });
+    if title.len() < transcript.trim_end_matches(['.', '!', '?']).len() {
+        task["notes"] = json!(transcript);
+    }
+    task
+}
+
+/// Transcribes a recording into a new task on the job queue and returns the
+/// job id. The job's result is the `QuickAddResult`.
+#[cfg(feature = "voice")]
+#[tauri::command]
+pub fn transcribe_to_task(app: AppHandle, path: String) -> Result<String, String> {
+    let voice = settings::current(&app).voice;
+    if voice.whisper_path.is_empty() || voice.model_path.is_empty() {
+        return Err("Set up whisper.cpp in settings to use voice capture".to_string());
+    }
+    let audio = files::take_grant(&app, &path, FilePurpose::Audio)?;
+
+    Ok(jobs::spawn(&app, "transcription", move |job: &JobContext| -> Result<QuickAddResult, String> {
+        let transcript = transcribe(&voice, &audio)?;
+        tasks::add_task(&job.app, to_task(&transcript), None)
+    }))
+}
+
+#[cfg(not(feature = "voice"))]
+#[tauri::command]
+pub fn transcribe_to_task(_app: AppHandle, _path: String) -> Result<String, String> {
+    Err("This build of Afterglow was made without voice capture".to_string())
+}
@@ -0,0 +1,109 @@
+//! Extra windows beside the main board: a focused Today window, detached
+//! task details and the always-on-top mini widget. They load the same frontend with a query string picking
+//! the view, and share the backend's task data with every other window.
+
+use serde::Deserialize;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::http;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum WindowView {
+    Today,
+    #[serde(rename_all = "camelCase")]
+    Task { task_id: String },
+}
+
+impl WindowView {
+    /// One window per view; opening it again focuses the existing one.
+    fn label(&self) -> String {
+        match self {
+            WindowView::Today => "today".to_string(),
+            // Labels only allow a few characters; task ids are uuids anyway
+            WindowView::Task { task_id } => format!(
+                "task-{}",
+                task_id
+                    .chars()
+                    .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+                    .collect::<String>()
+            ),
+        }
+    }
+
+    fn url(&self) -> String {
+        match self {
+            WindowView::Today => "index.html?view=today".to_string(),
+            WindowView::Task { task_id } => {
+                format!("index.html?task={}", http::encode_path_segment(task_id))
+            }
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            WindowView::Today => "Afterglow — Today",
+            WindowView::Task { .. } => "Afterglow — Switchback",
+        }
+    }
+
+    fn size(&self) -> (f64, f64) {
+        match self {
+            WindowView::Today => (480.0, 720.0),
+            WindowView::Task { .. } => (560.0, 640.0),
+        }
+    }
+}
+
+// Window-creating commands are async: a sync command runs on the main
+// thread, and building a webview there deadlocks on Windows.
+
+#[tauri::command]
+pub async fn open_window(app: AppHandle, view: WindowView) -> Result<(), String> {
+    let label = view.label();
+
+    if let Some(window) = app.get_webview_window(&label) {
+        window.unminimize().ok();
+        return window
+            .set_focus()
+            .map_err(|e| format!("Failed to focus window: {}", e));
+    }
+
+    let (width, height) = view.size();
+    WebviewWindowBuilder::new(&app, label, WebviewUrl::App(view.url().into()))
+        .title(view.title())
+        .inner_size(width, height)
+        .min_inner_size(360.0, 400.0)
+        .build()
+        .map_err(|e| format!("Failed to open window: {}", e))?;
+
+    Ok(())
+}
+
+const WIDGET_LABEL: &str = "widget";
+
+/// Shows the mini widget, or closes it if it's open.
+pub async fn toggle_widget(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(WIDGET_LABEL) {
+        return window
+            .close()
+            .map_err(|e| format!("Failed to close widget: {}", e));
+    }
+
+    WebviewWindowBuilder::new(app, WIDGET_LABEL, WebviewUrl::App("index.html?view=widget".into()))
+        .title("Afterglow — Focus")
+        .inner_size(300.0, 110.0)
+        .resizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .build()
+        .map_err(|e| format!("Failed to open widget: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn toggle_widget_window(app: AppHandle) -> Result<(), String> {
+    toggle_widget(&app).await
+}
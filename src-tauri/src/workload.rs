@@ -0,0 +1,162 @@
+//! Workload balancing: how much open, estimated work each stakeholder has,
+//! and which tasks could move to even it out. Only suggestions are made;
+//! each move carries the task's new `stakeholders` for the UI to apply.
+//!
+//! A task is movable when it has exactly one stakeholder, isn't recurring
+//! and hasn't been started, so work someone is already on stays put.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+
+use crate::storage::{self, TaskData};
+use crate::tasks::{self, str_field, task_id};
+
+/// Minutes assumed for an open task without an estimate.
+const UNESTIMATED_MINUTES: i64 = 30;
+
+/// Upper bound on suggestions, so a very uneven board isn't reshuffled at once.
+const MAX_MOVES: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StakeholderLoad {
+    pub stakeholder: String,
+    pub tasks: usize,
+    pub minutes: i64,
+    /// Tasks counted at `UNESTIMATED_MINUTES`
+    pub unestimated: usize,
+    /// Minutes after the proposed moves
+    pub projected_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposedMove {
+    pub task_id: String,
+    pub title: String,
+    pub from: String,
+    pub to: String,
+    pub minutes: i64,
+    /// The task's `stakeholders` after the move
+    pub stakeholders: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkloadPlan {
+    /// Most loaded first
+    pub loads: Vec<StakeholderLoad>,
+    pub moves: Vec<ProposedMove>,
+}
+
+struct Movable<'a> {
+    task: &'a Value,
+    owner: String,
+    minutes: i64,
+}
+
+fn minutes(task: &Value) -> Option<i64> {
+    task.get("estimatedMinutes").and_then(Value::as_i64).filter(|m| *m > 0)
+}
+
+fn is_movable(task: &Value) -> bool {
+    str_field(task, "type") != Some("recurring")
+        && matches!(str_field(task, "status"), Some("not-started") | Some("waiting") | None)
+}
+
+/// Plans moves among `pool`, or every known stakeholder when it's empty:
+/// each move takes the task from the busiest person that best halves the gap
+/// to the least busy one, until no move narrows it.
+fn plan(data: &TaskData, pool: &[String]) -> WorkloadPlan {
+    let people: Vec<String> = if pool.is_empty() { data.stakeholders.clone() } else { pool.to_vec() };
+    let mut loads: BTreeMap<String, StakeholderLoad> = people
+        .iter()
+        .map(|name| {
+            let load = StakeholderLoad {
+                stakeholder: name.clone(),
+                tasks: 0,
+                minutes: 0,
+                unestimated: 0,
+                projected_minutes: 0,
+            };
+            (name.clone(), load)
+        })
+        .collect();
+
+    let mut movable: Vec<Movable> = Vec::new();
+    for task in data.tasks.iter().filter(|t| tasks::is_open(t) && str_field(t, "status") != Some("someday")) {
+        let stakeholders: Vec<&str> = task
+            .get("stakeholders")
+            .and_then(Value::as_array)
+            .map(|names| names.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        let task_minutes = minutes(task).unwrap_or(UNESTIMATED_MINUTES);
+        for name in &stakeholders {
+            if let Some(load) = loads.get_mut(*name) {
+                load.tasks += 1;
+                load.minutes += task_minutes;
+                load.unestimated += usize::from(minutes(task).is_none());
+            }
+        }
+        if let [owner] = stakeholders[..] {
+            if loads.contains_key(owner) && is_movable(task) {
+                movable.push(Movable { task, owner: owner.to_string(), minutes: task_minutes });
+            }
+        }
+    }
+
+    let mut projected: BTreeMap<String, i64> = loads.iter().map(|(name, load)| (name.clone(), load.minutes)).collect();
+    let mut moves = Vec::new();
+    while moves.len() < MAX_MOVES && projected.len() > 1 {
+        let (Some((busiest, &high)), Some((idlest, &low))) = (
+            projected.iter().max_by_key(|(_, minutes)| **minutes),
+            projected.iter().min_by_key(|(_, minutes)| **minutes),
+        ) else {
+            break;
+        };
+        let gap = high - low;
+        // Moving m minutes narrows the gap only when m < gap; m = gap / 2 is best
+        let Some(index) = movable
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| &m.owner == busiest && m.minutes < gap)
+            .min_by_key(|(_, m)| (m.minutes * 2 - gap).abs())
+            .map(|(index, _)| index)
+        else {
+            break;
+        };
+
+        let (busiest, idlest) = (busiest.clone(), idlest.clone());
+        let candidate = movable.remove(index);
+        *projected.entry(busiest.clone()).or_default() -= candidate.minutes;
+        *projected.entry(idlest.clone()).or_default() += candidate.minutes;
+        moves.push(ProposedMove {
+            task_id: task_id(candidate.task).unwrap_or_default().to_string(),
+            title: str_field(candidate.task, "title").unwrap_or_default().to_string(),
+            from: busiest,
+            to: idlest.clone(),
+            minutes: candidate.minutes,
+            stakeholders: vec![idlest],
+        });
+    }
+
+    let mut loads: Vec<StakeholderLoad> = loads
+        .into_values()
+        .map(|mut load| {
+            load.projected_minutes = projected.get(&load.stakeholder).copied().unwrap_or(load.minutes);
+            load
+        })
+        .collect();
+    loads.sort_by_key(|load| std::cmp::Reverse(load.minutes));
+    WorkloadPlan { loads, moves }
+}
+
+/// Open load per stakeholder and suggested reassignments that even it out.
+/// `stakeholders` limits who work can move between; by default everyone.
+#[tauri::command]
+pub fn suggest_rebalancing(app: AppHandle, stakeholders: Option<Vec<String>>) -> Result<WorkloadPlan, String> {
+    let data = storage::read_task_data(&app)?;
+    Ok(plan(&data, &stakeholders.unwrap_or_default()))
+}
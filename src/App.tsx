@@ -1,4 +1,6 @@
-import { useEffect, useState, useCallback } from 'react';
+import { useEffect, useState, useCallback, useRef } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
 import { Sidebar } from './components/Sidebar';
 import { TodayView } from './components/TodayView';
 import { AllTasksView } from './components/AllTasksView';
@@ -12,11 +14,16 @@ import { Task } from './types/task';
 
 export type ViewType = 'today' | 'all' | 'week';
 
+// Extra windows open with ?view=today or ?task=<id>
+const params = new URLSearchParams(window.location.search);
+const initialView = (params.get('view') as ViewType | null) ?? 'today';
+const detachedTaskId = params.get('task');
+
 function App() {
-  const [currentView, setCurrentView] = useState<ViewType>('today');
+  const [currentView, setCurrentView] = useState<ViewType>(initialView);
   const [isCreateModalOpen, setIsCreateModalOpen] = useState(false);
   const [editingTask, setEditingTask] = useState<Task | null>(null);
-  const { loadTasks, isLoading } = useTaskStore();
+  const { tasks, loadTasks, refreshTasks, isLoading } = useTaskStore();
 
   useKeyboardShortcuts({
     onViewChange: setCurrentView,
@@ -26,6 +33,18 @@ function App() {
     loadTasks();
   }, [loadTasks]);
 
+  // Emitted when the backend or another window changes tasks
+  useEffect(() => {
+    if (!('__TAURI__' in window)) return;
+    const label = getCurrentWebviewWindow().label;
+    const unlisten = listen<{ source?: string | null }>('tasks-changed', (event) => {
+      if (event.payload?.source !== label) refreshTasks();
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, [refreshTasks]);
+
   const handleNavigateToDate = useCallback((date: Date) => {
     useTaskStore.getState().setSelectedDate(date);
     setCurrentView('today');
@@ -39,8 +58,23 @@ function App() {
   const handleCloseModal = useCallback(() => {
     setIsCreateModalOpen(false);
     setEditingTask(null);
+    // A detached task window is just the modal
+    if (detachedTaskId && '__TAURI__' in window) {
+      getCurrentWebviewWindow().close();
+    }
   }, []);
 
+  // Open the task a detached window was created for, once
+  const openedDetachedTask = useRef(false);
+  useEffect(() => {
+    if (isLoading || !detachedTaskId || openedDetachedTask.current) return;
+    const task = tasks.find(t => t.id === detachedTaskId);
+    if (task) {
+      openedDetachedTask.current = true;
+      handleEditTask(task);
+    }
+  }, [isLoading, tasks, handleEditTask]);
+
   const renderView = () => {
     switch (currentView) {
       case 'today':
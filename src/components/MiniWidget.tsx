@@ -0,0 +1,100 @@
+import { useCallback, useEffect, useState } from 'react';
+import { Check, Pause, Play, RotateCcw } from 'lucide-react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+interface WidgetTask {
+  id: string;
+  title: string;
+  priority: string;
+  dueDate?: string;
+  estimatedMinutes?: number;
+}
+
+interface WidgetState {
+  task?: WidgetTask;
+  pinned: boolean;
+  running: boolean;
+  elapsedSeconds: number;
+}
+
+function formatElapsed(seconds: number): string {
+  const h = Math.floor(seconds / 3600);
+  const m = Math.floor((seconds % 3600) / 60);
+  const s = seconds % 60;
+  const pad = (n: number) => n.toString().padStart(2, '0');
+  return h > 0 ? `${h}:${pad(m)}:${pad(s)}` : `${pad(m)}:${pad(s)}`;
+}
+
+/** Always-on-top widget showing the focus switchback and its timer. */
+export function MiniWidget() {
+  const [state, setState] = useState<WidgetState | null>(null);
+  const [elapsed, setElapsed] = useState(0);
+
+  const refresh = useCallback(async () => {
+    try {
+      const next = await invoke<WidgetState>('get_widget_state');
+      setState(next);
+      setElapsed(next.elapsedSeconds);
+    } catch (error) {
+      console.error('Failed to load widget state:', error);
+    }
+  }, []);
+
+  useEffect(() => {
+    refresh();
+    const unlisteners = [listen('focus-changed', refresh), listen('tasks-changed', refresh)];
+    return () => {
+      unlisteners.forEach((unlisten) => unlisten.then((fn) => fn()));
+    };
+  }, [refresh]);
+
+  // The backend owns the timer; tick locally between refreshes
+  useEffect(() => {
+    if (!state?.running) return;
+    const interval = setInterval(() => setElapsed((value) => value + 1), 1000);
+    return () => clearInterval(interval);
+  }, [state?.running]);
+
+  const task = state?.task;
+
+  return (
+    <div
+      data-tauri-drag-region
+      className="h-screen flex items-center gap-3 px-3 bg-board-elevated border border-board-border text-white select-none"
+    >
+      <div data-tauri-drag-region className="flex-1 min-w-0">
+        <p className="text-sm font-medium truncate">{task ? task.title : 'Nothing to focus on'}</p>
+        <p className="text-xs text-board-muted font-mono">
+          {formatElapsed(elapsed)}
+          {task?.estimatedMinutes ? ` / ${formatElapsed(task.estimatedMinutes * 60)}` : ''}
+        </p>
+      </div>
+      {task && (
+        <div className="flex items-center gap-1">
+          <button
+            onClick={() => invoke(state?.running ? 'pause_focus_timer' : 'start_focus_timer')}
+            className="p-1.5 rounded text-board-muted hover:text-accent-gold transition-colors"
+            title={state?.running ? 'Pause' : 'Start'}
+          >
+            {state?.running ? <Pause size={16} /> : <Play size={16} />}
+          </button>
+          <button
+            onClick={() => invoke('reset_focus_timer')}
+            className="p-1.5 rounded text-board-muted hover:text-gray-300 transition-colors"
+            title="Reset timer"
+          >
+            <RotateCcw size={16} />
+          </button>
+          <button
+            onClick={() => invoke('complete_focus_task', { taskId: task.id })}
+            className="p-1.5 rounded text-board-muted hover:text-green-400 transition-colors"
+            title="Mark done"
+          >
+            <Check size={16} />
+          </button>
+        </div>
+      )}
+    </div>
+  );
+}
@@ -1,7 +1,7 @@
 import { useState, useRef, useEffect } from 'react';
 import { Plus, Flag, RefreshCw, Tag, X, Clock } from 'lucide-react';
 import { useTaskStore } from '../stores/taskStore';
-import { Priority, TaskType, RecurrenceRule } from '../types/task';
+import { Priority, QuickAddResult, TaskType, RecurrenceRule } from '../types/task';
 import { PRIORITY_LABELS } from '../types/task';
 import { format, getDate, getDaysInMonth, getDay, startOfMonth, startOfYear, addDays } from 'date-fns';
 import { RecurrenceModal } from './RecurrenceModal';
@@ -26,11 +26,12 @@ export function QuickAdd({ defaultType = 'one-off' }: QuickAddProps) {
   const [newLabel, setNewLabel] = useState('');
   const [notes, setNotes] = useState('');
   const [estimatedMinutes, setEstimatedMinutes] = useState<string>('');
+  const [duplicateWarning, setDuplicateWarning] = useState<QuickAddResult | null>(null);
   
   const inputRef = useRef<HTMLInputElement>(null);
   const containerRef = useRef<HTMLDivElement>(null);
   const labelInputRef = useRef<HTMLInputElement>(null);
-  const { addTask } = useTaskStore();
+  const { addTask, quickAddTask, deleteTask } = useTaskStore();
 
   useEffect(() => {
     if (isExpanded && inputRef.current) {
@@ -213,7 +214,9 @@ export function QuickAdd({ defaultType = 'one-off' }: QuickAddProps) {
       }
     }
 
-    addTask(newTask);
+    quickAddTask(newTask).then((result) => {
+      setDuplicateWarning(result && result.possibleDuplicates.length > 0 ? result : null);
+    });
 
     // Reset form
     setTitle('');
@@ -250,16 +253,50 @@ export function QuickAdd({ defaultType = 'one-off' }: QuickAddProps) {
     return formatRecurrence(recurrenceRule);
   };
 
+  const duplicateNotice = duplicateWarning && (
+    <div className="mt-2 px-3 py-2 rounded-lg border border-accent-gold/30 bg-accent-gold/10 text-xs text-gray-300">
+      <div>This might already exist:</div>
+      <ul className="mt-1 text-board-muted">
+        {duplicateWarning.possibleDuplicates.map((candidate) => (
+          <li key={candidate.id}>
+            {candidate.title}
+            {candidate.dueDate && ` (due ${candidate.dueDate})`}
+          </li>
+        ))}
+      </ul>
+      <div className="flex gap-2 mt-2">
+        <button
+          onClick={() => {
+            deleteTask(duplicateWarning.task.id);
+            setDuplicateWarning(null);
+          }}
+          className="px-2 py-1 rounded-md bg-board-elevated hover:bg-board-border"
+        >
+          Remove new one
+        </button>
+        <button
+          onClick={() => setDuplicateWarning(null)}
+          className="px-2 py-1 rounded-md text-board-muted hover:text-gray-300"
+        >
+          Keep both
+        </button>
+      </div>
+    </div>
+  );
+
   if (!isExpanded) {
     return (
-      <button
-        onClick={() => setIsExpanded(true)}
-        className="w-full flex items-center gap-3 px-4 py-3 rounded-lg border border-dashed border-board-border
-          text-board-muted hover:text-gray-300 hover:border-board-muted transition-all"
-      >
-        <Plus size={18} />
-        <span className="text-sm">Add switchback...</span>
-      </button>
+      <>
+        <button
+          onClick={() => setIsExpanded(true)}
+          className="w-full flex items-center gap-3 px-4 py-3 rounded-lg border border-dashed border-board-border
+            text-board-muted hover:text-gray-300 hover:border-board-muted transition-all"
+        >
+          <Plus size={18} />
+          <span className="text-sm">Add switchback...</span>
+        </button>
+        {duplicateNotice}
+      </>
     );
   }
 
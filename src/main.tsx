@@ -1,11 +1,14 @@
 import React from 'react';
 import ReactDOM from 'react-dom/client';
 import App from './App';
+import { MiniWidget } from './components/MiniWidget';
 import './index.css';
 
+// The mini widget window needs none of the board, so it skips App entirely
+const isWidget = new URLSearchParams(window.location.search).get('view') === 'widget';
+
 ReactDOM.createRoot(document.getElementById('root')!).render(
   <React.StrictMode>
-    <App />
+    {isWidget ? <MiniWidget /> : <App />}
   </React.StrictMode>
 );
-
@@ -1,6 +1,6 @@
 import { create } from 'zustand';
 import { v4 as uuidv4 } from 'uuid';
-import { Task, TaskData, TaskStatus } from '../types/task';
+import { LoadedTaskData, QuickAddResult, Task, TaskData, TaskStatus } from '../types/task';
 import { invoke } from '@tauri-apps/api/core';
 import { getNextRecurrenceDate } from '../utils/recurrence';
 
@@ -11,12 +11,16 @@ interface TaskStore {
   isLoading: boolean;
   error: string | null;
   selectedDate: Date | null;
+  // Revision of the backend data this window last loaded or saved
+  revision: number;
 
   // Actions
   loadTasks: () => Promise<void>;
+  refreshTasks: () => Promise<void>;
   saveTasks: () => Promise<void>;
   
   addTask: (task: Partial<Task>) => void;
+  quickAddTask: (task: Partial<Task>) => Promise<QuickAddResult | null>;
   updateTask: (id: string, updates: Partial<Task>) => void;
   editTaskAction: (id: string, updates: Partial<Task>, originalTask: Task) => void;
   deleteTask: (id: string) => void;
@@ -61,7 +65,12 @@ const saveToLocalStorage = (data: TaskData) => {
   }
 };
 
+// Saves run one after another, so each goes out at the revision the previous
+// one returned
+let saveQueue: Promise<void> = Promise.resolve();
+
 export const useTaskStore = create<TaskStore>((set, get) => ({
+  revision: 0,
   tasks: [],
   labels: [],
   stakeholders: [],
@@ -73,9 +82,12 @@ export const useTaskStore = create<TaskStore>((set, get) => ({
     set({ isLoading: true, error: null });
     try {
       let data: TaskData;
+      let revision = 0;
       
       if (isTauri()) {
-        data = await invoke<TaskData>('load_tasks');
+        const loaded = await invoke<LoadedTaskData>('load_tasks');
+        data = loaded;
+        revision = loaded.revision;
       } else {
         data = loadFromLocalStorage();
       }
@@ -84,6 +96,7 @@ export const useTaskStore = create<TaskStore>((set, get) => ({
         tasks: data.tasks || [], 
         labels: data.labels || [], 
         stakeholders: data.stakeholders || [],
+        revision,
         isLoading: false 
       });
     } catch (error) {
@@ -92,22 +105,47 @@ export const useTaskStore = create<TaskStore>((set, get) => ({
     }
   },
 
-  saveTasks: async () => {
-    const { tasks, labels, stakeholders } = get();
-    const data: TaskData = { tasks, labels, stakeholders };
-    
+  // Reload after the backend changed the data (e.g. a reminder was snoozed
+  // from the tray) without flashing the loading screen
+  refreshTasks: async () => {
+    if (!isTauri()) return;
     try {
-      if (isTauri()) {
-        await invoke('save_tasks', { data });
-      } else {
-        saveToLocalStorage(data);
-      }
+      const data = await invoke<LoadedTaskData>('load_tasks');
+      set({ 
+        tasks: data.tasks || [], 
+        labels: data.labels || [], 
+        stakeholders: data.stakeholders || [],
+        revision: data.revision,
+      });
     } catch (error) {
-      console.error('Failed to save tasks:', error);
+      console.error('Failed to refresh tasks:', error);
       set({ error: String(error) });
     }
   },
 
+  saveTasks: () => {
+    saveQueue = saveQueue.then(async () => {
+      const { tasks, labels, stakeholders, revision } = get();
+      const data: TaskData = { tasks, labels, stakeholders };
+      
+      try {
+        if (isTauri()) {
+          const saved = await invoke<number>('save_tasks', { data, revision });
+          set({ revision: saved });
+        } else {
+          saveToLocalStorage(data);
+        }
+      } catch (error) {
+        // Refused because another window or the backend saved first: take
+        // their data rather than overwrite it
+        console.error('Failed to save tasks:', error);
+        set({ error: String(error) });
+        await get().refreshTasks();
+      }
+    });
+    return saveQueue;
+  },
+
   addTask: (taskData: Partial<Task>) => {
     const { tasks } = get();
     const maxSortOrder = tasks.reduce((max, t) => Math.max(max, t.sortOrder), 0);
@@ -127,6 +165,25 @@ export const useTaskStore = create<TaskStore>((set, get) => ({
     get().saveTasks();
   },
 
+  // Adds through the backend, which also reports near-duplicate open tasks
+  quickAddTask: async (taskData: Partial<Task>) => {
+    if (!isTauri()) {
+      get().addTask(taskData);
+      return null;
+    }
+    try {
+      const result = await invoke<QuickAddResult>('quick_add_task', { task: taskData });
+      // The backend may also have added labels and stakeholders (project
+      // defaults, remembered names), so take the whole list back from it
+      await get().refreshTasks();
+      return result;
+    } catch (error) {
+      console.error('Failed to add task:', error);
+      set({ error: String(error) });
+      return null;
+    }
+  },
+
   updateTask: (id: string, updates: Partial<Task>) => {
     const { tasks } = get();
     const updatedTasks = tasks.map(task =>
@@ -209,6 +266,7 @@ export const useTaskStore = create<TaskStore>((set, get) => ({
           parentRecurringId: task.parentRecurringId || task.id,
           sortOrder: maxSortOrder + 1,
           estimatedMinutes: task.estimatedMinutes, // Preserve time estimate for recurring tasks
+          habit: task.habit,
         };
         updatedTasks = [...updatedTasks, nextTask];
       }
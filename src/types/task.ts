@@ -47,6 +47,20 @@ export interface Task {
   parentRecurringId?: string;
   sortOrder: number;
   estimatedMinutes?: number;  // Optional time estimate in minutes
+  reminderAt?: string;        // ISO timestamp for a reminder notification
+  habit?: boolean;            // Recurring task tracked as a habit (per-day history and streaks)
+  dependsOn?: string[];       // Ids of tasks that must be done first
+  externalId?: string;        // Id in the system the task was imported from
+  checklist?: ChecklistItem[]; // Sub-steps, e.g. from a checklist template
+  project?: string;           // Project whose default settings apply
+  sourceUrl?: string;         // Web page the task was saved from
+  review?: boolean;           // Resurfaces in the review queue at growing intervals
+}
+
+export interface ChecklistItem {
+  id: string;
+  text: string;
+  done: boolean;
 }
 
 export interface TaskData {
@@ -55,6 +69,24 @@ export interface TaskData {
   stakeholders: string[];
 }
 
+// What load_tasks returns: the data and the revision to save it back at
+export interface LoadedTaskData extends TaskData {
+  revision: number;
+}
+
+// An open task whose title is close to one being added
+export interface DuplicateCandidate {
+  id: string;
+  title: string;
+  dueDate?: string;
+  similarity: number;
+}
+
+export interface QuickAddResult {
+  task: Task;
+  possibleDuplicates: DuplicateCandidate[];
+}
+
 // Priority weight for sorting (lower = higher priority)
 export const PRIORITY_WEIGHT: Record<Priority, number> = {
   'p0': 0,
@@ -0,0 +1,19 @@
+import { convertFileSrc } from '@tauri-apps/api/core';
+
+export interface AttachmentInfo {
+  name: string;
+  size: number;
+  mimeType: string;
+  hasThumbnail: boolean;
+}
+
+/**
+ * Builds an `afterglow-asset://` URL for a switchback's attachment.
+ * @param taskId - The task the file is attached to
+ * @param name - The attachment's file name
+ * @param kind - The original file, or its thumbnail
+ * @returns A URL usable in img/video/audio src attributes
+ */
+export function attachmentUrl(taskId: string, name: string, kind: 'file' | 'thumbnail' = 'file'): string {
+  return convertFileSrc(`${kind}/${taskId}/${name}`, 'afterglow-asset');
+}
@@ -0,0 +1,29 @@
+import { invoke } from '@tauri-apps/api/core';
+import { TaskData } from '../types/task';
+
+export type FilePurpose = 'import' | 'export' | 'attachment' | 'csvImport' | 'image' | 'audio' | 'email' | 'csvExport';
+
+export type UserFile =
+  | { kind: 'tasks'; data: TaskData }
+  | { kind: 'csv'; headers: string[]; rows: string[][] }
+  | { kind: 'image'; dataUrl: string };
+
+/**
+ * Shows a file dialog from the backend, which grants the picked path for one purpose.
+ * @param purpose - What the file will be used for; decides the allowed extensions
+ * @param suggestedName - Default file name for save dialogs
+ * @returns The chosen path, or null if the dialog was cancelled
+ */
+export function chooseFile(purpose: FilePurpose, suggestedName?: string): Promise<string | null> {
+  return invoke<string | null>('choose_file', { purpose, suggestedName });
+}
+
+/**
+ * Reads a file picked with chooseFile, parsed for its purpose.
+ * @param path - Path returned by chooseFile
+ * @param purpose - The purpose it was chosen for
+ * @returns Typed content; images come back as data URLs
+ */
+export function readUserFile(path: string, purpose: FilePurpose): Promise<UserFile> {
+  return invoke<UserFile>('read_user_file', { path, purpose });
+}